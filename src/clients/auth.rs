@@ -0,0 +1,337 @@
+use bevy::prelude::Resource;
+use bevy_ecs::system::ResMut;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use super::bans::normalize_ip;
+use crate::{
+    network::events::on_connection_info::PlayerConnectionInfoEvent,
+    utils::yaml_file::{YamlFile, YamlFileResource},
+};
+
+pub const AUTH_FILE: &str = "auth.yml";
+
+/// PBKDF2-HMAC-SHA256 rounds of stored password hashes
+const PASSWORD_ITERATIONS: u32 = 100_000;
+
+/// Client has to answer the challenge in time
+const AUTH_TIMEOUT: Duration = Duration::from_secs(30);
+pub const AUTH_EXPIRE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Same answer for unknown logins and wrong passwords, so logins can't be probed
+pub const AUTH_FAILED_MESSAGE: &str = "Wrong login or password";
+
+/// Failed attempts from one ip address allowed inside the window
+const MAX_AUTH_FAILURES: usize = 5;
+const AUTH_FAILURES_WINDOW: Duration = Duration::from_secs(60);
+
+/// How players prove the login
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthMode {
+    /// Login sent by the client is trusted
+    #[default]
+    Offline,
+
+    /// Login must have a password or a token from the `auth` command
+    Password,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Credential {
+    salt: String,
+    stored_key: String,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+struct AuthData {
+    // Key of fake challenges for unknown logins; generated on the first load
+    #[serde(default)]
+    secret: String,
+
+    #[serde(default)]
+    players: BTreeMap<String, Credential>,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(input: &str) -> Option<Vec<u8>> {
+    if input.len() % 2 != 0 {
+        return None;
+    }
+    (0..input.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(input.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn random_hex(len: usize) -> String {
+    let mut bytes = vec![0_u8; len];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    to_hex(&bytes)
+}
+
+/// Salted hash of the password; the client computes the same from the challenge salt
+pub fn hash_password(password: &str, salt: &str, iterations: u32) -> Vec<u8> {
+    let mut hash = [0_u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt.as_bytes(), iterations, &mut hash);
+    hash.to_vec()
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// `HMAC(hash, "Client Key")` like in SCRAM; only the client knows it
+pub fn get_client_key(password_hash: &[u8]) -> Vec<u8> {
+    hmac_sha256(password_hash, b"Client Key")
+}
+
+/// `H(ClientKey)` is stored instead of the password hash, so the file isn't enough to log in
+fn get_stored_key(client_key: &[u8]) -> Vec<u8> {
+    Sha256::digest(client_key).to_vec()
+}
+
+/// Stored keys of player passwords
+///
+/// Stored in `auth.yml` inside the server data folder. Passwords and their hashes are never stored or sent.
+#[derive(Resource, Default)]
+pub struct AuthCredentials {
+    data: YamlFile<AuthData>,
+}
+
+impl YamlFileResource for AuthCredentials {
+    const SPAN: &'static str = "clients.load_auth_credentials";
+    const LOG_TARGET: &'static str = "clients";
+
+    fn load(server_data_path: &Path) -> Result<Self, String> {
+        let mut auth = Self {
+            data: YamlFile::load(server_data_path, AUTH_FILE)?,
+        };
+        if auth.data.secret.is_empty() {
+            auth.data.secret = random_hex(32);
+            auth.data.save()?;
+        }
+        Ok(auth)
+    }
+}
+
+impl AuthCredentials {
+    pub fn has_credential(&self, login: &str) -> bool {
+        self.data.players.contains_key(login)
+    }
+
+    /// Replaces the password of the login
+    pub fn set_password(&mut self, login: &str, password: &str) -> Result<(), String> {
+        let salt = random_hex(16);
+        let client_key = get_client_key(&hash_password(password, &salt, PASSWORD_ITERATIONS));
+        let stored_key = to_hex(&get_stored_key(&client_key));
+        self.data
+            .players
+            .insert(login.to_string(), Credential { salt, stored_key });
+        self.data.save()
+    }
+
+    /// Generates a random password for the login and returns it
+    pub fn create_token(&mut self, login: &str) -> Result<String, String> {
+        let token = random_hex(16);
+        self.set_password(login, &token)?;
+        Ok(token)
+    }
+
+    /// Returns false if the login had no password
+    pub fn remove(&mut self, login: &str) -> Result<bool, String> {
+        let removed = self.data.players.remove(login).is_some();
+        if removed {
+            self.data.save()?;
+        }
+        Ok(removed)
+    }
+
+    /// Salt and iterations the client needs to hash its password
+    ///
+    /// Unknown logins get a fake salt derived from the server secret, so it's the same on every attempt
+    /// and the challenge doesn't tell whether the login is registered.
+    pub fn get_salt(&self, login: &str) -> (String, u32) {
+        let salt = match self.data.players.get(login) {
+            Some(credential) => credential.salt.clone(),
+            None => to_hex(&hmac_sha256(self.data.secret.as_bytes(), format!("salt:{}", login).as_bytes())[..16]),
+        };
+        (salt, PASSWORD_ITERATIONS)
+    }
+
+    /// Proof is `ClientKey XOR HMAC(StoredKey, nonce)`, hex encoded;
+    /// the client key recovered from it must hash to the stored key
+    ///
+    /// Unknown logins are checked against a fake stored key to take the same time.
+    pub fn verify(&self, login: &str, nonce: &str, proof: &str) -> bool {
+        let credential = self.data.players.get(login);
+        let stored_key = match credential {
+            Some(credential) => from_hex(&credential.stored_key),
+            None => Some(hmac_sha256(
+                self.data.secret.as_bytes(),
+                format!("key:{}", login).as_bytes(),
+            )),
+        };
+        let (Some(stored_key), Some(proof)) = (stored_key, from_hex(proof)) else {
+            return false;
+        };
+        let signature = hmac_sha256(&stored_key, nonce.as_bytes());
+        if proof.len() != signature.len() {
+            return false;
+        }
+        let client_key: Vec<u8> = proof.iter().zip(signature.iter()).map(|(p, s)| p ^ s).collect();
+        get_stored_key(&client_key) == stored_key && credential.is_some()
+    }
+}
+
+/// Connection waiting for the answer to the challenge
+pub struct PendingAuth {
+    pub event: PlayerConnectionInfoEvent,
+    pub nonce: String,
+    created: Instant,
+}
+
+/// Handshakes in progress and failed attempts by ip address
+#[derive(Resource, Default)]
+pub struct AuthHandshakes {
+    pending: HashMap<u64, PendingAuth>,
+    failures: HashMap<String, Vec<Instant>>,
+}
+
+impl AuthHandshakes {
+    /// Returns the nonce the client has to sign
+    pub fn start(&mut self, client_id: u64, event: PlayerConnectionInfoEvent) -> String {
+        let nonce = random_hex(32);
+        let pending = PendingAuth {
+            event,
+            nonce: nonce.clone(),
+            created: Instant::now(),
+        };
+        self.pending.insert(client_id, pending);
+        nonce
+    }
+
+    pub fn take(&mut self, client_id: u64) -> Option<PendingAuth> {
+        self.pending.remove(&client_id)
+    }
+
+    /// Removes handshakes older than `AUTH_TIMEOUT`
+    pub fn take_expired(&mut self, now: Instant) -> Vec<PendingAuth> {
+        let expired: Vec<u64> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| now.duration_since(pending.created) > AUTH_TIMEOUT)
+            .map(|(client_id, _)| *client_id)
+            .collect();
+        expired
+            .iter()
+            .filter_map(|client_id| self.pending.remove(client_id))
+            .collect()
+    }
+
+    pub fn record_failure(&mut self, ip: &str, now: Instant) {
+        self.failures.entry(ip.to_string()).or_default().push(now);
+    }
+
+    /// Forgets failures outside the window
+    fn clear_old_failures(&mut self, now: Instant) {
+        self.failures.retain(|_, failures| {
+            failures.retain(|time| now.duration_since(*time) < AUTH_FAILURES_WINDOW);
+            !failures.is_empty()
+        });
+    }
+
+    /// Too many failures from the ip address inside the window
+    pub fn is_rate_limited(&mut self, ip: &str, now: Instant) -> bool {
+        let Some(failures) = self.failures.get_mut(ip) else {
+            return false;
+        };
+        failures.retain(|time| now.duration_since(*time) < AUTH_FAILURES_WINDOW);
+        if failures.is_empty() {
+            self.failures.remove(ip);
+            return false;
+        }
+        failures.len() >= MAX_AUTH_FAILURES
+    }
+}
+
+/// Disconnects clients which didn't answer the challenge
+pub(crate) fn expire_auth_handshakes(mut handshakes: ResMut<AuthHandshakes>) {
+    let _s = crate::span!("clients.expire_auth_handshakes");
+    let now = Instant::now();
+    for pending in handshakes.take_expired(now) {
+        let client = pending.event.get_client();
+        handshakes.record_failure(&normalize_ip(client.get_client_ip()), now);
+        client.disconnect(Some("Authentication timed out".to_string()));
+    }
+    handshakes.clear_old_failures(now);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        get_client_key, get_stored_key, hash_password, hmac_sha256, to_hex, AuthCredentials, AuthHandshakes,
+        MAX_AUTH_FAILURES,
+    };
+    use crate::utils::yaml_file::YamlFile;
+    use std::time::{Duration, Instant};
+
+    fn create_proof(password_hash: &[u8], nonce: &str) -> String {
+        let client_key = get_client_key(password_hash);
+        let signature = hmac_sha256(&get_stored_key(&client_key), nonce.as_bytes());
+        let proof: Vec<u8> = client_key.iter().zip(signature.iter()).map(|(k, s)| k ^ s).collect();
+        to_hex(&proof)
+    }
+
+    #[test]
+    fn test_challenge_response() {
+        let mut auth = AuthCredentials::default();
+        auth.data = YamlFile::new(
+            std::env::temp_dir().join(format!("brilliance-auth-{}.yml", std::process::id())),
+            Default::default(),
+        );
+        auth.data.secret = "secret".to_string();
+        auth.set_password("alice", "secret").unwrap();
+
+        // What the client does with the challenge
+        let (salt, iterations) = auth.get_salt("alice");
+        let proof = |password: &str, nonce: &str| create_proof(&hash_password(password, &salt, iterations), nonce);
+
+        assert!(auth.verify("alice", "nonce", &proof("secret", "nonce")));
+        assert!(!auth.verify("alice", "nonce", &proof("wrong", "nonce")));
+        assert!(!auth.verify("alice", "other", &proof("secret", "nonce")));
+        assert!(!auth.verify("bob", "nonce", &proof("secret", "nonce")));
+
+        // Unknown login gets a stable salt which looks like a real one
+        let (fake_salt, _) = auth.get_salt("bob");
+        assert_eq!(fake_salt, auth.get_salt("bob").0);
+        assert_eq!(fake_salt.len(), salt.len());
+        assert_ne!(fake_salt, auth.get_salt("carol").0);
+        let _ = std::fs::remove_file(auth.data.get_path());
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let mut handshakes = AuthHandshakes::default();
+        let now = Instant::now();
+        for _ in 0..MAX_AUTH_FAILURES {
+            assert!(!handshakes.is_rate_limited("10.0.0.1", now));
+            handshakes.record_failure("10.0.0.1", now);
+        }
+        assert!(handshakes.is_rate_limited("10.0.0.1", now));
+        assert!(!handshakes.is_rate_limited("10.0.0.2", now));
+        assert!(!handshakes.is_rate_limited("10.0.0.1", now + Duration::from_secs(61)));
+    }
+}
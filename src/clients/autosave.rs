@@ -0,0 +1,44 @@
+use bevy_ecs::system::Res;
+use std::time::Duration;
+
+use crate::{
+    storage::storage_manager::{SharedStorageManager, StorageManager},
+    worlds::worlds_manager::{SharedWorldsManager, WorldsManager},
+};
+
+use super::clients_container::{ClientsContainer, SharedClientsContainer};
+
+pub const PLAYERS_AUTOSAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Stores the current location of every spawned player and saves his data
+pub fn save_players(clients: &ClientsContainer, worlds_manager: &WorldsManager, storage: &StorageManager) -> usize {
+    let server_storage = storage.read_server_storage();
+    let mut count = 0;
+    for (client_id, client) in clients.iter() {
+        if client.get_player_data().is_none() {
+            continue;
+        }
+
+        client.store_location(worlds_manager);
+        match client.save_player_data(&server_storage) {
+            Ok(()) => count += 1,
+            Err(e) => {
+                log::error!(target: "storage", "&cFailed to save player data for client &4{}&c: {}", client_id, e);
+            }
+        }
+    }
+    count
+}
+
+pub(crate) fn autosave_players(
+    clients: Res<SharedClientsContainer>,
+    worlds_manager: Res<SharedWorldsManager>,
+    storage: Res<SharedStorageManager>,
+) {
+    let _s = crate::span!("clients.autosave_players");
+    let now = std::time::Instant::now();
+    let count = save_players(&clients.read(), &worlds_manager.read(), &storage.read());
+    if count > 0 {
+        log::debug!(target: "storage", "Players data saved; &8players:&7{} &8(executed:{:.2?})", count, now.elapsed());
+    }
+}
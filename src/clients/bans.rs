@@ -0,0 +1,198 @@
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, net::SocketAddr, path::Path};
+
+use crate::utils::{
+    duration::parse_duration,
+    yaml_file::{YamlFile, YamlFileResource},
+};
+
+pub const BANS_FILE: &str = "bans.yml";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BanEntry {
+    // Who issued the ban
+    pub source: String,
+
+    #[serde(default)]
+    pub reason: Option<String>,
+
+    // Unix time in milliseconds; the ban is permanent without it
+    #[serde(default)]
+    pub expires: Option<i64>,
+}
+
+impl BanEntry {
+    pub fn is_active(&self, now: i64) -> bool {
+        match self.expires {
+            Some(expires) => expires > now,
+            None => true,
+        }
+    }
+
+    /// Disconnect message for the banned player
+    pub fn get_message(&self) -> String {
+        let mut message = "You are banned from this server".to_string();
+        if let Some(reason) = self.reason.as_ref() {
+            message.push_str(&format!(": {}", reason));
+        }
+        if let Some(expires) = self.expires.and_then(chrono::DateTime::from_timestamp_millis) {
+            message.push_str(&format!(" (until {})", expires.format("%Y-%m-%d %H:%M UTC")));
+        }
+        message
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+struct BansData {
+    #[serde(default)]
+    players: BTreeMap<String, BanEntry>,
+
+    #[serde(default)]
+    ips: BTreeMap<String, BanEntry>,
+}
+
+/// Banned logins and ip addresses
+///
+/// Stored in `bans.yml` inside the server data folder. Expired bans are ignored and removed on the next change.
+#[derive(Resource, Default)]
+pub struct BanList {
+    data: YamlFile<BansData>,
+}
+
+impl YamlFileResource for BanList {
+    const SPAN: &'static str = "clients.load_bans";
+    const LOG_TARGET: &'static str = "clients";
+
+    fn load(server_data_path: &Path) -> Result<Self, String> {
+        Ok(Self {
+            data: YamlFile::load(server_data_path, BANS_FILE)?,
+        })
+    }
+}
+
+impl BanList {
+    fn save(&mut self) -> Result<(), String> {
+        let now = chrono::Utc::now().timestamp_millis();
+        self.data.players.retain(|_, ban| ban.is_active(now));
+        self.data.ips.retain(|_, ban| ban.is_active(now));
+        self.data.save()
+    }
+
+    pub fn ban_player(&mut self, login: &str, ban: BanEntry) -> Result<(), String> {
+        self.data.players.insert(login.to_string(), ban);
+        self.save()
+    }
+
+    pub fn ban_ip(&mut self, ip: &str, ban: BanEntry) -> Result<(), String> {
+        self.data.ips.insert(normalize_ip(ip), ban);
+        self.save()
+    }
+
+    /// Removes the ban of the login or the ip address; returns false if nothing was banned
+    pub fn pardon(&mut self, target: &str) -> Result<bool, String> {
+        let removed_player = self.data.players.remove(target).is_some();
+        let removed_ip = self.data.ips.remove(&normalize_ip(target)).is_some();
+        if removed_player || removed_ip {
+            self.save()?;
+        }
+        Ok(removed_player || removed_ip)
+    }
+
+    /// Active ban of the login or the ip address of the connection
+    pub fn get_ban(&self, login: &str, ip: &str, now: i64) -> Option<&BanEntry> {
+        let player_ban = self.data.players.get(login).filter(|ban| ban.is_active(now));
+        player_ban.or_else(|| self.data.ips.get(&normalize_ip(ip)).filter(|ban| ban.is_active(now)))
+    }
+
+    pub fn iter_banned(&self) -> impl Iterator<Item = &String> {
+        self.data.players.keys().chain(self.data.ips.keys())
+    }
+}
+
+/// Connection address may contain the port
+pub fn normalize_ip(ip: &str) -> String {
+    match ip.parse::<SocketAddr>() {
+        Ok(address) => address.ip().to_string(),
+        Err(_) => ip.to_string(),
+    }
+}
+
+/// `30s`, `30m`, `12h`, `7d` from now or `permanent`; returns unix time in milliseconds
+pub fn parse_expiry(input: &str, now: i64) -> Result<Option<i64>, String> {
+    if input == "permanent" {
+        return Ok(None);
+    }
+    let Some(duration) = parse_duration(input) else {
+        return Err(format!(
+            "time \"{}\" must be like 30s, 30m, 12h, 7d or permanent",
+            input
+        ));
+    };
+    Ok(Some(now.saturating_add(duration)))
+}
+
+/// Expiry and reason of `ban <target> [time] [reason]`; the first word is the reason if it's not a time
+pub fn parse_ban_args(time: Option<String>, reason: Option<String>, now: i64) -> (Option<i64>, Option<String>) {
+    let Some(time) = time else {
+        return (None, reason);
+    };
+    match parse_expiry(&time, now) {
+        Ok(expires) => (expires, reason),
+        Err(_) => match reason {
+            Some(reason) => (None, Some(format!("{} {}", time, reason))),
+            None => (None, Some(time)),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_ip, parse_ban_args, parse_expiry, BanEntry, BanList};
+
+    fn ban(expires: Option<i64>) -> BanEntry {
+        BanEntry {
+            source: "console".to_string(),
+            reason: None,
+            expires,
+        }
+    }
+
+    #[test]
+    fn test_get_ban() {
+        let mut bans = BanList::default();
+        bans.data.players.insert("alice".to_string(), ban(Some(1_000)));
+        bans.data.ips.insert("10.0.0.1".to_string(), ban(None));
+
+        assert!(bans.get_ban("alice", "127.0.0.1", 500).is_some());
+        assert!(bans.get_ban("alice", "127.0.0.1", 1_000).is_none());
+        assert!(bans.get_ban("bob", "10.0.0.1:5000", 1_000).is_some());
+        assert!(bans.get_ban("bob", "10.0.0.2", 0).is_none());
+        assert_eq!(normalize_ip("[::1]:80"), "::1");
+    }
+
+    #[test]
+    fn test_parse_expiry() {
+        assert_eq!(parse_expiry("permanent", 0), Ok(None));
+        assert_eq!(parse_expiry("2h", 1_000), Ok(Some(7_201_000)));
+        assert_eq!(parse_expiry("30s", 0), Ok(Some(30_000)));
+        assert!(parse_expiry("0d", 0).is_err());
+        assert!(parse_expiry("week", 0).is_err());
+    }
+
+    #[test]
+    fn test_parse_ban_args() {
+        let arg = |value: &str| Some(value.to_string());
+        assert_eq!(
+            parse_ban_args(arg("1d"), arg("griefing"), 0),
+            (Some(86_400_000), arg("griefing"))
+        );
+        assert_eq!(parse_ban_args(arg("griefing"), None, 0), (None, arg("griefing")));
+        assert_eq!(
+            parse_ban_args(arg("griefing"), arg("again"), 0),
+            (None, arg("griefing again"))
+        );
+        assert_eq!(parse_ban_args(arg("permanent"), None, 0), (None, None));
+        assert_eq!(parse_ban_args(None, None, 0), (None, None));
+    }
+}
@@ -3,7 +3,7 @@ use bevy::prelude::{Component, Entity};
 use common::{
     chunks::chunk_position::ChunkPosition,
     inventory::item::BodyPart,
-    server_storage::taits::{IServerStorage, PlayerData},
+    server_storage::taits::{IServerStorage, PlayerData, PlayerLocation},
     utils::vec_remove_item,
     ServerStorageManager, SPECIAL_INVENTORY_BOOTS_SLOT, SPECIAL_INVENTORY_CHEST_SLOT, SPECIAL_INVENTORY_HEAD_SLOT,
     SPECIAL_INVENTORY_PANTS_SLOT,
@@ -20,15 +20,18 @@ use std::{any::Any, fmt::Display, sync::Arc};
 use std::collections::HashMap;
 
 use crate::{
+    clients::client_version::ClientCompatibility,
     console::console_sender::{ConsoleSender, ConsoleSenderType},
     entities::{
-        entity::{Position, Rotation},
+        entity::{IntoServerPosition, IntoServerRotation, Position, Rotation},
         entity_tag::EntityTagComponent,
+        health::Health,
         skin::EntitySkinComponent,
+        stats::{EntityStats, EQUIPMENT_SLOTS},
         EntityComponent,
     },
     items_manager::{item_info::ItemType, items_manager::SharedItemsManager},
-    SEND_CHUNK_QUEUE_LIMIT,
+    worlds::worlds_manager::WorldsManager,
 };
 
 use crate::network::{events::on_connection_info::PlayerConnectionInfoEvent, server::NetworkPlugin};
@@ -61,6 +64,7 @@ impl WorldEntity {
 pub struct ClientInfo {
     login: String,
     version: String,
+    compatibility: ClientCompatibility,
     _architecture: String,
     _rendering_device: String,
 }
@@ -70,6 +74,7 @@ impl ClientInfo {
         Self {
             login: event.login.clone(),
             version: event.version.clone(),
+            compatibility: ClientCompatibility::check(&event.version),
             _architecture: event.architecture.clone(),
             _rendering_device: event.rendering_device.clone(),
         }
@@ -83,6 +88,10 @@ impl ClientInfo {
         &self.version
     }
 
+    pub fn get_compatibility(&self) -> ClientCompatibility {
+        self.compatibility
+    }
+
     pub fn _get_architecture(&self) -> &String {
         &self._architecture
     }
@@ -105,6 +114,14 @@ pub struct Client {
     /// Server-side persistent player data loaded after login and saved on disconnect.
     player_data: Arc<RwLock<Option<PlayerData>>>,
 
+    /// View distance requested by the client at connect or changed by the command.
+    /// None means the server default.
+    requested_view_distance: Arc<RwLock<Option<u16>>>,
+
+    /// Effective view distance in chunks: the requested one capped by the server and current world.
+    /// Used for chunk tickets, chunk sending and entity streaming.
+    view_distance: Arc<RwLock<u16>>,
+
     /// Chunks that have been sent over the network but are not yet acknowledged by the client.
     send_chunk_queue: Arc<RwLock<Vec<ChunkPosition>>>,
 
@@ -121,6 +138,8 @@ impl Client {
             client_info: Default::default(),
             world_entity: Default::default(),
             player_data: Default::default(),
+            requested_view_distance: Default::default(),
+            view_distance: Default::default(),
 
             send_chunk_queue: Default::default(),
             confirmed_chunks: Default::default(),
@@ -192,6 +211,37 @@ impl Client {
         storage.save_player_data(&player_data).map(|_| ())
     }
 
+    /// Remembers the world and the place where the player is standing right now,
+    /// so the next login restores it
+    pub fn store_location(&self, worlds_manager: &WorldsManager) {
+        let Some(world_entity) = self.get_world_entity() else {
+            return;
+        };
+        let Some(world_manager) = worlds_manager.get_world_manager(world_entity.get_world_slug()) else {
+            return;
+        };
+        let Some((position, rotation)) = world_manager.get_entity_location(world_entity.get_entity()) else {
+            return;
+        };
+        let location = PlayerLocation::create(
+            world_entity.get_world_slug().clone(),
+            position.to_network(),
+            rotation.to_network(),
+        );
+        self.with_player_data_mut(|player_data| player_data.set_location(Some(location)));
+    }
+
+    /// The world slug, position and rotation stored during the last session
+    pub fn get_stored_location(&self) -> Option<(String, Position, Rotation)> {
+        let player_data = self.get_player_data()?;
+        let location = player_data.get_location().as_ref()?;
+        Some((
+            location.get_world_slug().clone(),
+            location.get_position().to_server(),
+            location.get_rotation().to_server(),
+        ))
+    }
+
     pub fn get_client_ip(&self) -> &String {
         self.connection.get_ip()
     }
@@ -206,6 +256,33 @@ impl Client {
         *self.world_entity.write() = world_entity;
     }
 
+    pub fn get_requested_view_distance(&self) -> Option<u16> {
+        *self.requested_view_distance.read()
+    }
+
+    pub fn set_requested_view_distance(&self, view_distance: Option<u16>) {
+        *self.requested_view_distance.write() = view_distance;
+    }
+
+    pub fn get_view_distance(&self) -> u16 {
+        *self.view_distance.read()
+    }
+
+    /// Returns true if the effective view distance was changed
+    pub fn set_view_distance(&self, view_distance: u16) -> bool {
+        let mut lock = self.view_distance.write();
+        let changed = *lock != view_distance;
+        *lock = view_distance;
+        changed
+    }
+
+    pub fn network_send_view_distance(&self) {
+        let input = ServerMessages::ViewDistance {
+            chunks_distance: self.get_view_distance(),
+        };
+        self.send_message(NetworkMessageType::ReliableOrdered, &input);
+    }
+
     pub fn network_send_spawn_pending(&self) {
         let lock = self.get_world_entity();
         let world_entity = lock.as_ref().unwrap();
@@ -256,6 +333,19 @@ impl Client {
         EntitySkinData::Generic { body_parts }
     }
 
+    /// Stats of the player with modifiers of all equipped items; `None` before player data is loaded
+    pub fn get_player_stats(&self, items_manager: &SharedItemsManager) -> Option<EntityStats> {
+        let player_data = self.get_player_data()?;
+        let inventory = player_data.get_inventory();
+        let items_manager = items_manager.read();
+        Some(EntityStats::from_modifiers(
+            EQUIPMENT_SLOTS
+                .iter()
+                .filter_map(|slot| inventory.get_slot(*slot))
+                .filter_map(|item| items_manager.get_item_stats(item)),
+        ))
+    }
+
     // Before spawn
     pub fn get_player_spawn_components(&self, items_manager: &SharedItemsManager) -> Vec<EntityComponent> {
         let mut components: Vec<EntityComponent> = Default::default();
@@ -264,6 +354,11 @@ impl Client {
         let skin = EntitySkinComponent::create(self.get_player_skin(items_manager));
         components.push(EntityComponent::Skin(Some(skin)));
 
+        let max_health = self.get_player_stats(items_manager).unwrap_or_default().get_max_health();
+        let health = self.get_player_data().and_then(|player_data| player_data.get_health());
+        let health = Health::create(health.unwrap_or(max_health), max_health);
+        components.push(EntityComponent::Health(Some(health)));
+
         let client_info = self.get_client_info().unwrap();
         let tag = EntityTagComponent::create(EntityTagData::create(client_info.get_login().clone(), None, None, None));
         components.push(EntityComponent::Tag(Some(tag)));
@@ -317,8 +412,8 @@ impl Client {
 
     /// If too many chunks currently was sended and waiting
     /// for confirmation that they have reached the client
-    pub fn is_queue_limit(&self) -> bool {
-        self.send_chunk_queue.read().len() >= SEND_CHUNK_QUEUE_LIMIT
+    pub fn is_queue_limit(&self, send_chunk_queue_limit: usize) -> bool {
+        self.send_chunk_queue.read().len() >= send_chunk_queue_limit
     }
 
     /// Total count of confirmed + queued chunks (2 locks total)
@@ -407,6 +502,14 @@ impl Client {
         self.debug_check_chunk_state();
     }
 
+    /// Unloads every chunk of the world on the client and forgets chunks that are still in flight.
+    /// Used when the player leaves the world without disconnecting.
+    pub fn unload_all_chunks(&self, world_slug: &String) {
+        self.send_chunk_queue.write().clear();
+        let confirmed_chunks: Vec<ChunkPosition> = self.confirmed_chunks.read().clone();
+        self.send_chunks_to_unload(world_slug, confirmed_chunks);
+    }
+
     pub fn send_message(&self, message_type: NetworkMessageType, message: &ServerMessages) {
         self.connection.send_message(message_type, message);
     }
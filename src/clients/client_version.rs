@@ -0,0 +1,130 @@
+use serde::Serialize;
+use std::fmt::Display;
+
+/// Oldest client which speaks the protocol of this server
+pub const MIN_CLIENT_VERSION: ClientVersion = ClientVersion::new(0, 1, 0);
+
+/// Newest client which speaks the protocol of this server
+pub const MAX_CLIENT_VERSION: ClientVersion = ClientVersion::new(0, 1, u32::MAX);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ClientVersion {
+    major: u32,
+    minor: u32,
+    patch: u32,
+}
+
+impl ClientVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self { major, minor, patch }
+    }
+
+    /// `1.2.3`, `v1.2` or `1.2.3-beta`; missing parts are zero and pre-release tags are ignored
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim().trim_start_matches('v');
+        let input = input.split(['-', '+']).next()?;
+        let mut parts = input.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = match parts.next() {
+            Some(p) => p.parse().ok()?,
+            None => 0,
+        };
+        let patch = match parts.next() {
+            Some(p) => p.parse().ok()?,
+            None => 0,
+        };
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self::new(major, minor, patch))
+    }
+}
+
+impl Display for ClientVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.patch {
+            u32::MAX => write!(f, "{}.{}.x", self.major, self.minor),
+            patch => write!(f, "{}.{}.{}", self.major, self.minor, patch),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientCompatibility {
+    Compatible,
+
+    /// Older than `MIN_CLIENT_VERSION`
+    Outdated,
+
+    /// Newer than `MAX_CLIENT_VERSION`
+    Newer,
+
+    /// Version can't be parsed
+    Unknown,
+}
+
+impl ClientCompatibility {
+    pub fn check(version: &str) -> Self {
+        match ClientVersion::parse(version) {
+            Some(v) if v < MIN_CLIENT_VERSION => Self::Outdated,
+            Some(v) if v > MAX_CLIENT_VERSION => Self::Newer,
+            Some(_) => Self::Compatible,
+            None => Self::Unknown,
+        }
+    }
+
+    pub fn is_compatible(&self) -> bool {
+        *self == Self::Compatible
+    }
+
+    /// Disconnect message for the incompatible client
+    pub fn get_message(&self, version: &str) -> String {
+        let range = format!("{} - {}", MIN_CLIENT_VERSION, MAX_CLIENT_VERSION);
+        match self {
+            Self::Compatible => format!("Client version {} is supported", version),
+            Self::Outdated => format!("Client version {} is outdated; the server supports {}", version, range),
+            Self::Newer => format!(
+                "Client version {} is newer than the server supports: {}",
+                version, range
+            ),
+            Self::Unknown => format!(
+                "Client version \"{}\" is not recognized; the server supports {}",
+                version, range
+            ),
+        }
+    }
+}
+
+impl Display for ClientCompatibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Compatible => write!(f, "compatible"),
+            Self::Outdated => write!(f, "outdated"),
+            Self::Newer => write!(f, "newer"),
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClientCompatibility, ClientVersion};
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(ClientVersion::parse("1.2.3"), Some(ClientVersion::new(1, 2, 3)));
+        assert_eq!(ClientVersion::parse("v0.1"), Some(ClientVersion::new(0, 1, 0)));
+        assert_eq!(ClientVersion::parse("0.1.4-beta+42"), Some(ClientVersion::new(0, 1, 4)));
+        assert_eq!(ClientVersion::parse("1.2.3.4"), None);
+        assert_eq!(ClientVersion::parse("dev"), None);
+    }
+
+    #[test]
+    fn test_compatibility() {
+        assert_eq!(ClientCompatibility::check("0.1.7"), ClientCompatibility::Compatible);
+        assert_eq!(ClientCompatibility::check("0.0.9"), ClientCompatibility::Outdated);
+        assert_eq!(ClientCompatibility::check("0.2.0"), ClientCompatibility::Newer);
+        assert_eq!(ClientCompatibility::check(""), ClientCompatibility::Unknown);
+    }
+}
@@ -1,14 +1,39 @@
-use crate::entities::entity::{Position, Rotation};
-use crate::network::events::on_player_move::move_player;
-use bevy::time::Time;
-use bevy_ecs::world::World;
+use crate::entities::{
+    commands::{UpdatePlayerComponent, UpdatePlayerStats},
+    entity::{Position, Rotation},
+    health::{damage_player, heal_player, DamageSource, Health},
+    skin::EntitySkinComponent,
+    EntityComponent,
+};
+use crate::inventory::{
+    audit_log::{
+        format_item, get_rollback_slots, parse_point_in_time, snapshot, AuditActor, AuditEntry, AuditInventory,
+    },
+    SharedInventoryManager,
+};
+use crate::worlds::{
+    commands::{change_view_distance, teleport_player},
+    console_commands::complete_worlds,
+    world_manager::WorldManager,
+};
+use bevy_ecs::{resource::Resource, system::Command as _, world::World};
 use common::commands::command::{Arg, ArgCompleterContext, Command, CommandMatch};
 use common::inventory::item::Item;
-use network::entities::AnimationState;
+use std::collections::BTreeMap;
 
 use crate::{
-    clients::clients_container::SharedClientsContainer, console::console_sender::ConsoleSenderType,
-    items_manager::items_manager::SharedItemsManager, network::sync_inventory::send_inventory_changes_to_client,
+    clients::{
+        auth::AuthCredentials,
+        bans::{normalize_ip, parse_ban_args, BanEntry, BanList},
+        client::Client,
+        client_version::{MAX_CLIENT_VERSION, MIN_CLIENT_VERSION},
+        clients_container::SharedClientsContainer,
+        whitelist::Whitelist,
+    },
+    console::console_sender::{Console, ConsoleSenderType},
+    items_manager::items_manager::SharedItemsManager,
+    network::{rate_limit::NetworkRateLimits, sync_inventory::send_inventory_changes_to_client},
+    storage::storage_manager::SharedStorageManager,
     worlds::worlds_manager::SharedWorldsManager,
 };
 
@@ -68,27 +93,114 @@ pub(crate) fn command_parser_clear() -> Command {
     Command::new("clear".to_owned()).arg(Arg::new("player".to_owned()).required(true).completer(complete_players))
 }
 
+/// Players and worlds, both can be a teleport destination
+fn complete_teleport_target(context: &dyn ArgCompleterContext, input: &str) -> Vec<String> {
+    let mut targets = complete_players(context, input);
+    targets.extend(complete_worlds(context, input));
+    targets
+}
+
 pub(crate) fn command_parser_teleport() -> Command {
     Command::new("tp".to_owned())
-        .arg(Arg::new("x".to_owned()).required(true))
-        .arg(Arg::new("y".to_owned()).required(true))
-        .arg(Arg::new("z".to_owned()).required(true))
+        .arg(Arg::new("player".to_owned()).required(true).completer(complete_players))
         .arg(
-            Arg::new("player".to_owned())
-                .required(false)
-                .completer(complete_players),
+            Arg::new("target".to_owned())
+                .required(true)
+                .completer(complete_teleport_target),
         )
+        .arg(Arg::new("x".to_owned()).required(false))
+        .arg(Arg::new("y".to_owned()).required(false))
+        .arg(Arg::new("z".to_owned()).required(false))
+}
+
+pub(crate) fn command_parser_view_distance() -> Command {
+    Command::new("view-distance".to_owned())
+        .arg(Arg::new("player".to_owned()).required(true).completer(complete_players))
+        .arg(Arg::new("distance".to_owned()).required(false))
+}
+
+pub(crate) fn command_parser_kill() -> Command {
+    Command::new("kill".to_owned()).arg(Arg::new("player".to_owned()).required(true).completer(complete_players))
 }
 
-fn clear_player_inventory(client: &crate::clients::client::Client) -> usize {
+pub(crate) fn command_parser_heal() -> Command {
+    Command::new("heal".to_owned())
+        .arg(Arg::new("player".to_owned()).required(true).completer(complete_players))
+        .arg(Arg::new("amount".to_owned()).required(false))
+}
+
+pub(crate) fn command_parser_inventory() -> Command {
+    Command::new("inventory".to_owned())
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("history".to_owned())
+                .arg(Arg::new("target".to_owned()).required(true).completer(complete_players))
+                .arg(Arg::new("limit".to_owned()).required(false)),
+        )
+        .subcommand(
+            Command::new("rollback".to_owned())
+                .arg(Arg::new("player".to_owned()).required(true).completer(complete_players))
+                .arg(Arg::new("time".to_owned()).required(true)),
+        )
+}
+
+/// Returns slots of the inventory before it was cleared
+fn clear_player_inventory(client: &crate::clients::client::Client) -> Vec<Option<Item>> {
     client
         .with_player_data_mut(|player_data| {
             let inventory = player_data.get_inventory_mut();
-            let slots_len = inventory.slots_len();
+            let before = snapshot(inventory);
             inventory.clear();
-            slots_len
+            before
         })
-        .unwrap_or(0)
+        .unwrap_or_default()
+}
+
+/// `world:<id>` is the block inventory; `player:<login>` or the bare login is the player inventory,
+/// so numeric logins are not taken for inventory ids
+fn parse_audit_inventory(target: &str) -> Result<AuditInventory, String> {
+    if let Some(inventory_id) = target.strip_prefix("world:") {
+        return match inventory_id.parse::<u64>() {
+            Ok(inventory_id) => Ok(AuditInventory::World(inventory_id)),
+            Err(_) => Err(format!("Inventory id \"{}\" must be a number", inventory_id)),
+        };
+    }
+    let login = target.strip_prefix("player:").unwrap_or(target);
+    Ok(AuditInventory::Player(login.to_string()))
+}
+
+/// Returns slots of the inventory before and after the rollback
+fn rollback_slots(
+    inventory: &mut common::inventory::inventory::Inventory,
+    slots: &BTreeMap<usize, Option<Item>>,
+) -> (Vec<Option<Item>>, Vec<Option<Item>>) {
+    let before = snapshot(inventory);
+    for (slot, item) in slots.iter() {
+        if *slot < inventory.slots_len() {
+            inventory.set_slot_option(*slot, item.clone());
+        }
+    }
+    (before, snapshot(inventory))
+}
+
+/// Records the change of the player inventory made by the console command
+fn record_console_change(
+    world: &World,
+    sender: &dyn ConsoleSenderType,
+    login: &str,
+    before: &[Option<Item>],
+    after: &[Option<Item>],
+) {
+    world
+        .resource::<SharedInventoryManager>()
+        .write()
+        .get_audit_log_mut()
+        .record_diff(
+            &AuditActor::Console(sender.get_name()),
+            &AuditInventory::Player(login.to_string()),
+            before,
+            after,
+        );
 }
 
 pub(crate) fn command_give(
@@ -132,18 +244,23 @@ pub(crate) fn command_give(
     let max_stack_size = items_manager.read().get_max_stack_size(&item);
 
     let result = client.with_player_data_mut(|player_data| {
-        player_data
-            .get_inventory_mut()
-            .add_item(item, max_stack_size, |slot, updated_item| {
-                send_inventory_changes_to_client(
-                    client,
-                    &crate::network::events::on_inventory_action::InventoryTarget::Client(client.get_client_id()),
-                    vec![network::messages::InventorySlotChange {
-                        slot,
-                        item: updated_item.map(|item| items_manager.read().to_client_item(item)),
-                    }],
-                );
-            })
+        let inventory = player_data.get_inventory_mut();
+        let before = snapshot(inventory);
+        let result = inventory.add_item(item, max_stack_size, |slot, updated_item| {
+            send_inventory_changes_to_client(
+                client,
+                &crate::network::events::on_inventory_action::InventoryTarget::Client(client.get_client_id()),
+                vec![network::messages::InventorySlotChange {
+                    slot,
+                    item: updated_item.map(|item| items_manager.read().to_client_item(item)),
+                }],
+            );
+        });
+        (result, before, snapshot(inventory))
+    });
+    let result = result.map(|(result, before, after)| {
+        record_console_change(world, &*sender, &login, &before, &after);
+        result
     });
 
     match result {
@@ -218,13 +335,14 @@ pub(crate) fn command_clear(
         return Ok(());
     };
 
-    let slots_len = clear_player_inventory(client);
-    if slots_len == 0 {
+    let before = clear_player_inventory(client);
+    if before.is_empty() {
         sender.send_console_message(format!("&cPlayer \"{}\" has no player data loaded", login));
         return Ok(());
     }
+    record_console_change(world, &*sender, &login, &before, &vec![None; before.len()]);
 
-    let changes: Vec<network::messages::InventorySlotChange> = (0..slots_len)
+    let changes: Vec<network::messages::InventorySlotChange> = (0..before.len())
         .map(|slot| network::messages::InventorySlotChange { slot, item: None })
         .collect();
     send_inventory_changes_to_client(
@@ -237,89 +355,841 @@ pub(crate) fn command_clear(
     Ok(())
 }
 
+/// Old form `tp <x> <y> <z> [player]` keeps the player inside the current world
+///
+/// It's accepted only when the three leading args are numbers and no world is given.
+fn get_legacy_teleport(args: &CommandMatch) -> Option<((f32, f32, f32), Option<String>)> {
+    if args.get_arg::<String, _>("z").is_ok() {
+        return None;
+    }
+    let x = args.get_arg::<f32, _>("player").ok()?;
+    let y = args.get_arg::<f32, _>("target").ok()?;
+    let z = args.get_arg::<f32, _>("x").ok()?;
+    Some(((x, y, z), args.get_arg::<String, _>("y").ok()))
+}
+
+/// `tp <player> <target-player>` or `tp <player> <world> <x> <y> <z>`
 pub(crate) fn command_teleport(
     world: &mut World,
     sender: Box<dyn ConsoleSenderType>,
     args: CommandMatch,
 ) -> Result<(), String> {
-    let x = args.get_arg::<f32, _>("x")?.clone();
-    let y = args.get_arg::<f32, _>("y")?.clone();
-    let z = args.get_arg::<f32, _>("z")?.clone();
-    let server_time = world.resource::<Time>().elapsed().as_secs_f64();
-    let worlds_manager = world.resource::<SharedWorldsManager>();
+    let legacy = get_legacy_teleport(&args);
+    let login = match &legacy {
+        Some((_, Some(login))) => login.clone(),
+        Some((_, None)) => {
+            let sender_login = sender
+                .as_any()
+                .downcast_ref::<Client>()
+                .and_then(|c| c.get_client_info().map(|info| info.get_login().clone()));
+            match sender_login {
+                Some(login) => login,
+                None => {
+                    sender.send_console_message("Console must specify player".to_string());
+                    return Ok(());
+                }
+            }
+        }
+        None => args.get_arg::<String, _>("player")?.clone(),
+    };
+    let target = args.get_arg::<String, _>("target")?.clone();
+    let coordinates = match legacy {
+        Some(((x, y, z), _)) => (Ok(x), Ok(y), Ok(z)),
+        None => (
+            args.get_arg::<f32, _>("x"),
+            args.get_arg::<f32, _>("y"),
+            args.get_arg::<f32, _>("z"),
+        ),
+    };
+    let legacy = legacy.is_some();
+
+    let Some(clients) = world.get_resource::<SharedClientsContainer>() else {
+        sender.send_console_message("&cClients container is not loaded".to_string());
+        return Ok(());
+    };
+    let clients_guard = clients.read();
+    let Some(client) = clients_guard.get_by_login(&login).cloned() else {
+        sender.send_console_message(format!("&cPlayer with login \"{}\" not found", login));
+        return Ok(());
+    };
+
+    let (world_slug, position, rotation) = match coordinates {
+        (Ok(x), Ok(y), Ok(z)) if legacy => {
+            let Some(world_entity) = client.get_world_entity() else {
+                sender.send_console_message(format!("&cPlayer \"{}\" is not in the world", login));
+                return Ok(());
+            };
+            (
+                world_entity.get_world_slug().clone(),
+                Position::new(x, y, z),
+                Rotation::new(0.0, 0.0),
+            )
+        }
+        (Ok(x), Ok(y), Ok(z)) => (target.clone(), Position::new(x, y, z), Rotation::new(0.0, 0.0)),
+        (Err(_), Err(_), Err(_)) => {
+            let Some(target_client) = clients_guard.get_by_login(&target) else {
+                sender.send_console_message(format!("&cPlayer with login \"{}\" not found", target));
+                return Ok(());
+            };
+            let Some(target_world_entity) = target_client.get_world_entity() else {
+                sender.send_console_message(format!("&cPlayer \"{}\" is not in the world", target));
+                return Ok(());
+            };
+
+            let worlds_manager = world.resource::<SharedWorldsManager>().read();
+            let location = worlds_manager
+                .get_world_manager(target_world_entity.get_world_slug())
+                .and_then(|w| w.get_entity_location(target_world_entity.get_entity()));
+            let Some((position, rotation)) = location else {
+                sender.send_console_message(format!("&cPlayer \"{}\" is not in the world", target));
+                return Ok(());
+            };
+            (target_world_entity.get_world_slug().clone(), position, rotation)
+        }
+        _ => {
+            sender.send_console_message(
+                "&cUsage: tp <player> <target-player> | tp <player> <world> <x> <y> <z> | tp <x> <y> <z> [player]"
+                    .to_string(),
+            );
+            return Ok(());
+        }
+    };
+    drop(clients_guard);
+
+    if let Err(e) = teleport_player(world, &client, &world_slug, position, rotation) {
+        sender.send_console_message(format!("&cTeleport of \"{}\" failed: {}", login, e));
+        return Ok(());
+    }
+
     let sender_name = sender.get_name().clone();
-    let sender_client = sender.as_any().downcast_ref::<crate::clients::client::Client>();
+    sender.send_console_message(format!(
+        "&a{}&r teleported &a{}&r to &e{}&r {}",
+        sender_name, login, world_slug, position
+    ));
+
+    let target_is_sender = match sender.as_any().downcast_ref::<Client>() {
+        Some(sender_client) => sender_client.get_client_id() == client.get_client_id(),
+        None => false,
+    };
+    if !target_is_sender {
+        client.send_console_message(format!(
+            "&a{}&r teleported you to &e{}&r {}",
+            sender_name, world_slug, position
+        ));
+    }
+    Ok(())
+}
+
+/// `view-distance <player>` shows the distance; `view-distance <player> <chunks|default>` changes it
+pub(crate) fn command_view_distance(
+    world: &mut World,
+    sender: Box<dyn ConsoleSenderType>,
+    args: CommandMatch,
+) -> Result<(), String> {
+    let login = args.get_arg::<String, _>("player")?.clone();
+
+    let Some(clients) = world.get_resource::<SharedClientsContainer>() else {
+        sender.send_console_message("&cClients container is not loaded".to_string());
+        return Ok(());
+    };
+    let Some(client) = clients.read().get_by_login(&login).cloned() else {
+        sender.send_console_message(format!("&cPlayer with login \"{}\" not found", login));
+        return Ok(());
+    };
 
-    let (target_login, world_entity) = if let Some(client) = sender_client {
-        let Some(world_entity) = client.get_world_entity() else {
+    let distance = match args.get_arg::<String, _>("distance") {
+        Ok(d) => d,
+        Err(_) => {
+            let requested = match client.get_requested_view_distance() {
+                Some(r) => r.to_string(),
+                None => "default".to_string(),
+            };
             sender.send_console_message(format!(
-                "Player \"{}\" is not in the world",
-                client.get_client_info().unwrap().get_login()
+                "Player &a{}&r view distance: &e{}&r (requested: &e{}&r)",
+                login,
+                client.get_view_distance(),
+                requested
             ));
             return Ok(());
-        };
+        }
+    };
 
-        (client.get_client_info().unwrap().get_login().clone(), world_entity)
-    } else {
-        let Some(login) = args.get_arg::<String, _>("player").ok() else {
-            sender.send_console_message("Console must specify player".to_string());
-            return Ok(());
-        };
+    let requested = match distance.as_str() {
+        "default" => None,
+        d => match d.parse::<u16>() {
+            Ok(d) if d > 0 => Some(d),
+            _ => {
+                sender
+                    .send_console_message("&cDistance must be a positive number of chunks or \"default\"".to_string());
+                return Ok(());
+            }
+        },
+    };
 
-        let Some(clients) = world.get_resource::<SharedClientsContainer>() else {
-            sender.send_console_message("&cClients container is not loaded".to_string());
-            return Ok(());
-        };
+    let view_distance = change_view_distance(world, &client, requested);
+    sender.send_console_message(format!(
+        "&a{}&r changed view distance of &a{}&r to &e{}&r",
+        sender.get_name(),
+        login,
+        view_distance
+    ));
+    Ok(())
+}
+
+/// Changes the health of the spawned player; returns none if he isn't in the world
+fn change_player_health(
+    world: &World,
+    client: &Client,
+    f: impl FnOnce(&mut WorldManager, &Client) -> Option<Health>,
+) -> Option<Health> {
+    let world_entity = client.get_world_entity()?;
+    let worlds_manager = world.resource::<SharedWorldsManager>().read();
+    let mut world_manager = worlds_manager.get_world_manager_mut(world_entity.get_world_slug())?;
+    f(&mut *world_manager, client)
+}
+
+pub(crate) fn command_kill(
+    world: &mut World,
+    sender: Box<dyn ConsoleSenderType>,
+    args: CommandMatch,
+) -> Result<(), String> {
+    let login = args.get_arg::<String, _>("player")?.clone();
+
+    let Some(clients) = world.get_resource::<SharedClientsContainer>() else {
+        sender.send_console_message("&cClients container is not loaded".to_string());
+        return Ok(());
+    };
+    let Some(client) = clients.read().get_by_login(&login).cloned() else {
+        sender.send_console_message(format!("&cPlayer with login \"{}\" not found", login));
+        return Ok(());
+    };
+
+    let source = DamageSource::Command(sender.get_name().clone());
+    let killed = change_player_health(world, &client, |world_manager, client| {
+        damage_player(world_manager, client, f32::INFINITY, source)
+    });
+    if killed.is_none() {
+        sender.send_console_message(format!("&cPlayer \"{}\" is not in the world", login));
+        return Ok(());
+    }
 
-        let clients_guard = clients.read();
-        let Some(target_client) = clients_guard.get_by_login(&login) else {
-            sender.send_console_message(format!("&cPlayer with login \"{}\" not found", login));
+    sender.send_console_message(format!("&a{}&r killed &a{}&r", sender.get_name(), login));
+    Ok(())
+}
+
+/// `heal <player>` restores the full health; `heal <player> <amount>` adds the amount
+pub(crate) fn command_heal(
+    world: &mut World,
+    sender: Box<dyn ConsoleSenderType>,
+    args: CommandMatch,
+) -> Result<(), String> {
+    let login = args.get_arg::<String, _>("player")?.clone();
+    let amount = match args.get_arg::<f32, _>("amount") {
+        Ok(amount) if amount > 0.0 && amount.is_finite() => amount,
+        Ok(_) => {
+            sender.send_console_message("&cAmount must be a positive number".to_string());
             return Ok(());
+        }
+        Err(_) => f32::INFINITY,
+    };
+
+    let Some(clients) = world.get_resource::<SharedClientsContainer>() else {
+        sender.send_console_message("&cClients container is not loaded".to_string());
+        return Ok(());
+    };
+    let Some(client) = clients.read().get_by_login(&login).cloned() else {
+        sender.send_console_message(format!("&cPlayer with login \"{}\" not found", login));
+        return Ok(());
+    };
+
+    let health = change_player_health(world, &client, |world_manager, client| {
+        heal_player(world_manager, client, amount)
+    });
+    let Some(health) = health else {
+        sender.send_console_message(format!("&cPlayer \"{}\" is not in the world", login));
+        return Ok(());
+    };
+
+    sender.send_console_message(format!(
+        "&a{}&r healed &a{}&r; health: &e{}/{}",
+        sender.get_name(),
+        login,
+        health.get_health(),
+        health.get_max_health()
+    ));
+    Ok(())
+}
+
+/// Changes shown by `inventory history` without the limit
+const INVENTORY_HISTORY_LIMIT: usize = 20;
+
+/// `inventory history <[player:]login|world:inventory-id> [limit]` shows the last changes from the audit log;
+/// `inventory rollback <player> <time>` restores slots of the player as they were at the time;
+/// the inventory of the offline player is changed in the stored player data
+pub(crate) fn command_inventory(
+    world: &mut World,
+    sender: Box<dyn ConsoleSenderType>,
+    args: CommandMatch,
+) -> Result<(), String> {
+    let Some(subcommand) = args.subcommand() else {
+        return Ok(());
+    };
+
+    match subcommand.get_name().as_str() {
+        "history" => {
+            let target = subcommand.get_arg::<String, _>("target")?.clone();
+            let limit = match subcommand.get_arg::<usize, _>("limit") {
+                Ok(limit) => limit,
+                Err(_) => INVENTORY_HISTORY_LIMIT,
+            };
+            let inventory = match parse_audit_inventory(&target) {
+                Ok(inventory) => inventory,
+                Err(e) => {
+                    sender.send_console_message(format!("&c{}", e));
+                    return Ok(());
+                }
+            };
+
+            let reader = world
+                .resource::<SharedInventoryManager>()
+                .write()
+                .get_audit_log_mut()
+                .reader()?;
+
+            // Audit files can be large, the tick must not wait for the scan
+            rayon::spawn(move || match reader.read_history(&inventory, limit) {
+                Ok((count, entries)) => send_inventory_history(&*sender, &inventory, count, &entries),
+                Err(e) => sender.send_console_message(format!("&cInventory history error: {}", e)),
+            });
+        }
+        "rollback" => {
+            let login = subcommand.get_arg::<String, _>("player")?.clone();
+            let time = subcommand.get_arg::<String, _>("time")?.clone();
+            let timestamp = match parse_point_in_time(&time, chrono::Utc::now().timestamp_millis()) {
+                Ok(t) => t,
+                Err(e) => {
+                    sender.send_console_message(format!("&c{}", e));
+                    return Ok(());
+                }
+            };
+
+            let reader = world
+                .resource::<SharedInventoryManager>()
+                .write()
+                .get_audit_log_mut()
+                .reader()?;
+            let rollbacks = world.resource::<InventoryRollbacks>().sender.clone();
+
+            // Slots are restored by apply_inventory_rollbacks after the scan
+            rayon::spawn(move || {
+                let changes = reader.read_changes_since(&AuditInventory::Player(login.clone()), timestamp);
+                let rollback = PendingRollback {
+                    sender,
+                    login,
+                    time,
+                    timestamp,
+                    changes,
+                };
+                let _ = rollbacks.send(rollback);
+            });
+        }
+        _ => {
+            sender.send_console_message("Error".to_string());
+        }
+    }
+    Ok(())
+}
+
+fn send_inventory_history(
+    sender: &dyn ConsoleSenderType,
+    inventory: &AuditInventory,
+    count: usize,
+    entries: &[AuditEntry],
+) {
+    if count == 0 {
+        sender.send_console_message(format!("&cNo changes of the inventory &4{}&c found", inventory));
+        return;
+    }
+
+    sender.send_console_message(format!(
+        "Inventory &a{}&r changes: &e{}&r; last &e{}&r:",
+        inventory,
+        count,
+        entries.len()
+    ));
+    for entry in entries.iter() {
+        let time = match chrono::DateTime::from_timestamp_millis(entry.timestamp) {
+            Some(time) => time
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+            None => entry.timestamp.to_string(),
         };
+        sender.send_console_message(format!(
+            " - &8{} &7{} &8slot:&7{} &c{} &8-> &a{}",
+            time,
+            entry.actor,
+            entry.slot,
+            format_item(entry.before.as_ref()),
+            format_item(entry.after.as_ref())
+        ));
+    }
+}
+
+/// `inventory rollback` waiting for the audit log scan
+pub(crate) struct PendingRollback {
+    sender: Box<dyn ConsoleSenderType>,
+    login: String,
+    time: String,
+    timestamp: i64,
+
+    // Count of all entries of the player inventory and the entries after the timestamp
+    changes: Result<(usize, Vec<AuditEntry>), String>,
+}
+
+#[derive(Resource)]
+pub(crate) struct InventoryRollbacks {
+    sender: flume::Sender<PendingRollback>,
+    receiver: flume::Receiver<PendingRollback>,
+}
+
+impl Default for InventoryRollbacks {
+    fn default() -> Self {
+        let (sender, receiver) = flume::unbounded();
+        Self { sender, receiver }
+    }
+}
+
+/// Restores slots of the rollbacks which audit log scan is finished
+pub(crate) fn apply_inventory_rollbacks(world: &mut World) {
+    let _s = crate::span!("clients.apply_inventory_rollbacks");
+    let rollbacks: Vec<PendingRollback> = world.resource::<InventoryRollbacks>().receiver.drain().collect();
+    for rollback in rollbacks {
+        let PendingRollback {
+            sender,
+            login,
+            time,
+            timestamp,
+            changes,
+        } = rollback;
+        if let Err(e) = rollback_inventory(world, &*sender, &login, &time, timestamp, changes) {
+            sender.send_console_message(format!("&cInventory rollback of \"{}\" error: {}", login, e));
+        }
+    }
+}
+
+fn rollback_inventory(
+    world: &mut World,
+    sender: &dyn ConsoleSenderType,
+    login: &String,
+    time: &String,
+    timestamp: i64,
+    changes: Result<(usize, Vec<AuditEntry>), String>,
+) -> Result<(), String> {
+    let (count, entries) = changes?;
+
+    // Every player who had items is in the audit log; nothing is created for an unknown login
+    if count == 0 {
+        sender.send_console_message(format!(
+            "&cPlayer \"{}\" has no inventory changes in the audit log",
+            login
+        ));
+        return Ok(());
+    }
+    let slots = get_rollback_slots(&entries, timestamp);
+    if slots.is_empty() {
+        sender.send_console_message(format!("&eInventory of &a{}&e wasn't changed since then", login));
+        return Ok(());
+    }
 
-        let Some(world_entity) = target_client.get_world_entity() else {
-            sender.send_console_message(format!("Player \"{}\" is not in the world", login));
+    let client = world
+        .resource::<SharedClientsContainer>()
+        .read()
+        .get_by_login(login)
+        .filter(|client| client.get_player_data().is_some())
+        .cloned();
+    let Some(client) = client else {
+        let Some(storage) = world.get_resource::<SharedStorageManager>() else {
+            sender.send_console_message(format!("&cPlayer \"{}\" is offline and storage is not loaded", login));
             return Ok(());
         };
+        let (before, after) = {
+            let storage = storage.read();
+            let server_storage = storage.read_server_storage();
+            let mut player_data = server_storage.get_or_create_player_data(login.clone())?;
+            let result = rollback_slots(player_data.get_inventory_mut(), &slots);
+            server_storage.save_player_data(&player_data)?;
+            result
+        };
+        record_console_change(world, sender, login, &before, &after);
+
+        sender.send_console_message(format!(
+            "&a{}&r rolled back &e{}&r slots of offline &a{}&r inventory to &e{}",
+            sender.get_name(),
+            slots.len(),
+            login,
+            time
+        ));
+        return Ok(());
+    };
+
+    let result = client.with_player_data_mut(|player_data| rollback_slots(player_data.get_inventory_mut(), &slots));
+    let Some((before, after)) = result else {
+        sender.send_console_message(format!("&cPlayer \"{}\" has no player data loaded", login));
+        return Ok(());
+    };
+    record_console_change(world, sender, login, &before, &after);
+
+    let items_manager = world.resource::<SharedItemsManager>().clone();
+    let changes: Vec<network::messages::InventorySlotChange> = after
+        .iter()
+        .enumerate()
+        .filter(|(slot, _item)| slots.contains_key(slot))
+        .map(|(slot, item)| network::messages::InventorySlotChange {
+            slot,
+            item: item.as_ref().map(|item| items_manager.read().to_client_item(item)),
+        })
+        .collect();
+    send_inventory_changes_to_client(
+        &client,
+        &crate::network::events::on_inventory_action::InventoryTarget::Client(client.get_client_id()),
+        changes,
+    );
+
+    // Equipment could be rolled back too
+    if client.get_world_entity().is_some() {
+        let skin = client.get_player_skin(&items_manager);
+        let stats = client.get_player_stats(&items_manager);
+        UpdatePlayerComponent::_create(
+            client.clone(),
+            EntityComponent::Skin(Some(EntitySkinComponent::create(skin))),
+        )
+        .apply(world);
+        if let Some(stats) = stats {
+            UpdatePlayerStats::create(client.clone(), stats).apply(world);
+        }
+    }
+
+    sender.send_console_message(format!(
+        "&a{}&r rolled back &e{}&r slots of &a{}&r inventory to &e{}",
+        sender.get_name(),
+        slots.len(),
+        login,
+        time
+    ));
+    Ok(())
+}
 
-        (login, world_entity)
+fn complete_banned(context: &dyn ArgCompleterContext, input: &str) -> Vec<String> {
+    let world = world_from_context(context);
+    let Some(bans) = world.get_resource::<BanList>() else {
+        return Vec::new();
     };
-    let target_is_sender = sender_client
-        .and_then(|client| client.get_client_info().map(|info| info.get_login().clone()))
-        .map(|login| login == target_login)
-        .unwrap_or(false);
+    bans.iter_banned()
+        .filter(|target| target.contains(input))
+        .cloned()
+        .collect()
+}
+
+pub(crate) fn command_parser_ban() -> Command {
+    Command::new("ban".to_owned())
+        .arg(Arg::new("player".to_owned()).required(true).completer(complete_players))
+        .arg(Arg::new("time".to_owned()).required(false))
+        .arg(Arg::new("reason".to_owned()).required(false))
+}
 
-    let position = Position::new(x, y, z);
-    let rotation = Rotation::new(0.0, 0.0);
+pub(crate) fn command_parser_ban_ip() -> Command {
+    Command::new("ban-ip".to_owned())
+        .arg(Arg::new("target".to_owned()).required(true).completer(complete_players))
+        .arg(Arg::new("time".to_owned()).required(false))
+        .arg(Arg::new("reason".to_owned()).required(false))
+}
+
+pub(crate) fn command_parser_pardon() -> Command {
+    Command::new("pardon".to_owned()).arg(Arg::new("target".to_owned()).required(true).completer(complete_banned))
+}
 
-    let worlds_manager = worlds_manager.write();
-    let mut world_manager = worlds_manager
-        .get_world_manager_mut(&world_entity.get_world_slug())
-        .unwrap();
+pub(crate) fn command_parser_whitelist() -> Command {
+    Command::new("whitelist".to_owned())
+        .subcommand_required(true)
+        .subcommand(Command::new("on".to_owned()))
+        .subcommand(Command::new("off".to_owned()))
+        .subcommand(
+            Command::new("add".to_owned())
+                .arg(Arg::new("player".to_owned()).required(true).completer(complete_players)),
+        )
+        .subcommand(
+            Command::new("remove".to_owned())
+                .arg(Arg::new("player".to_owned()).required(true).completer(complete_players)),
+        )
+        .subcommand(Command::new("list".to_owned()))
+}
 
-    move_player(
-        &mut *world_manager,
-        &world_entity,
-        position,
-        rotation,
-        AnimationState::Idle,
-        server_time,
+/// Ban from the optional `time` and `reason` arguments
+fn ban_from_args(sender: &dyn ConsoleSenderType, args: &CommandMatch) -> BanEntry {
+    let (expires, reason) = parse_ban_args(
+        args.get_arg::<String, _>("time").ok(),
+        args.get_arg::<String, _>("reason").ok(),
+        chrono::Utc::now().timestamp_millis(),
     );
+    BanEntry {
+        source: sender.get_name(),
+        reason,
+        expires,
+    }
+}
+
+/// `ban <player> [30s|30m|12h|7d|permanent] [reason]`
+pub(crate) fn command_ban(
+    world: &mut World,
+    sender: Box<dyn ConsoleSenderType>,
+    args: CommandMatch,
+) -> Result<(), String> {
+    let login = args.get_arg::<String, _>("player")?.clone();
+    let ban = ban_from_args(&*sender, &args);
+    let message = ban.get_message();
+    world.resource_mut::<BanList>().ban_player(&login, ban)?;
+
+    if let Some(client) = world.resource::<SharedClientsContainer>().read().get_by_login(&login) {
+        client.disconnect(Some(message.clone()));
+    }
+    sender.send_console_message(format!(
+        "Admin &a{}&r banned player &a{}&r: &e{}",
+        sender.get_name(),
+        login,
+        message
+    ));
+    Ok(())
+}
+
+/// `ban-ip <player|ip> [30s|30m|12h|7d|permanent] [reason]`; an online player is banned by his address
+pub(crate) fn command_ban_ip(
+    world: &mut World,
+    sender: Box<dyn ConsoleSenderType>,
+    args: CommandMatch,
+) -> Result<(), String> {
+    let target = args.get_arg::<String, _>("target")?.clone();
+    let ip = match world.resource::<SharedClientsContainer>().read().get_by_login(&target) {
+        Some(client) => normalize_ip(client.get_client_ip()),
+        None => match target.parse::<std::net::IpAddr>() {
+            Ok(ip) => ip.to_string(),
+            Err(_) => {
+                sender.send_console_message(format!("&cPlayer \"{}\" is offline and it's not an ip address", target));
+                return Ok(());
+            }
+        },
+    };
+    let ban = ban_from_args(&*sender, &args);
+    let message = ban.get_message();
+    world.resource_mut::<BanList>().ban_ip(&ip, ban)?;
 
+    let clients = world.resource::<SharedClientsContainer>().read();
+    for (_client_id, client) in clients.iter() {
+        if normalize_ip(client.get_client_ip()) == ip {
+            client.disconnect(Some(message.clone()));
+        }
+    }
     sender.send_console_message(format!(
-        "&a{}&r teleported &a{}&r to &e{}, {}, {}&r",
-        sender_name, target_login, x, y, z
+        "Admin &a{}&r banned ip &a{}&r: &e{}",
+        sender.get_name(),
+        ip,
+        message
     ));
-    let Some(clients) = world.get_resource::<SharedClientsContainer>() else {
+    Ok(())
+}
+
+/// `pardon <player|ip>`
+pub(crate) fn command_pardon(
+    world: &mut World,
+    sender: Box<dyn ConsoleSenderType>,
+    args: CommandMatch,
+) -> Result<(), String> {
+    let target = args.get_arg::<String, _>("target")?.clone();
+    if !world.resource_mut::<BanList>().pardon(&target)? {
+        sender.send_console_message(format!("&c\"{}\" is not banned", target));
+        return Ok(());
+    }
+    sender.send_console_message(format!("Admin &a{}&r pardoned &a{}", sender.get_name(), target));
+    Ok(())
+}
+
+/// `whitelist on|off|add|remove|list`
+pub(crate) fn command_whitelist(
+    world: &mut World,
+    sender: Box<dyn ConsoleSenderType>,
+    args: CommandMatch,
+) -> Result<(), String> {
+    let Some(subcommand) = args.subcommand() else {
         return Ok(());
     };
-    let clients_guard = clients.read();
-    if let Some(target_client) = clients_guard.get_by_login(&target_login) {
-        target_client.network_send_spawn(&Position::new(x, y, z), &Rotation::new(0.0, 0.0), &Vec::new());
-        if !target_is_sender {
-            target_client
-                .send_console_message(format!("&a{}&r teleported you to &e{}, {}, {}&r", sender_name, x, y, z));
+
+    let mut whitelist = world.resource_mut::<Whitelist>();
+    match subcommand.get_name().as_str() {
+        "on" | "off" => {
+            let enabled = subcommand.get_name() == "on";
+            if !whitelist.set_enabled(enabled)? {
+                sender.send_console_message(format!("&eWhitelist is already {}", subcommand.get_name()));
+                return Ok(());
+            }
+            sender.send_console_message(format!("Whitelist is &a{}", subcommand.get_name()));
+        }
+        "add" => {
+            let login = subcommand.get_arg::<String, _>("player")?.clone();
+            match whitelist.add(&login)? {
+                true => sender.send_console_message(format!("&a{}&r added to the whitelist", login)),
+                false => sender.send_console_message(format!("&ePlayer &a{}&e is already whitelisted", login)),
+            }
+        }
+        "remove" => {
+            let login = subcommand.get_arg::<String, _>("player")?.clone();
+            match whitelist.remove(&login)? {
+                true => sender.send_console_message(format!("&a{}&r removed from the whitelist", login)),
+                false => sender.send_console_message(format!("&ePlayer &a{}&e is not whitelisted", login)),
+            }
+        }
+        "list" => {
+            let players: Vec<String> = whitelist.iter_players().cloned().collect();
+            sender.send_console_message(format!(
+                "Whitelist &8enabled:&7{} &8players:&7{}",
+                whitelist.is_enabled(),
+                if players.is_empty() {
+                    "-".to_string()
+                } else {
+                    players.join(", ")
+                }
+            ));
+        }
+        _ => {
+            sender.send_console_message("Error".to_string());
         }
     }
     Ok(())
 }
+
+pub(crate) fn command_parser_auth() -> Command {
+    Command::new("auth".to_owned())
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("password".to_owned())
+                .arg(Arg::new("player".to_owned()).required(true).completer(complete_players))
+                .arg(Arg::new("password".to_owned()).required(true)),
+        )
+        .subcommand(
+            Command::new("token".to_owned())
+                .arg(Arg::new("player".to_owned()).required(true).completer(complete_players)),
+        )
+        .subcommand(
+            Command::new("remove".to_owned())
+                .arg(Arg::new("player".to_owned()).required(true).completer(complete_players)),
+        )
+}
+
+/// `auth password|token|remove <player>` manages credentials of the `password` auth mode;
+/// `password` is accepted only from the server console
+pub(crate) fn command_auth(
+    world: &mut World,
+    sender: Box<dyn ConsoleSenderType>,
+    args: CommandMatch,
+) -> Result<(), String> {
+    let Some(subcommand) = args.subcommand() else {
+        return Ok(());
+    };
+    let login = subcommand.get_arg::<String, _>("player")?.clone();
+
+    let mut credentials = world.resource_mut::<AuthCredentials>();
+    match subcommand.get_name().as_str() {
+        "password" => {
+            // Client console input travels over the network and can be logged
+            if sender.as_any().downcast_ref::<Console>().is_none() {
+                sender
+                    .send_console_message("&cPasswords can be set only from the server console; use token".to_string());
+                return Ok(());
+            }
+            let password = subcommand.get_arg::<String, _>("password")?.clone();
+            credentials.set_password(&login, &password)?;
+            sender.send_console_message(format!("Password of &a{}&r is changed", login));
+        }
+        "token" => {
+            let token = credentials.create_token(&login)?;
+            sender.send_console_message(format!("Token of &a{}&r: &e{}", login, token));
+        }
+        "remove" => match credentials.remove(&login)? {
+            true => sender.send_console_message(format!("Credentials of &a{}&r are removed", login)),
+            false => sender.send_console_message(format!("&ePlayer &a{}&e has no credentials", login)),
+        },
+        _ => {
+            sender.send_console_message("Error".to_string());
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn command_parser_players() -> Command {
+    Command::new("players".to_owned())
+}
+
+/// `players` lists connected players with their client versions and dropped messages
+pub(crate) fn command_players(
+    world: &mut World,
+    sender: Box<dyn ConsoleSenderType>,
+    _args: CommandMatch,
+) -> Result<(), String> {
+    let rate_limits = world.resource::<NetworkRateLimits>();
+    let clients = world.resource::<SharedClientsContainer>().read();
+    let mut lines: Vec<(String, String)> = clients
+        .iter()
+        .filter_map(|(_, client)| {
+            let info = client.get_client_info()?;
+            let world_slug = match client.get_world_entity() {
+                Some(world_entity) => world_entity.get_world_slug().clone(),
+                None => "-".to_string(),
+            };
+            let line = format!(
+                "&a{}&r &8version:&7{} ({}) &8ip:&7{} &8world:&7{} &8dropped:&7{}",
+                info.get_login(),
+                info.get_version(),
+                info.get_compatibility(),
+                client.get_client_ip(),
+                world_slug,
+                rate_limits.get_dropped(client.get_client_id())
+            );
+            Some((info.get_login().clone(), line))
+        })
+        .collect();
+    lines.sort();
+
+    sender.send_console_message(format!(
+        "Players online: &e{}&r &8supported clients:&7{} - {}",
+        lines.len(),
+        MIN_CLIENT_VERSION,
+        MAX_CLIENT_VERSION
+    ));
+    for (_login, line) in lines {
+        sender.send_console_message(line);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{command_parser_teleport, get_legacy_teleport};
+    use crate::console::commands_executer::eval_command;
+
+    #[test]
+    fn test_legacy_teleport() {
+        let legacy = |command: &str| get_legacy_teleport(&eval_command(command_parser_teleport(), command).unwrap());
+
+        assert_eq!(legacy("tp 1 2.5 3"), Some(((1.0, 2.5, 3.0), None)));
+        assert_eq!(
+            legacy("tp 1 2 3 alice"),
+            Some(((1.0, 2.0, 3.0), Some("alice".to_string())))
+        );
+
+        // New forms are never remapped, even with numeric names
+        assert_eq!(legacy("tp alice bob"), None);
+        assert_eq!(legacy("tp 1 2"), None);
+        assert_eq!(legacy("tp alice default 1 2 3"), None);
+        assert_eq!(legacy("tp 1 2 3 4 5"), None);
+        assert_eq!(legacy("tp alice 2 3 bob"), None);
+    }
+}
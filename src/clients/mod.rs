@@ -1,15 +1,31 @@
-use bevy_app::{App, Plugin};
+use bevy::time::common_conditions::on_timer;
+use bevy_app::{App, Plugin, Startup, Update};
+use bevy_ecs::schedule::IntoScheduleConfigs;
 
 use crate::console::commands_executer::{CommandExecuter, CommandsHandler};
+use crate::utils::yaml_file::load_yaml_file_resource;
 
+pub mod auth;
+pub mod autosave;
+pub mod bans;
 pub mod client;
+pub mod client_version;
 pub mod clients_container;
 pub mod console_commands;
+pub mod whitelist;
 
+use auth::{expire_auth_handshakes, AuthCredentials, AuthHandshakes, AUTH_EXPIRE_INTERVAL};
+use autosave::{autosave_players, PLAYERS_AUTOSAVE_INTERVAL};
+use bans::BanList;
 use console_commands::{
-    command_clear, command_give, command_kick, command_parser_clear, command_parser_give, command_parser_kick,
-    command_parser_teleport, command_teleport,
+    apply_inventory_rollbacks, command_auth, command_ban, command_ban_ip, command_clear, command_give, command_heal,
+    command_inventory, command_kick, command_kill, command_pardon, command_parser_auth, command_parser_ban,
+    command_parser_ban_ip, command_parser_clear, command_parser_give, command_parser_heal, command_parser_inventory,
+    command_parser_kick, command_parser_kill, command_parser_pardon, command_parser_players, command_parser_teleport,
+    command_parser_view_distance, command_parser_whitelist, command_players, command_teleport, command_view_distance,
+    command_whitelist, InventoryRollbacks,
 };
+use whitelist::Whitelist;
 
 pub struct ClientsPlugin;
 
@@ -26,5 +42,35 @@ impl Plugin for ClientsPlugin {
         commands_handler.add_command_executer(CommandExecuter::new(command_parser_kick(), command_kick));
         commands_handler.add_command_executer(CommandExecuter::new(command_parser_clear(), command_clear));
         commands_handler.add_command_executer(CommandExecuter::new(command_parser_teleport(), command_teleport));
+        commands_handler.add_command_executer(CommandExecuter::new(command_parser_kill(), command_kill));
+        commands_handler.add_command_executer(CommandExecuter::new(command_parser_heal(), command_heal));
+        commands_handler.add_command_executer(CommandExecuter::new(command_parser_inventory(), command_inventory));
+        commands_handler.add_command_executer(CommandExecuter::new(
+            command_parser_view_distance(),
+            command_view_distance,
+        ));
+        commands_handler.add_command_executer(CommandExecuter::new(command_parser_ban(), command_ban));
+        commands_handler.add_command_executer(CommandExecuter::new(command_parser_ban_ip(), command_ban_ip));
+        commands_handler.add_command_executer(CommandExecuter::new(command_parser_pardon(), command_pardon));
+        commands_handler.add_command_executer(CommandExecuter::new(command_parser_whitelist(), command_whitelist));
+        commands_handler.add_command_executer(CommandExecuter::new(command_parser_auth(), command_auth));
+        commands_handler.add_command_executer(CommandExecuter::new(command_parser_players(), command_players));
+
+        app.insert_resource(BanList::default());
+        app.insert_resource(Whitelist::default());
+        app.insert_resource(AuthCredentials::default());
+        app.insert_resource(AuthHandshakes::default());
+        app.insert_resource(InventoryRollbacks::default());
+        app.add_systems(
+            Startup,
+            (
+                load_yaml_file_resource::<BanList>,
+                load_yaml_file_resource::<Whitelist>,
+                load_yaml_file_resource::<AuthCredentials>,
+            ),
+        );
+        app.add_systems(Update, expire_auth_handshakes.run_if(on_timer(AUTH_EXPIRE_INTERVAL)));
+        app.add_systems(Update, autosave_players.run_if(on_timer(PLAYERS_AUTOSAVE_INTERVAL)));
+        app.add_systems(Update, apply_inventory_rollbacks);
     }
 }
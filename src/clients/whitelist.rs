@@ -0,0 +1,77 @@
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, path::Path};
+
+use crate::utils::yaml_file::{YamlFile, YamlFileResource};
+
+pub const WHITELIST_FILE: &str = "whitelist.yml";
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+struct WhitelistData {
+    #[serde(default)]
+    enabled: bool,
+
+    #[serde(default)]
+    players: BTreeSet<String>,
+}
+
+/// Only listed logins can connect while the whitelist is enabled
+///
+/// Stored in `whitelist.yml` inside the server data folder.
+#[derive(Resource, Default)]
+pub struct Whitelist {
+    data: YamlFile<WhitelistData>,
+}
+
+impl YamlFileResource for Whitelist {
+    const SPAN: &'static str = "clients.load_whitelist";
+    const LOG_TARGET: &'static str = "clients";
+
+    fn load(server_data_path: &Path) -> Result<Self, String> {
+        Ok(Self {
+            data: YamlFile::load(server_data_path, WHITELIST_FILE)?,
+        })
+    }
+}
+
+impl Whitelist {
+    pub fn is_enabled(&self) -> bool {
+        self.data.enabled
+    }
+
+    /// Returns false if nothing was changed
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<bool, String> {
+        if self.data.enabled == enabled {
+            return Ok(false);
+        }
+        self.data.enabled = enabled;
+        self.data.save()?;
+        Ok(true)
+    }
+
+    /// Returns false if the login is already listed
+    pub fn add(&mut self, login: &str) -> Result<bool, String> {
+        let added = self.data.players.insert(login.to_string());
+        if added {
+            self.data.save()?;
+        }
+        Ok(added)
+    }
+
+    /// Returns false if the login is not listed
+    pub fn remove(&mut self, login: &str) -> Result<bool, String> {
+        let removed = self.data.players.remove(login);
+        if removed {
+            self.data.save()?;
+        }
+        Ok(removed)
+    }
+
+    pub fn is_allowed(&self, login: &str) -> bool {
+        !self.data.enabled || self.data.players.contains(login)
+    }
+
+    pub fn iter_players(&self) -> impl Iterator<Item = &String> {
+        self.data.players.iter()
+    }
+}
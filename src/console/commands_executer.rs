@@ -1,4 +1,4 @@
-use super::console_sender::ConsoleSenderType;
+use super::{console_sender::ConsoleSenderType, permissions::Permissions};
 use bevy_ecs::{resource::Resource, world::World};
 use common::commands::{
     command::{ArgCompleterContext, Command, CommandMatch},
@@ -26,6 +26,9 @@ pub struct CommandExecuter {
     command_parser: Command,
     handler: CommandFN,
     name: String,
+
+    // Players need it to run the command; `command.<name>` by default
+    permission: String,
 }
 
 impl CommandExecuter {
@@ -34,9 +37,15 @@ impl CommandExecuter {
         Self {
             command_parser,
             handler,
+            permission: format!("command.{}", name),
             name,
         }
     }
+
+    pub fn permission(mut self, permission: impl Into<String>) -> Self {
+        self.permission = permission.into();
+        self
+    }
 }
 
 #[derive(Resource)]
@@ -62,14 +71,22 @@ impl CommandsHandler {
         }
         let lead_command = command_sequence[0].clone();
 
-        let mut handlers = world.resource_mut::<CommandsHandler>();
+        let handlers = world.resource::<CommandsHandler>();
+        let permissions = world.resource::<Permissions>();
         let mut handler: Option<(CommandFN, CommandMatch)> = None;
 
-        for command_handler in handlers.commands.iter_mut() {
+        for command_handler in handlers.commands.iter() {
             if command_handler.name != lead_command {
                 continue;
             }
 
+            if !permissions.is_allowed(&*sender, &command_handler.permission) {
+                // Arguments can contain secrets
+                log::debug!(target: "console", "Command \"{}\" by {} is denied", lead_command, sender);
+                sender.send_console_message(format!("&cYou don't have permission to run &4\"{}\"", lead_command));
+                return;
+            }
+
             let command = command_handler.command_parser.clone();
             match command.eval(&command_sequence[1..]) {
                 Ok(command_match) => {
@@ -86,7 +103,7 @@ impl CommandsHandler {
             Some((handler_fn, command_match)) => {
                 let sender_title = format!("{}", sender);
                 if let Err(e) = (handler_fn)(world, sender, command_match) {
-                    error!("Command {} by:{} error: {}", command, sender_title, e);
+                    error!("Command {} by:{} error: {}", lead_command, sender_title, e);
                 }
             }
             None => {
@@ -97,12 +114,19 @@ impl CommandsHandler {
 
     pub fn complete(
         world: &mut World,
-        _sender: Box<dyn ConsoleSenderType>,
+        sender: Box<dyn ConsoleSenderType>,
         request: &CompleteRequest,
     ) -> CompleteResponse {
         let handlers = world.resource::<CommandsHandler>();
-
-        let commands: Vec<Command> = handlers.commands.iter().map(|m| m.command_parser.clone()).collect();
+        let permissions = world.resource::<Permissions>();
+
+        // Commands which the sender can't run are hidden
+        let commands: Vec<Command> = handlers
+            .commands
+            .iter()
+            .filter(|m| permissions.is_allowed(&*sender, &m.permission))
+            .map(|m| m.command_parser.clone())
+            .collect();
         let context = ServerCompleterContext { world: &*world };
         let complete_response = CompleteResponse::complete(request, commands.iter(), Some(&context));
         complete_response
@@ -112,3 +136,10 @@ impl CommandsHandler {
         //}
     }
 }
+
+/// Parses the command line like `execute_command` does; the lead command is skipped
+#[cfg(test)]
+pub(crate) fn eval_command(command_parser: Command, command: &str) -> Result<CommandMatch, String> {
+    let command_sequence = Command::parse_command(&command.to_string());
+    command_parser.eval(&command_sequence[1..]).map_err(|e| e.to_string())
+}
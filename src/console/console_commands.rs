@@ -0,0 +1,162 @@
+use bevy_ecs::world::World;
+use common::commands::command::{Arg, ArgCompleterContext, Command, CommandMatch};
+
+use super::{console_sender::ConsoleSenderType, permissions::Permissions};
+use crate::clients::{clients_container::SharedClientsContainer, console_commands::complete_players};
+
+fn complete_groups(context: &dyn ArgCompleterContext, input: &str) -> Vec<String> {
+    let Some(world) = context.world().downcast_ref::<World>() else {
+        return Vec::new();
+    };
+    let Some(permissions) = world.get_resource::<Permissions>() else {
+        return Vec::new();
+    };
+    permissions
+        .iter_groups()
+        .filter(|group| group.contains(input))
+        .cloned()
+        .collect()
+}
+
+pub(crate) fn command_parser_op() -> Command {
+    Command::new("op".to_owned()).arg(Arg::new("player".to_owned()).required(true).completer(complete_players))
+}
+
+pub(crate) fn command_parser_deop() -> Command {
+    Command::new("deop".to_owned()).arg(Arg::new("player".to_owned()).required(true).completer(complete_players))
+}
+
+pub(crate) fn command_parser_perm() -> Command {
+    let group = |name: &str| {
+        Command::new(name.to_owned())
+            .arg(Arg::new("player".to_owned()).required(true).completer(complete_players))
+            .arg(Arg::new("group".to_owned()).required(true).completer(complete_groups))
+    };
+    Command::new("perm".to_owned())
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("info".to_owned())
+                .arg(Arg::new("player".to_owned()).required(true).completer(complete_players)),
+        )
+        .subcommand(group("add"))
+        .subcommand(group("remove"))
+        .subcommand(
+            Command::new("check".to_owned())
+                .arg(Arg::new("player".to_owned()).required(true).completer(complete_players))
+                .arg(Arg::new("permission".to_owned()).required(true)),
+        )
+        .subcommand(Command::new("groups".to_owned()))
+}
+
+/// Tells the online player that his permissions are changed
+fn notify_player(world: &World, login: &String, message: String) {
+    let Some(clients) = world.get_resource::<SharedClientsContainer>() else {
+        return;
+    };
+    if let Some(client) = clients.read().get_by_login(login) {
+        client.send_console_message(message);
+    }
+}
+
+/// `op <player>` allows the player to run every command
+pub(crate) fn command_op(
+    world: &mut World,
+    sender: Box<dyn ConsoleSenderType>,
+    args: CommandMatch,
+) -> Result<(), String> {
+    let login = args.get_arg::<String, _>("player")?.clone();
+    if !world.resource_mut::<Permissions>().set_op(&login, true)? {
+        sender.send_console_message(format!("&ePlayer &a{}&e is already an operator", login));
+        return Ok(());
+    }
+    log::info!(target: "console", "&a{}&r made &a{}&r an operator", sender.get_name(), login);
+    sender.send_console_message(format!("&a{}&r is an operator now", login));
+    notify_player(world, &login, "&aYou are an operator now".to_string());
+    Ok(())
+}
+
+pub(crate) fn command_deop(
+    world: &mut World,
+    sender: Box<dyn ConsoleSenderType>,
+    args: CommandMatch,
+) -> Result<(), String> {
+    let login = args.get_arg::<String, _>("player")?.clone();
+    if !world.resource_mut::<Permissions>().set_op(&login, false)? {
+        sender.send_console_message(format!("&ePlayer &a{}&e is not an operator", login));
+        return Ok(());
+    }
+    log::info!(target: "console", "&a{}&r removed operator &a{}&r", sender.get_name(), login);
+    sender.send_console_message(format!("&a{}&r is no longer an operator", login));
+    notify_player(world, &login, "&eYou are no longer an operator".to_string());
+    Ok(())
+}
+
+/// `perm info|add|remove|check|groups` manages groups of players from `permissions.yml`
+pub(crate) fn command_perm(
+    world: &mut World,
+    sender: Box<dyn ConsoleSenderType>,
+    args: CommandMatch,
+) -> Result<(), String> {
+    let Some(subcommand) = args.subcommand() else {
+        return Ok(());
+    };
+
+    match subcommand.get_name().as_str() {
+        "info" => {
+            let login = subcommand.get_arg::<String, _>("player")?.clone();
+            let permissions = world.resource::<Permissions>();
+            let groups = permissions.get_player_groups(&login);
+            sender.send_console_message(format!(
+                "Player &a{}&r: &8op:&7{} &8groups:&7{}",
+                login,
+                permissions.is_op(&login),
+                if groups.is_empty() {
+                    "-".to_string()
+                } else {
+                    groups.join(", ")
+                }
+            ));
+        }
+        "add" | "remove" => {
+            let login = subcommand.get_arg::<String, _>("player")?.clone();
+            let group = subcommand.get_arg::<String, _>("group")?.clone();
+            let mut permissions = world.resource_mut::<Permissions>();
+            if subcommand.get_name() == "add" {
+                match permissions.add_player_group(&login, &group) {
+                    Ok(true) => sender.send_console_message(format!("&a{}&r added to the group &e{}", login, group)),
+                    Ok(false) => sender.send_console_message(format!("&ePlayer &a{}&e already has the group", login)),
+                    Err(e) => sender.send_console_message(format!("&c{}", e)),
+                }
+            } else {
+                match permissions.remove_player_group(&login, &group)? {
+                    true => sender.send_console_message(format!("&a{}&r removed from the group &e{}", login, group)),
+                    false => sender.send_console_message(format!("&ePlayer &a{}&e has no such group", login)),
+                }
+            }
+        }
+        "check" => {
+            let login = subcommand.get_arg::<String, _>("player")?.clone();
+            let node = subcommand.get_arg::<String, _>("permission")?.clone();
+            let allowed = world.resource::<Permissions>().has_permission(&login, &node);
+            sender.send_console_message(format!(
+                "Player &a{}&r {} &e{}",
+                login,
+                if allowed { "&ahas&r" } else { "&chas no&r" },
+                node
+            ));
+        }
+        "groups" => {
+            let permissions = world.resource::<Permissions>();
+            let groups: Vec<String> = permissions.iter_groups().cloned().collect();
+            if groups.is_empty() {
+                sender.send_console_message("&eNo groups in permissions.yml".to_string());
+                return Ok(());
+            }
+            sender.send_console_message(format!("Groups: &e{}", groups.join("&r, &e")));
+        }
+        _ => {
+            sender.send_console_message("Error".to_string());
+        }
+    }
+    Ok(())
+}
@@ -8,7 +8,8 @@ pub trait ConsoleSender {
     fn get_name(&self) -> String;
 }
 
-pub trait ConsoleSenderType: ConsoleSender + Display {
+/// Commands may answer from other threads
+pub trait ConsoleSenderType: ConsoleSender + Display + Send + Sync {
     fn as_any(&self) -> &dyn Any;
 }
 
@@ -1,14 +1,26 @@
-use self::{commands_executer::CommandsHandler, console_handler::ConsoleHandler, console_sender::Console};
-use crate::{launch_settings::LaunchSettings, runtime_plugin::RuntimePlugin};
+use self::{
+    commands_executer::{CommandExecuter, CommandsHandler},
+    console_commands::{
+        command_deop, command_op, command_parser_deop, command_parser_op, command_parser_perm, command_perm,
+    },
+    console_handler::ConsoleHandler,
+    console_sender::Console,
+    permissions::Permissions,
+};
+use crate::{
+    launch_settings::LaunchSettings, runtime_plugin::RuntimePlugin, utils::yaml_file::load_yaml_file_resource,
+};
 use bevy_app::{App, Plugin, Startup, Update};
 use bevy_ecs::{system::Res, system::ResMut, world::World};
 use completer::CustomCompleter;
 
 pub mod commands_executer;
 pub mod completer;
+pub mod console_commands;
 pub mod console_handler;
 pub mod console_sender;
 pub mod helper;
+pub mod permissions;
 
 pub struct ConsolePlugin;
 
@@ -20,11 +32,16 @@ impl Default for ConsolePlugin {
 
 impl Plugin for ConsolePlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(CommandsHandler::default());
+        let mut commands_handler = CommandsHandler::default();
+        commands_handler.add_command_executer(CommandExecuter::new(command_parser_op(), command_op));
+        commands_handler.add_command_executer(CommandExecuter::new(command_parser_deop(), command_deop));
+        commands_handler.add_command_executer(CommandExecuter::new(command_parser_perm(), command_perm));
+        app.insert_resource(commands_handler);
         app.insert_resource(ConsoleHandler::default());
+        app.insert_resource(Permissions::default());
         app.add_systems(Update, handler_console_input);
         app.add_systems(Update, handler_console_complete);
-        app.add_systems(Startup, run_handler);
+        app.add_systems(Startup, (load_yaml_file_resource::<Permissions>, run_handler));
     }
 }
 
@@ -0,0 +1,303 @@
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use super::console_sender::{Console, ConsoleSenderType};
+use crate::clients::client::Client;
+use crate::utils::yaml_file::{YamlFile, YamlFileResource};
+
+pub const PERMISSIONS_FILE: &str = "permissions.yml";
+
+/// Group of every player, even if it's not declared
+pub const DEFAULT_GROUP: &str = "default";
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct PermissionGroup {
+    #[serde(default)]
+    inherit: Vec<String>,
+
+    // Nodes like `command.give`; `command.*` and `*` match all nodes under them
+    #[serde(default)]
+    permissions: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+struct PermissionsData {
+    #[serde(default)]
+    groups: BTreeMap<String, PermissionGroup>,
+
+    // Groups of players by login
+    #[serde(default)]
+    players: BTreeMap<String, BTreeSet<String>>,
+
+    // Operators can run everything
+    #[serde(default)]
+    ops: BTreeSet<String>,
+}
+
+fn node_matches(pattern: &str, node: &str) -> bool {
+    if pattern == "*" || pattern == node {
+        return true;
+    }
+    match pattern.strip_suffix('*') {
+        Some(prefix) => prefix.ends_with('.') && node.starts_with(prefix),
+        None => false,
+    }
+}
+
+/// Permissions of players for console commands
+///
+/// Stored in `permissions.yml` inside the server data folder:
+/// ```yaml
+/// groups:
+///   default:
+///     permissions: [command.tps]
+///   moderator:
+///     inherit: [default]
+///     permissions: [command.kick, command.tp]
+/// players:
+///   alice: [moderator]
+/// ops: [bob]
+/// ```
+#[derive(Resource, Default)]
+pub struct Permissions {
+    data: YamlFile<PermissionsData>,
+}
+
+impl YamlFileResource for Permissions {
+    const SPAN: &'static str = "console.load_permissions";
+    const LOG_TARGET: &'static str = "console";
+
+    fn load(server_data_path: &Path) -> Result<Self, String> {
+        let permissions = Self {
+            data: YamlFile::load(server_data_path, PERMISSIONS_FILE)?,
+        };
+        permissions
+            .validate()
+            .map_err(|e| format!("&cfile &4{}&c error: {}", permissions.data.get_path().display(), e))?;
+        Ok(permissions)
+    }
+}
+
+impl Permissions {
+    /// Inherited and player groups must exist; inheritance can't be circular
+    fn validate(&self) -> Result<(), String> {
+        for (slug, group) in self.data.groups.iter() {
+            for parent in group.inherit.iter() {
+                if !self.has_group(parent) {
+                    return Err(format!("group \"{}\" inherits unknown group \"{}\"", slug, parent));
+                }
+            }
+            let mut path: Vec<&String> = vec![slug];
+            self.check_cycle(slug, &mut path)?;
+        }
+        for (login, groups) in self.data.players.iter() {
+            if let Some(group) = groups.iter().find(|group| !self.has_group(group)) {
+                return Err(format!("player \"{}\" has unknown group \"{}\"", login, group));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_cycle<'a>(&'a self, slug: &String, path: &mut Vec<&'a String>) -> Result<(), String> {
+        let Some(group) = self.data.groups.get(slug) else {
+            return Ok(());
+        };
+        for parent in group.inherit.iter() {
+            if path.contains(&parent) {
+                return Err(format!("group \"{}\" inherits itself through \"{}\"", parent, slug));
+            }
+            path.push(parent);
+            self.check_cycle(parent, path)?;
+            path.pop();
+        }
+        Ok(())
+    }
+
+    pub fn has_group(&self, slug: &str) -> bool {
+        slug == DEFAULT_GROUP || self.data.groups.contains_key(slug)
+    }
+
+    pub fn iter_groups(&self) -> impl Iterator<Item = &String> {
+        self.data.groups.keys()
+    }
+
+    pub fn is_op(&self, login: &str) -> bool {
+        self.data.ops.contains(login)
+    }
+
+    /// Returns false if nothing was changed
+    pub fn set_op(&mut self, login: &str, op: bool) -> Result<bool, String> {
+        let changed = match op {
+            true => self.data.ops.insert(login.to_string()),
+            false => self.data.ops.remove(login),
+        };
+        if changed {
+            self.data.save()?;
+        }
+        Ok(changed)
+    }
+
+    /// Groups of the player without the default one
+    pub fn get_player_groups(&self, login: &str) -> Vec<String> {
+        match self.data.players.get(login) {
+            Some(groups) => groups.iter().cloned().collect(),
+            None => Default::default(),
+        }
+    }
+
+    /// Returns false if the player already has the group
+    pub fn add_player_group(&mut self, login: &str, group: &str) -> Result<bool, String> {
+        if !self.data.groups.contains_key(group) {
+            return Err(format!("Group \"{}\" not found", group));
+        }
+        let added = self
+            .data
+            .players
+            .entry(login.to_string())
+            .or_default()
+            .insert(group.to_string());
+        if added {
+            self.data.save()?;
+        }
+        Ok(added)
+    }
+
+    /// Returns false if the player has no such group
+    pub fn remove_player_group(&mut self, login: &str, group: &str) -> Result<bool, String> {
+        let Some(groups) = self.data.players.get_mut(login) else {
+            return Ok(false);
+        };
+        let removed = groups.remove(group);
+        if groups.is_empty() {
+            self.data.players.remove(login);
+        }
+        if removed {
+            self.data.save()?;
+        }
+        Ok(removed)
+    }
+
+    pub fn has_permission(&self, login: &str, node: &str) -> bool {
+        if self.is_op(login) {
+            return true;
+        }
+
+        let mut queue: Vec<String> = vec![DEFAULT_GROUP.to_string()];
+        queue.extend(self.get_player_groups(login));
+        let mut visited: BTreeSet<String> = Default::default();
+        while let Some(slug) = queue.pop() {
+            if !visited.insert(slug.clone()) {
+                continue;
+            }
+            let Some(group) = self.data.groups.get(&slug) else {
+                continue;
+            };
+            if group.permissions.iter().any(|pattern| node_matches(pattern, node)) {
+                return true;
+            }
+            queue.extend(group.inherit.iter().cloned());
+        }
+        false
+    }
+
+    /// The server console can run everything; clients without a login can run nothing,
+    /// even what the default group allows
+    pub fn is_allowed(&self, sender: &dyn ConsoleSenderType, node: &str) -> bool {
+        if sender.as_any().is::<Console>() {
+            return true;
+        }
+        if let Some(client) = sender.as_any().downcast_ref::<Client>() {
+            let Some(client_info) = client.get_client_info() else {
+                return false;
+            };
+            return self.has_permission(client_info.get_login(), node);
+        }
+        self.has_permission(&sender.get_name(), node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{node_matches, Permissions};
+    use crate::clients::client::Client;
+    use crate::console::console_sender::Console;
+    use crate::utils::yaml_file::YamlFile;
+
+    fn permissions(yaml: &str) -> Result<Permissions, String> {
+        let permissions = Permissions {
+            data: YamlFile::new(Default::default(), serde_yaml::from_str(yaml).unwrap()),
+        };
+        permissions.validate()?;
+        Ok(permissions)
+    }
+
+    #[test]
+    fn test_node_matches() {
+        assert!(node_matches("*", "command.give"));
+        assert!(node_matches("command.*", "command.give"));
+        assert!(node_matches("command.give", "command.give"));
+        assert!(!node_matches("command.give", "command.gives"));
+        assert!(!node_matches("command*", "commander.give"));
+        assert!(!node_matches("debug.*", "command.give"));
+    }
+
+    #[test]
+    fn test_groups_inheritance() {
+        let permissions = permissions(
+            r#"
+groups:
+  default:
+    permissions: [command.tps]
+  moderator:
+    inherit: [default]
+    permissions: [command.kick]
+  admin:
+    inherit: [moderator]
+    permissions: [command.*]
+players:
+  alice: [moderator]
+  carol: [admin]
+ops: [bob]
+"#,
+        )
+        .unwrap();
+
+        assert!(permissions.has_permission("guest", "command.tps"));
+        assert!(!permissions.has_permission("guest", "command.kick"));
+        assert!(permissions.has_permission("alice", "command.kick"));
+        assert!(!permissions.has_permission("alice", "command.give"));
+        assert!(permissions.has_permission("carol", "command.give"));
+        assert!(!permissions.has_permission("carol", "debug.tps"));
+        assert!(permissions.has_permission("bob", "debug.tps"));
+    }
+
+    #[test]
+    fn test_invalid_groups() {
+        let circular = r#"
+groups:
+  a: { inherit: [b] }
+  b: { inherit: [a] }
+"#;
+        assert!(permissions(circular).is_err());
+
+        let unknown = r#"
+players:
+  alice: [admin]
+"#;
+        assert!(permissions(unknown).is_err());
+    }
+
+    #[test]
+    fn test_client_without_login() {
+        let permissions = permissions("groups:\n  default:\n    permissions: [command.tps]\n").unwrap();
+        assert!(permissions.has_permission("-", "command.tps"));
+
+        // Client which didn't send ConnectionInfo or didn't pass the auth yet
+        assert!(!permissions.is_allowed(&Client::test(), "command.tps"));
+        assert!(permissions.is_allowed(&Console, "command.tps"));
+    }
+}
@@ -81,7 +81,10 @@ impl Plugin for DebugPlugin {
         );
 
         let mut commands_handler = app.world_mut().get_resource_mut::<CommandsHandler>().unwrap();
-        commands_handler.add_command_executer(CommandExecuter::new(command_parser_tps(), command_tps));
-        commands_handler.add_command_executer(CommandExecuter::new(command_parser_color(), command_color));
+        commands_handler
+            .add_command_executer(CommandExecuter::new(command_parser_tps(), command_tps).permission("debug.tps"));
+        commands_handler.add_command_executer(
+            CommandExecuter::new(command_parser_color(), command_color).permission("debug.colors"),
+        );
     }
 }
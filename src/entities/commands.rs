@@ -8,7 +8,12 @@ use common::chunks::block_position::BlockPositionTrait;
 use network::messages::{NetworkMessageType, ServerMessages};
 
 use super::{
-    entity::Position, entity_tag::EntityTagComponent, skin::EntitySkinComponent, traits::IEntityNetworkComponent,
+    entity::Position,
+    entity_tag::EntityTagComponent,
+    health::{update_player_health, Health},
+    skin::EntitySkinComponent,
+    stats::EntityStats,
+    traits::IEntityNetworkComponent,
     EntityComponent,
 };
 
@@ -90,6 +95,21 @@ impl Command for UpdatePlayerComponent {
                     }
                 }
             }
+            EntityComponent::Health(health) => {
+                is_send_to_player = true;
+                match health {
+                    Some(new_health) => match entity.get_mut::<Health>() {
+                        Some(mut old) => *old = new_health.clone(),
+                        None => {
+                            entity.insert(new_health.clone());
+                        }
+                    },
+                    None => {
+                        entity.remove::<Health>();
+                    }
+                }
+                sync_update_entity_component::<Health>(&*world_manager, world_entity.get_entity())
+            }
         }
 
         if is_send_to_player {
@@ -102,6 +122,42 @@ impl Command for UpdatePlayerComponent {
     }
 }
 
+/// Replaces the stats of the player entity; stats aren't sent to clients, only the maximum health
+pub struct UpdatePlayerStats {
+    client: Client,
+    stats: EntityStats,
+}
+
+impl UpdatePlayerStats {
+    pub fn create(client: Client, stats: EntityStats) -> Self {
+        Self { client, stats }
+    }
+}
+
+impl Command for UpdatePlayerStats {
+    fn apply(self, world: &mut World) {
+        // Player could leave the world before the command is applied
+        let Some(world_entity) = self.client.get_world_entity() else {
+            return;
+        };
+        let worlds_manager = world.resource::<SharedWorldsManager>();
+        let worlds_manager = worlds_manager.read();
+        let Some(mut world_manager) = worlds_manager.get_world_manager_mut(world_entity.get_world_slug()) else {
+            return;
+        };
+        let ecs = world_manager.get_ecs_mut();
+        if ecs.get_entity(world_entity.get_entity()).is_none() {
+            return;
+        }
+        let max_health = self.stats.get_max_health();
+        ecs.entity_mut(world_entity.get_entity()).insert(self.stats);
+
+        update_player_health(&mut *world_manager, &self.client, world_entity.get_entity(), |health| {
+            health.set_max_health(max_health)
+        });
+    }
+}
+
 /// Sync entity component for all watchers
 pub(crate) fn sync_update_entity_component<T: Component + IEntityNetworkComponent>(
     world_manager: &WorldManager,
@@ -0,0 +1,320 @@
+use ahash::AHashMap;
+use bevy::prelude::{Component, Entity};
+use bevy::time::Time;
+use bevy_ecs::system::Res;
+use common::chunks::chunk_storage::{ChunkStorage, DroppedItemData};
+use common::chunks::{block_position::BlockPositionTrait, chunk_position::ChunkPosition};
+use common::inventory::item::{ClientItem, Item};
+use network::entities::EntityNetworkComponent;
+use network::messages::InventorySlotChange;
+use std::time::Duration;
+
+use super::entity::{IntoServerPosition, Position, Rotation};
+use super::traits::IEntityNetworkComponent;
+use crate::{
+    clients::client::Client,
+    inventory::{
+        audit_log::{snapshot, AuditActor, AuditInventory},
+        SharedInventoryManager,
+    },
+    items_manager::items_manager::{ItemsManager, SharedItemsManager},
+    network::{
+        events::on_inventory_action::InventoryTarget,
+        sync_entities::{sync_entity_despawn, sync_entity_spawn},
+        sync_inventory::send_inventory_changes_to_client,
+    },
+    runtime_plugin::RuntimePlugin,
+    server_config::ServerConfig,
+    worlds::{ecs::Ecs, world_manager::WorldManager, worlds_manager::SharedWorldsManager},
+};
+
+/// Distance from the player position to items which he picks up
+const PICKUP_DISTANCE: f32 = 1.5;
+
+/// Dropped item can't be picked up at once; otherwise it would return to the player
+const PICKUP_DELAY: Duration = Duration::from_secs(2);
+
+/// Distance in front of the player where the dropped item appears
+const DROP_DISTANCE: f32 = 1.0;
+
+/// Item lying in the world
+#[derive(Component, Clone)]
+pub struct DroppedItemComponent {
+    item: Item,
+
+    // Sent to clients; converted once when the item is spawned
+    client_item: ClientItem,
+
+    // Time since the drop, including the time stored with the chunk
+    age: Duration,
+}
+
+impl DroppedItemComponent {
+    pub fn create(item: Item, client_item: ClientItem, age: Duration) -> Self {
+        Self { item, client_item, age }
+    }
+
+    pub fn get_item(&self) -> &Item {
+        &self.item
+    }
+
+    pub fn get_age(&self) -> Duration {
+        self.age
+    }
+
+    pub fn can_be_picked_up(&self) -> bool {
+        self.age >= PICKUP_DELAY
+    }
+}
+
+impl IEntityNetworkComponent for DroppedItemComponent {
+    fn to_network(&self) -> EntityNetworkComponent {
+        EntityNetworkComponent::DroppedItem(Some(self.client_item.clone()))
+    }
+
+    fn _get_empty() -> EntityNetworkComponent {
+        EntityNetworkComponent::DroppedItem(None)
+    }
+}
+
+/// Position on the ground in front of the player
+pub fn get_drop_position(position: &Position, rotation: &Rotation) -> Position {
+    let yaw = rotation.get_yaw();
+    Position::new(
+        position.get_x() - yaw.sin() * DROP_DISTANCE,
+        position.get_y(),
+        position.get_z() - yaw.cos() * DROP_DISTANCE,
+    )
+}
+
+/// Spawns the item entity and starts streaming it to watchers of its chunk
+pub fn spawn_dropped_item(
+    world_manager: &mut WorldManager,
+    items_manager: &ItemsManager,
+    position: Position,
+    item: Item,
+    age: Duration,
+) -> Entity {
+    let component = DroppedItemComponent::create(item.clone(), items_manager.to_client_item(&item), age);
+    let entity = world_manager.get_ecs_mut().spawn(
+        (position, Rotation::default(), component),
+        position.get_chunk_position(),
+    );
+    sync_entity_spawn(world_manager, entity);
+    entity
+}
+
+/// Spawns items stored with the loaded chunk
+pub fn spawn_stored_dropped_items(
+    world_manager: &mut WorldManager,
+    items_manager: &ItemsManager,
+    chunk_storage: &ChunkStorage,
+) {
+    for dropped_item in chunk_storage.get_dropped_items() {
+        spawn_dropped_item(
+            world_manager,
+            items_manager,
+            dropped_item.get_position().to_server(),
+            dropped_item.get_item().clone(),
+            Duration::from_secs_f32(dropped_item.get_age()),
+        );
+    }
+}
+
+/// Storage data of the dropped item entity
+pub fn to_dropped_item_data(position: &Position, dropped_item: &DroppedItemComponent) -> DroppedItemData {
+    DroppedItemData::create(
+        dropped_item.get_item().clone(),
+        position.to_network(),
+        dropped_item.get_age().as_secs_f32(),
+    )
+}
+
+/// Despawns dropped items of the chunk and returns them for the chunk storage
+pub fn take_chunk_dropped_items(ecs: &mut Ecs, chunk_position: &ChunkPosition) -> Vec<DroppedItemData> {
+    let mut dropped_items: Vec<DroppedItemData> = Default::default();
+    let mut despawn: Vec<Entity> = Default::default();
+    let Ok(entities) = ecs.get_chunk_entities(chunk_position) else {
+        return dropped_items;
+    };
+    for entity_ref in entities {
+        let (Some(position), Some(dropped_item)) =
+            (entity_ref.get::<Position>(), entity_ref.get::<DroppedItemComponent>())
+        else {
+            continue;
+        };
+        dropped_items.push(to_dropped_item_data(position, dropped_item));
+        despawn.push(entity_ref.id());
+    }
+    for entity in despawn {
+        ecs.despawn(entity, Some(*chunk_position));
+    }
+    dropped_items
+}
+
+/// Dropped items of all chunks for the world save
+pub fn collect_dropped_items(ecs: &Ecs) -> AHashMap<ChunkPosition, Vec<DroppedItemData>> {
+    let mut dropped_items: AHashMap<ChunkPosition, Vec<DroppedItemData>> = Default::default();
+    for entity in ecs.get_entities_with::<DroppedItemComponent>() {
+        let entity_ref = ecs.get_entity(entity).unwrap();
+        let position = entity_ref.get::<Position>().unwrap();
+        let dropped_item = entity_ref.get::<DroppedItemComponent>().unwrap();
+        dropped_items
+            .entry(position.get_chunk_position())
+            .or_default()
+            .push(to_dropped_item_data(position, dropped_item));
+    }
+    dropped_items
+}
+
+/// Ages dropped items, despawns expired ones and gives items to players nearby
+pub fn update_dropped_items(
+    worlds_manager: Res<SharedWorldsManager>,
+    items_manager: Res<SharedItemsManager>,
+    inventory_manager: Res<SharedInventoryManager>,
+    server_config: Res<ServerConfig>,
+    time: Res<Time>,
+) {
+    let _s = crate::span!("entities.update_dropped_items");
+    if RuntimePlugin::is_stopped() {
+        return;
+    }
+
+    let lifetime = server_config.get_dropped_item_lifetime();
+    let worlds_manager = worlds_manager.read();
+
+    // Audit log is written after the worlds are released
+    let mut picked_up: Vec<(Client, Vec<Option<Item>>, Vec<Option<Item>>)> = Default::default();
+    for mut world_manager in worlds_manager.iter_worlds_mut() {
+        let ecs = world_manager.get_ecs_mut();
+        let items = ecs.get_entities_with::<DroppedItemComponent>();
+        if items.is_empty() {
+            continue;
+        }
+
+        let players: Vec<(Position, Client)> = ecs
+            .get_entities_with::<Client>()
+            .into_iter()
+            .filter_map(|entity| {
+                let entity_ref = ecs.get_entity(entity)?;
+                Some((*entity_ref.get::<Position>()?, entity_ref.get::<Client>()?.clone()))
+            })
+            .collect();
+
+        let mut despawn: Vec<Entity> = Default::default();
+        for entity in items {
+            let mut entity_mut = ecs.entity_mut(entity);
+            let position = *entity_mut.get::<Position>().unwrap();
+            let mut dropped_item = entity_mut.get_mut::<DroppedItemComponent>().unwrap();
+            dropped_item.age += time.delta();
+
+            if dropped_item.get_age() >= lifetime {
+                despawn.push(entity);
+                continue;
+            }
+            if !dropped_item.can_be_picked_up() {
+                continue;
+            }
+
+            let nearest = players
+                .iter()
+                .map(|(player_position, client)| (player_position.distance(&position), client))
+                .filter(|(distance, _client)| *distance <= PICKUP_DISTANCE)
+                .min_by(|a, b| a.0.total_cmp(&b.0));
+            if let Some((_distance, client)) = nearest {
+                if let Some((before, after)) = pick_up_item(client, dropped_item.get_item(), &items_manager) {
+                    picked_up.push((client.clone(), before, after));
+                    despawn.push(entity);
+                }
+            }
+        }
+
+        for entity in despawn {
+            sync_entity_despawn(&*world_manager, entity);
+            let ecs = world_manager.get_ecs_mut();
+            let chunk_position = ecs
+                .get_entity(entity)
+                .and_then(|entity_ref| entity_ref.get::<Position>().map(|p| p.get_chunk_position()));
+            ecs.despawn(entity, chunk_position);
+        }
+    }
+    drop(worlds_manager);
+
+    for (client, before, after) in picked_up {
+        let Some(login) = client.get_client_info().map(|info| info.get_login().clone()) else {
+            continue;
+        };
+        inventory_manager.write().get_audit_log_mut().record_diff(
+            &AuditActor::Player(login.clone()),
+            &AuditInventory::Player(login),
+            &before,
+            &after,
+        );
+    }
+}
+
+/// Returns slots of the player inventory before and after the pickup;
+/// None if the whole stack doesn't fit into it
+fn pick_up_item(
+    client: &Client,
+    item: &Item,
+    items_manager: &SharedItemsManager,
+) -> Option<(Vec<Option<Item>>, Vec<Option<Item>>)> {
+    let max_stack_size = items_manager.read().get_max_stack_size(item);
+    let mut changes: Vec<InventorySlotChange> = Default::default();
+    let picked_up = client.with_player_data_mut(|player_data| {
+        // The part of the stack would be duplicated, so the check goes on a copy
+        let mut preview = player_data.get_inventory().clone();
+        if preview
+            .add_item(item.clone(), max_stack_size, |_slot, _item| {})
+            .is_err()
+        {
+            return None;
+        }
+
+        let before = snapshot(player_data.get_inventory());
+        player_data
+            .get_inventory_mut()
+            .add_item(item.clone(), max_stack_size, |slot, updated_item| {
+                changes.push(InventorySlotChange {
+                    slot,
+                    item: updated_item.map(|item| items_manager.read().to_client_item(item)),
+                });
+            })
+            .ok()?;
+        Some((before, snapshot(player_data.get_inventory())))
+    });
+    let slots = picked_up.flatten()?;
+
+    send_inventory_changes_to_client(client, &InventoryTarget::Client(client.get_client_id()), changes);
+    Some(slots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_drop_position, take_chunk_dropped_items};
+    use crate::entities::entity::{Position, Rotation};
+    use crate::worlds::ecs::Ecs;
+    use common::chunks::chunk_position::ChunkPosition;
+
+    #[test]
+    fn test_drop_position() {
+        let position = Position::new(0.0, 10.0, 0.0);
+
+        let dropped = get_drop_position(&position, &Rotation::new(0.0, 0.0));
+        assert!(dropped.get_x().abs() < 0.001);
+        assert_eq!(dropped.get_y(), 10.0);
+        assert!((dropped.get_z() + 1.0).abs() < 0.001);
+
+        let dropped = get_drop_position(&position, &Rotation::new(0.0, std::f32::consts::FRAC_PI_2));
+        assert!((dropped.get_x() + 1.0).abs() < 0.001);
+        assert!(dropped.get_z().abs() < 0.001);
+    }
+
+    #[test]
+    fn test_take_dropped_items_without_entities() {
+        let mut ecs = Ecs::new();
+        let dropped_items = take_chunk_dropped_items(&mut ecs, &ChunkPosition::new(0, 0));
+        assert!(dropped_items.is_empty());
+    }
+}
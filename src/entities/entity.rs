@@ -2,7 +2,10 @@ use bevy_ecs::prelude::Component;
 use common::chunks::position::Vector3 as NetworkVector3;
 use common::chunks::rotation::Rotation as NetworkRotation;
 use common::{
-    chunks::{block_position::BlockPositionTrait, chunk_position::ChunkPosition},
+    chunks::{
+        block_position::{BlockPosition, BlockPositionTrait},
+        chunk_position::ChunkPosition,
+    },
     utils::fix_chunk_loc_pos,
 };
 
@@ -28,13 +31,34 @@ impl PartialEq for Position {
 }
 
 impl Position {
-    pub fn new(x: PositionFloatType, y: PositionFloatType, z: PositionFloatType) -> Self {
+    pub const fn new(x: PositionFloatType, y: PositionFloatType, z: PositionFloatType) -> Self {
         Self { x, y, z }
     }
 
     pub fn to_network(&self) -> NetworkVector3 {
         NetworkVector3::new(self.x, self.y, self.z)
     }
+
+    pub fn get_x(&self) -> PositionFloatType {
+        self.x
+    }
+
+    pub fn get_y(&self) -> PositionFloatType {
+        self.y
+    }
+
+    pub fn get_z(&self) -> PositionFloatType {
+        self.z
+    }
+
+    pub fn distance(&self, other: &Position) -> PositionFloatType {
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2) + (self.z - other.z).powi(2)).sqrt()
+    }
+
+    /// Block containing this position
+    pub fn to_block_position(&self) -> BlockPosition {
+        BlockPosition::new(self.x.floor() as i64, self.y.floor() as i64, self.z.floor() as i64)
+    }
 }
 
 impl BlockPositionTrait for Position {
@@ -64,7 +88,7 @@ impl Rotation {
         Self { pitch, yaw }
     }
 
-    pub fn _get_yaw(&self) -> &PositionFloatType {
+    pub fn get_yaw(&self) -> &PositionFloatType {
         &self.yaw
     }
 
@@ -1,6 +1,6 @@
 use bevy::prelude::Res;
 use bevy_ecs::message::MessageReader;
-use common::{chunks::position::Vector3, plugin_api::events::player_spawn::PlayerSpawnEvent as PluginPlayerSpawnEvent};
+use common::plugin_api::events::player_spawn::PlayerSpawnEvent as PluginPlayerSpawnEvent;
 use strum::IntoEnumIterator;
 
 use crate::{
@@ -8,6 +8,7 @@ use crate::{
     entities::{
         entity::{Position, Rotation},
         entity_tag::EntityTagComponent,
+        health::Health,
         skin::EntitySkinComponent,
         EntityComponent,
     },
@@ -19,6 +20,9 @@ use crate::{
     worlds::worlds_manager::SharedWorldsManager,
 };
 
+/// Radius in blocks around the spawn position to look for a safe place
+const SAFE_SPAWN_RADIUS: i64 = 8;
+
 /// Спавн игрока в мире
 ///
 /// Вызывается при успешном подключении игрока если чанк прогружен
@@ -48,20 +52,18 @@ pub(crate) fn on_player_spawn(
             *entity_ref.get::<Position>().unwrap()
         };
 
-        let origin = current_position.to_network();
-        let solid = world_manager.get_chunks_map().first_solid_block(
-            origin,
-            Vector3::new(0.0, -1.0, 0.0),
-            512.0,
-            &server_settings,
-        );
-
-        // Find first solid block under position
-        let spawn_position = match solid {
-            Some(block_pos) => {
-                let p = block_pos.get_position();
-                Position::new(p.x, p.y + 1.0, p.z)
+        // Find a safe place to stand around the position
+        let origin = current_position.to_block_position();
+        let safe_spawn = world_manager
+            .get_chunks_map()
+            .find_safe_spawn(&origin, SAFE_SPAWN_RADIUS, &server_settings);
+        let spawn_position = match safe_spawn {
+            Some(block_pos) if block_pos.x == origin.x && block_pos.z == origin.z => {
+                let current = current_position.to_network();
+                Position::new(current.x, block_pos.y as f32, current.z)
             }
+            // Another column was chosen; stand at the center of it
+            Some(block_pos) => Position::new(block_pos.x as f32 + 0.5, block_pos.y as f32, block_pos.z as f32 + 0.5),
             None => current_position,
         };
 
@@ -89,6 +91,11 @@ pub(crate) fn on_player_spawn(
                         components.push(EntityComponent::Skin(Some(skin.clone())));
                     }
                 }
+                EntityComponent::Health(_) => {
+                    if let Some(health) = entity_ref.get::<Health>() {
+                        components.push(EntityComponent::Health(Some(health.clone())));
+                    }
+                }
             }
         }
 
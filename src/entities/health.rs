@@ -0,0 +1,415 @@
+use bevy::prelude::{Component, Entity, World};
+use bevy_ecs::system::Command;
+use common::inventory::item::Item;
+use network::{
+    entities::{EntityHealthData, EntityNetworkComponent},
+    messages::{InventorySlotChange, NetworkMessageType, ServerMessages},
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, time::Duration};
+
+use super::{
+    commands::{sync_update_entity_component, UpdatePlayerComponent, UpdatePlayerStats},
+    dropped_item::spawn_dropped_item,
+    entity::{Position, Rotation},
+    skin::EntitySkinComponent,
+    traits::IEntityNetworkComponent,
+    EntityComponent,
+};
+use crate::{
+    clients::client::Client,
+    inventory::{
+        audit_log::{AuditActor, AuditInventory},
+        SharedInventoryManager,
+    },
+    items_manager::items_manager::{ItemsManager, SharedItemsManager},
+    network::{events::on_inventory_action::InventoryTarget, sync_inventory::send_inventory_changes_to_client},
+    plugins::server_settings::ServerSettings,
+    runtime_plugin::RuntimePlugin,
+    server_config::ServerConfig,
+    worlds::{commands::teleport_player, world_manager::WorldManager, worlds_manager::SharedWorldsManager},
+};
+
+/// Height in blocks the player can fall without damage
+const SAFE_FALL_DISTANCE: f32 = 3.0;
+
+/// Players below this height are killed by the void
+const VOID_LEVEL: f32 = -16.0;
+
+/// Offset below the feet to find the block the player stands on
+const GROUND_OFFSET: f32 = 0.05;
+
+/// What hurt the entity
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DamageSource {
+    Fall,
+    Void,
+
+    // Client id of the attacker
+    Player(u64),
+
+    // Plugin slug
+    Plugin(String),
+
+    // Name of the console command sender
+    Command(String),
+}
+
+impl Display for DamageSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DamageSource::Fall => write!(f, "fall"),
+            DamageSource::Void => write!(f, "void"),
+            DamageSource::Player(client_id) => write!(f, "player #{}", client_id),
+            DamageSource::Plugin(slug) => write!(f, "plugin \"{}\"", slug),
+            DamageSource::Command(sender) => write!(f, "{}", sender),
+        }
+    }
+}
+
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct Health {
+    health: f32,
+    max_health: f32,
+
+    // Reason of the death
+    last_damage: Option<DamageSource>,
+}
+
+impl Health {
+    pub fn create(health: f32, max_health: f32) -> Self {
+        let max_health = max_health.max(1.0);
+        Self {
+            health: health.clamp(0.0, max_health),
+            max_health,
+            last_damage: None,
+        }
+    }
+
+    pub fn get_health(&self) -> f32 {
+        self.health
+    }
+
+    pub fn get_max_health(&self) -> f32 {
+        self.max_health
+    }
+
+    pub fn get_last_damage(&self) -> Option<&DamageSource> {
+        self.last_damage.as_ref()
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.health <= 0.0
+    }
+
+    pub fn damage(&mut self, amount: f32, source: DamageSource) {
+        self.health = (self.health - amount.max(0.0)).max(0.0);
+        self.last_damage = Some(source);
+    }
+
+    pub fn heal(&mut self, amount: f32) {
+        self.health = (self.health + amount.max(0.0)).min(self.max_health);
+    }
+
+    /// Restores the full health after the death
+    pub fn restore(&mut self) {
+        self.health = self.max_health;
+        self.last_damage = None;
+    }
+
+    /// Current health is cut if it's above the new maximum
+    pub fn set_max_health(&mut self, max_health: f32) {
+        self.max_health = max_health.max(1.0);
+        self.health = self.health.min(self.max_health);
+    }
+}
+
+impl IEntityNetworkComponent for Health {
+    fn to_network(&self) -> EntityNetworkComponent {
+        EntityNetworkComponent::Health(Some(EntityHealthData::create(self.health, self.max_health)))
+    }
+
+    fn _get_empty() -> EntityNetworkComponent {
+        EntityNetworkComponent::Health(None)
+    }
+}
+
+/// Highest point of the current fall; the damage is dealt on landing
+#[derive(Component, Clone, Default)]
+pub struct FallTracker {
+    highest_y: Option<f32>,
+}
+
+impl FallTracker {
+    /// Returns the fall damage when the player lands
+    pub fn update(&mut self, y: f32, on_ground: bool) -> Option<f32> {
+        if !on_ground {
+            self.highest_y = Some(self.highest_y.map_or(y, |highest| highest.max(y)));
+            return None;
+        }
+
+        let distance = self.highest_y.take()? - y;
+        match distance > SAFE_FALL_DISTANCE {
+            true => Some(distance - SAFE_FALL_DISTANCE),
+            false => None,
+        }
+    }
+}
+
+/// Changes the health of the player, saves it into the player data
+/// and sends it to the player and watchers.
+///
+/// Returns the updated health; none if the entity has no health.
+pub(crate) fn update_player_health(
+    world_manager: &mut WorldManager,
+    client: &Client,
+    entity: Entity,
+    f: impl FnOnce(&mut Health),
+) -> Option<Health> {
+    let health = {
+        let ecs = world_manager.get_ecs_mut();
+        ecs.get_entity(entity)?;
+        let mut entity_mut = ecs.entity_mut(entity);
+        let mut health = entity_mut.get_mut::<Health>()?;
+        f(&mut health);
+        health.clone()
+    };
+
+    client.with_player_data_mut(|player_data| player_data.set_health(Some(health.get_health())));
+    sync_update_entity_component::<Health>(world_manager, entity);
+    let message = ServerMessages::UpdatePlayerComponent {
+        component: health.to_network(),
+    };
+    client.send_message(NetworkMessageType::ReliableOrdered, &message);
+    Some(health)
+}
+
+/// Damages the spawned player; the death is handled by `handle_player_deaths`
+pub(crate) fn damage_player(
+    world_manager: &mut WorldManager,
+    client: &Client,
+    amount: f32,
+    source: DamageSource,
+) -> Option<Health> {
+    let world_entity = client.get_world_entity()?;
+    if world_entity.get_world_slug() != world_manager.get_slug() {
+        return None;
+    }
+    update_player_health(world_manager, client, world_entity.get_entity(), |health| {
+        if !health.is_dead() {
+            health.damage(amount, source);
+        }
+    })
+}
+
+pub(crate) fn heal_player(world_manager: &mut WorldManager, client: &Client, amount: f32) -> Option<Health> {
+    let world_entity = client.get_world_entity()?;
+    if world_entity.get_world_slug() != world_manager.get_slug() {
+        return None;
+    }
+    update_player_health(world_manager, client, world_entity.get_entity(), |health| {
+        if !health.is_dead() {
+            health.heal(amount);
+        }
+    })
+}
+
+/// Fall damage on landing and death in the void after the player moved
+pub(crate) fn apply_movement_damage(
+    world_manager: &mut WorldManager,
+    client: &Client,
+    entity: Entity,
+    position: &Position,
+    server_settings: &ServerSettings,
+) {
+    if position.get_y() < VOID_LEVEL {
+        damage_player(world_manager, client, f32::INFINITY, DamageSource::Void);
+        return;
+    }
+
+    let below = Position::new(position.get_x(), position.get_y() - GROUND_OFFSET, position.get_z());
+    let on_ground = world_manager
+        .get_chunks_map()
+        .is_solid_block(&below.to_block_position(), server_settings);
+    let fall_damage = {
+        let mut entity_mut = world_manager.get_ecs_mut().entity_mut(entity);
+        match entity_mut.get_mut::<FallTracker>() {
+            Some(mut fall_tracker) => fall_tracker.update(position.get_y(), on_ground),
+            None => None,
+        }
+    };
+    if let Some(fall_damage) = fall_damage {
+        damage_player(world_manager, client, fall_damage, DamageSource::Fall);
+    }
+}
+
+/// Drops the inventory of dead players if it's enabled and respawns them at the spawn of their world
+pub(crate) fn handle_player_deaths(world: &mut World) {
+    let _s = crate::span!("entities.handle_player_deaths");
+    if RuntimePlugin::is_stopped() {
+        return;
+    }
+
+    let drop_inventory = world.resource::<ServerConfig>().get_drop_inventory_on_death();
+    let mut respawns: Vec<(Client, String, Position, Option<DamageSource>)> = Default::default();
+    let mut dropped: Vec<(Client, Vec<(usize, Item)>)> = Default::default();
+    {
+        let items_manager = world.resource::<SharedItemsManager>().read();
+        let worlds_manager = world.resource::<SharedWorldsManager>().read();
+        for mut world_manager in worlds_manager.iter_worlds_mut() {
+            let ecs = world_manager.get_ecs();
+            let dead: Vec<(Entity, Client, Position, Option<DamageSource>)> = ecs
+                .get_entities_with::<Health>()
+                .into_iter()
+                .filter_map(|entity| {
+                    let entity_ref = ecs.get_entity(entity)?;
+                    let health = entity_ref.get::<Health>()?;
+                    if !health.is_dead() {
+                        return None;
+                    }
+                    Some((
+                        entity,
+                        entity_ref.get::<Client>()?.clone(),
+                        *entity_ref.get::<Position>()?,
+                        health.get_last_damage().cloned(),
+                    ))
+                })
+                .collect();
+
+            for (entity, client, position, source) in dead {
+                if drop_inventory {
+                    let items = drop_player_inventory(&mut *world_manager, &items_manager, &client, position);
+                    dropped.push((client.clone(), items));
+                }
+                update_player_health(&mut *world_manager, &client, entity, Health::restore);
+                respawns.push((
+                    client,
+                    world_manager.get_slug().clone(),
+                    world_manager.get_spawn_position(),
+                    source,
+                ));
+            }
+        }
+    }
+
+    for (client, items) in dropped {
+        let Some(login) = client.get_client_info().map(|info| info.get_login().clone()) else {
+            continue;
+        };
+        let inventory_manager = world.resource::<SharedInventoryManager>();
+        let mut inventory_manager = inventory_manager.write();
+        for (slot, item) in items {
+            inventory_manager.get_audit_log_mut().record(
+                &AuditActor::Server,
+                &AuditInventory::Player(login.clone()),
+                slot,
+                Some(item),
+                None,
+            );
+        }
+    }
+
+    for (client, world_slug, spawn_position, source) in respawns {
+        let source = match source {
+            Some(source) => source.to_string(),
+            None => "unknown".to_string(),
+        };
+        log::info!(target: "entities", "Player &a{}&r died: &e{}", client, source);
+        client.send_console_message(format!("&cYou died: {}", source));
+
+        if drop_inventory {
+            let items_manager = world.resource::<SharedItemsManager>();
+            let skin = client.get_player_skin(items_manager);
+            let stats = client.get_player_stats(items_manager);
+            UpdatePlayerComponent::_create(
+                client.clone(),
+                EntityComponent::Skin(Some(EntitySkinComponent::create(skin))),
+            )
+            .apply(world);
+            if let Some(stats) = stats {
+                UpdatePlayerStats::create(client.clone(), stats).apply(world);
+            }
+        }
+
+        if let Err(e) = teleport_player(world, &client, &world_slug, spawn_position, Rotation::new(0.0, 0.0)) {
+            log::error!(target: "entities", "&cRespawn of &4{}&c failed: {}", client, e);
+        }
+    }
+}
+
+/// All items of the player inventory are dropped where he died; returns them with their slots
+fn drop_player_inventory(
+    world_manager: &mut WorldManager,
+    items_manager: &ItemsManager,
+    client: &Client,
+    position: Position,
+) -> Vec<(usize, Item)> {
+    let items = client.with_player_data_mut(|player_data| {
+        let inventory = player_data.get_inventory_mut();
+        (0..inventory.slots_len())
+            .filter_map(|slot| inventory.take_slot(slot).map(|item| (slot, item)))
+            .collect::<Vec<(usize, Item)>>()
+    });
+    let Some(items) = items else {
+        return Default::default();
+    };
+
+    let changes: Vec<InventorySlotChange> = items
+        .iter()
+        .map(|(slot, _item)| InventorySlotChange {
+            slot: *slot,
+            item: None,
+        })
+        .collect();
+    send_inventory_changes_to_client(client, &InventoryTarget::Client(client.get_client_id()), changes);
+
+    for (_slot, item) in items.iter() {
+        spawn_dropped_item(world_manager, items_manager, position, item.clone(), Duration::ZERO);
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DamageSource, FallTracker, Health};
+
+    #[test]
+    fn test_damage_and_heal() {
+        let mut health = Health::create(25.0, 20.0);
+        assert_eq!(health.get_health(), 20.0);
+
+        health.damage(5.0, DamageSource::Fall);
+        assert_eq!(health.get_health(), 15.0);
+        assert_eq!(health.get_last_damage(), Some(&DamageSource::Fall));
+
+        health.heal(100.0);
+        assert_eq!(health.get_health(), 20.0);
+
+        health.set_max_health(10.0);
+        assert_eq!(health.get_health(), 10.0);
+
+        health.damage(50.0, DamageSource::Void);
+        assert!(health.is_dead());
+        assert_eq!(health.get_health(), 0.0);
+
+        health.restore();
+        assert_eq!(health.get_health(), 10.0);
+        assert_eq!(health.get_last_damage(), None);
+    }
+
+    #[test]
+    fn test_fall_tracker() {
+        let mut tracker = FallTracker::default();
+        assert_eq!(tracker.update(64.0, true), None);
+
+        // Jump on the same level
+        assert_eq!(tracker.update(65.0, false), None);
+        assert_eq!(tracker.update(64.0, true), None);
+
+        assert_eq!(tracker.update(70.0, false), None);
+        assert_eq!(tracker.update(66.0, false), None);
+        assert_eq!(tracker.update(60.0, true), Some(7.0));
+        assert_eq!(tracker.update(60.0, true), None);
+    }
+}
@@ -1,14 +1,18 @@
 use entity_tag::EntityTagComponent;
+use health::Health;
 use network::entities::{EntityNetworkComponent, EntitySkinData};
 use skin::EntitySkinComponent;
 use strum_macros::EnumIter;
 use traits::IEntityNetworkComponent;
 
 pub mod commands;
+pub mod dropped_item;
 pub mod entity;
 pub mod entity_tag;
 pub mod events;
+pub mod health;
 pub mod skin;
+pub mod stats;
 pub mod traits;
 
 #[derive(Clone, EnumIter)]
@@ -16,6 +20,7 @@ pub mod traits;
 pub enum EntityComponent {
     Tag(Option<EntityTagComponent>),
     Skin(Option<EntitySkinComponent>),
+    Health(Option<Health>),
 }
 
 impl EntityComponent {
@@ -29,6 +34,10 @@ impl EntityComponent {
                 Some(c) => c.to_network(),
                 None => EntityNetworkComponent::Skin(EntitySkinData::None),
             },
+            EntityComponent::Health(c) => match c {
+                Some(c) => c.to_network(),
+                None => Health::_get_empty(),
+            },
         }
     }
 }
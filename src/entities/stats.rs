@@ -0,0 +1,142 @@
+use bevy::prelude::Component;
+use common::{
+    SPECIAL_INVENTORY_ARTIFACT_SLOT, SPECIAL_INVENTORY_BELT_SLOT, SPECIAL_INVENTORY_BOOTS_SLOT,
+    SPECIAL_INVENTORY_BRACER_SLOT, SPECIAL_INVENTORY_CHEST_SLOT, SPECIAL_INVENTORY_GLOVES_SLOT,
+    SPECIAL_INVENTORY_HEAD_SLOT, SPECIAL_INVENTORY_NECK_SLOT, SPECIAL_INVENTORY_OFFHAND_SLOT,
+    SPECIAL_INVENTORY_PANTS_SLOT, SPECIAL_INVENTORY_RING_0_SLOT, SPECIAL_INVENTORY_RING_1_SLOT,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+pub const STAT_ARMOR: &str = "armor";
+pub const STAT_SPEED: &str = "speed";
+pub const STAT_MAX_HEALTH: &str = "max_health";
+
+const BASE_ARMOR: f32 = 0.0;
+const BASE_SPEED: f32 = 1.0;
+const BASE_MAX_HEALTH: f32 = 20.0;
+
+/// Player inventory slots whose items modify the stats
+pub const EQUIPMENT_SLOTS: [usize; 12] = [
+    SPECIAL_INVENTORY_HEAD_SLOT,
+    SPECIAL_INVENTORY_CHEST_SLOT,
+    SPECIAL_INVENTORY_PANTS_SLOT,
+    SPECIAL_INVENTORY_BOOTS_SLOT,
+    SPECIAL_INVENTORY_NECK_SLOT,
+    SPECIAL_INVENTORY_BRACER_SLOT,
+    SPECIAL_INVENTORY_GLOVES_SLOT,
+    SPECIAL_INVENTORY_OFFHAND_SLOT,
+    SPECIAL_INVENTORY_BELT_SLOT,
+    SPECIAL_INVENTORY_ARTIFACT_SLOT,
+    SPECIAL_INVENTORY_RING_0_SLOT,
+    SPECIAL_INVENTORY_RING_1_SLOT,
+];
+
+/// Effective stats of the entity: base values plus modifiers of the equipped items
+///
+/// Server-side only; plugins read it through `get_player_stats`.
+#[derive(Component, Serialize, Clone, Debug, PartialEq)]
+pub struct EntityStats {
+    stats: BTreeMap<String, f32>,
+}
+
+impl Default for EntityStats {
+    fn default() -> Self {
+        let mut stats: BTreeMap<String, f32> = Default::default();
+        stats.insert(STAT_ARMOR.to_string(), BASE_ARMOR);
+        stats.insert(STAT_SPEED.to_string(), BASE_SPEED);
+        stats.insert(STAT_MAX_HEALTH.to_string(), BASE_MAX_HEALTH);
+        Self { stats }
+    }
+}
+
+impl EntityStats {
+    /// Sums the modifiers on top of the base values
+    pub fn from_modifiers<'a>(modifiers: impl IntoIterator<Item = &'a BTreeMap<String, f32>>) -> Self {
+        let mut result = Self::default();
+        for item_stats in modifiers {
+            for (stat, value) in item_stats.iter() {
+                *result.stats.entry(stat.clone()).or_default() += value;
+            }
+        }
+        result
+    }
+
+    /// Zero for stats which none of the items declare
+    pub fn get(&self, stat: &str) -> f32 {
+        self.stats.get(stat).copied().unwrap_or_default()
+    }
+
+    pub fn get_armor(&self) -> f32 {
+        self.get(STAT_ARMOR)
+    }
+
+    pub fn get_speed(&self) -> f32 {
+        self.get(STAT_SPEED)
+    }
+
+    pub fn get_max_health(&self) -> f32 {
+        self.get(STAT_MAX_HEALTH)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &f32)> {
+        self.stats.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EntityStats, STAT_ARMOR, STAT_SPEED};
+    use crate::{
+        clients::client::{Client, ClientInfo},
+        items_manager::items_manager::ItemsManager,
+        network::events::on_connection_info::PlayerConnectionInfoEvent,
+        utils::Shared,
+    };
+    use common::{
+        server_storage::taits::IServerStorage, timed_lock, utils::srotage_settings::StorageSettings,
+        ServerStorageManager,
+    };
+    use std::{collections::BTreeMap, sync::Arc};
+
+    #[test]
+    fn test_stats_from_modifiers() {
+        let helmet = BTreeMap::from([(STAT_ARMOR.to_string(), 2.0)]);
+        let boots = BTreeMap::from([
+            (STAT_ARMOR.to_string(), 1.0),
+            (STAT_SPEED.to_string(), 0.25),
+            ("luck".to_string(), 3.0),
+        ]);
+
+        let stats = EntityStats::from_modifiers([&helmet, &boots]);
+        assert_eq!(stats.get_armor(), 3.0);
+        assert_eq!(stats.get_speed(), 1.25);
+        assert_eq!(stats.get_max_health(), 20.0);
+        assert_eq!(stats.get("luck"), 3.0);
+        assert_eq!(stats.get("unknown"), 0.0);
+
+        let nothing: Vec<&BTreeMap<String, f32>> = Default::default();
+        assert_eq!(EntityStats::from_modifiers(nothing), EntityStats::default());
+    }
+
+    #[test]
+    fn test_player_stats_before_player_data() {
+        let items_manager = Shared::new(Arc::new(timed_lock!(ItemsManager::default(), "test_items_manager")));
+        let client = Client::test();
+        client.set_client_info(ClientInfo::new(&PlayerConnectionInfoEvent::new(
+            client.clone(),
+            "test_player".to_string(),
+            "test".to_string(),
+            "test".to_string(),
+            "test".to_string(),
+            None,
+        )));
+        assert!(client.get_player_stats(&items_manager).is_none());
+
+        let storage = ServerStorageManager::init(StorageSettings::in_memory()).expect("in-memory storage must init");
+        client
+            .read_player_data(&storage)
+            .expect("player data must load from storage");
+        assert_eq!(client.get_player_stats(&items_manager), Some(EntityStats::default()));
+    }
+}
@@ -0,0 +1,423 @@
+use common::inventory::{
+    inventory::Inventory,
+    item::{Item, ItemKind},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Display,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::utils::duration::parse_duration;
+
+pub const INVENTORY_AUDIT_FILE: &str = "inventory_audit.log";
+pub const MAX_AUDIT_FILE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Who changed the inventory
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditActor {
+    Player(String),
+    Plugin(String),
+    Console(String),
+    Server,
+}
+
+impl Display for AuditActor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditActor::Player(login) => write!(f, "player:{}", login),
+            AuditActor::Plugin(slug) => write!(f, "plugin:{}", slug),
+            AuditActor::Console(name) => write!(f, "console:{}", name),
+            AuditActor::Server => write!(f, "server"),
+        }
+    }
+}
+
+/// Player inventories are logged by login because client ids change between sessions
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditInventory {
+    Player(String),
+    World(u64),
+}
+
+impl Display for AuditInventory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditInventory::Player(login) => write!(f, "player:{}", login),
+            AuditInventory::World(inventory_id) => write!(f, "world:{}", inventory_id),
+        }
+    }
+}
+
+/// One line of the audit log
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuditEntry {
+    // Unix time in milliseconds
+    pub timestamp: i64,
+    pub actor: AuditActor,
+    pub inventory: AuditInventory,
+    pub slot: usize,
+    pub before: Option<Item>,
+    pub after: Option<Item>,
+}
+
+/// Slots of the inventory to compare before and after the change
+pub fn snapshot(inventory: &Inventory) -> Vec<Option<Item>> {
+    (0..inventory.slots_len())
+        .map(|slot| inventory.get_slot(slot).cloned())
+        .collect()
+}
+
+/// `5x stone` for the console; `-` for the empty slot
+pub fn format_item(item: Option<&Item>) -> String {
+    let Some(item) = item else {
+        return "-".to_string();
+    };
+    match item.get_item_kind() {
+        ItemKind::Block(block_id) => format!("{}x block:{}", item.get_amount(), block_id),
+        ItemKind::CustomItem(slug) => format!("{}x {}", item.get_amount(), slug),
+    }
+}
+
+fn same_item(a: Option<&Item>, b: Option<&Item>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.can_stack_with(b) && a.get_amount() == b.get_amount(),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Append-only log of inventory slot changes stored in the server data folder
+///
+/// Without an opened file (tests, failed open) changes are not recorded.
+/// Lines are buffered and reach the disk on `flush` (autosave, save-all, shutdown) or when the buffer is full.
+/// The file over `MAX_AUDIT_FILE_SIZE` is renamed to `.1`, so the log keeps two files at most.
+#[derive(Default)]
+pub struct InventoryAuditLog {
+    path: Option<PathBuf>,
+    file: Option<BufWriter<File>>,
+    size: u64,
+    max_size: u64,
+}
+
+impl InventoryAuditLog {
+    pub fn open(server_data_path: &Path) -> Result<Self, String> {
+        let path = server_data_path.join(INVENTORY_AUDIT_FILE);
+        let (file, size) = open_append(&path)?;
+        Ok(Self {
+            path: Some(path),
+            file: Some(file),
+            size,
+            max_size: MAX_AUDIT_FILE_SIZE,
+        })
+    }
+
+    pub fn record(
+        &mut self,
+        actor: &AuditActor,
+        inventory: &AuditInventory,
+        slot: usize,
+        before: Option<Item>,
+        after: Option<Item>,
+    ) {
+        let Some(file) = self.file.as_mut() else {
+            return;
+        };
+        let entry = AuditEntry {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            actor: actor.clone(),
+            inventory: inventory.clone(),
+            slot,
+            before,
+            after,
+        };
+        let line = match serde_json::to_string(&entry) {
+            Ok(l) => l,
+            Err(e) => {
+                log::error!(target: "inventory", "&cAudit entry serialize error: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = writeln!(file, "{}", line) {
+            log::error!(target: "inventory", "&cAudit log write error: {}", e);
+            return;
+        }
+        self.size += line.len() as u64 + 1;
+
+        if self.size >= self.max_size {
+            if let Err(e) = self.rotate() {
+                log::error!(target: "inventory", "{}", e);
+            }
+        }
+    }
+
+    /// Records every slot which differs between the snapshots
+    pub fn record_diff(
+        &mut self,
+        actor: &AuditActor,
+        inventory: &AuditInventory,
+        before: &[Option<Item>],
+        after: &[Option<Item>],
+    ) {
+        let slots = before.len().max(after.len());
+        for slot in 0..slots {
+            let old = before.get(slot).and_then(|item| item.as_ref());
+            let new = after.get(slot).and_then(|item| item.as_ref());
+            if !same_item(old, new) {
+                self.record(actor, inventory, slot, old.cloned(), new.cloned());
+            }
+        }
+    }
+
+    /// Writes buffered entries to the disk
+    pub fn flush(&mut self) -> Result<(), String> {
+        let (Some(path), Some(file)) = (self.path.as_ref(), self.file.as_mut()) else {
+            return Ok(());
+        };
+        file.flush()
+            .map_err(|e| format!("&cfile &4{}&c write error: {}", path.display(), e))
+    }
+
+    /// Moves the current file to `.1` replacing the previous one and starts a new file
+    fn rotate(&mut self) -> Result<(), String> {
+        self.flush()?;
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+        // The file must be closed before the rename
+        self.file = None;
+        let rotated_path = get_rotated_path(path);
+        let renamed = std::fs::rename(path, &rotated_path);
+
+        let (file, size) = open_append(path)?;
+        self.file = Some(file);
+        if let Err(e) = renamed {
+            // Next try after another `max_size` bytes
+            self.size = 0;
+            return Err(format!("&cfile &4{}&c rename error: {}", path.display(), e));
+        }
+        self.size = size;
+        log::info!(target: "inventory", "Audit log is rotated to &e{}", rotated_path.display());
+        Ok(())
+    }
+
+    /// Reader of the entries written so far; buffered lines are flushed first
+    pub fn reader(&mut self) -> Result<AuditReader, String> {
+        self.flush()?;
+        Ok(AuditReader {
+            path: self.path.clone(),
+        })
+    }
+}
+
+/// Scans the audit files apart from the log, so large files can be read outside of the tick
+///
+/// Entries recorded during the scan may be missed.
+#[derive(Clone)]
+pub struct AuditReader {
+    path: Option<PathBuf>,
+}
+
+impl AuditReader {
+    /// Total count of the inventory entries and the last `limit` of them from the oldest to the newest
+    pub fn read_history(&self, inventory: &AuditInventory, limit: usize) -> Result<(usize, Vec<AuditEntry>), String> {
+        let mut count = 0;
+        let mut entries: VecDeque<AuditEntry> = Default::default();
+        self.scan(inventory, |entry| {
+            count += 1;
+            if limit == 0 {
+                return;
+            }
+            if entries.len() == limit {
+                entries.pop_front();
+            }
+            entries.push_back(entry);
+        })?;
+        Ok((count, entries.into()))
+    }
+
+    /// Total count of the inventory entries and the entries made after the `timestamp`
+    /// from the oldest to the newest
+    pub fn read_changes_since(
+        &self,
+        inventory: &AuditInventory,
+        timestamp: i64,
+    ) -> Result<(usize, Vec<AuditEntry>), String> {
+        let mut count = 0;
+        let mut entries: Vec<AuditEntry> = Default::default();
+        self.scan(inventory, |entry| {
+            count += 1;
+            if entry.timestamp > timestamp {
+                entries.push(entry);
+            }
+        })?;
+        Ok((count, entries))
+    }
+
+    /// Streams entries of the inventory from the rotated file and then from the current one
+    fn scan(&self, inventory: &AuditInventory, mut f: impl FnMut(AuditEntry)) -> Result<(), String> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+        // Lines of other inventories are skipped without parsing
+        let needle = match serde_json::to_string(inventory) {
+            Ok(inventory) => format!("\"inventory\":{}", inventory),
+            Err(e) => return Err(format!("&cAudit inventory serialize error: {}", e)),
+        };
+
+        for path in [get_rotated_path(path), path.clone()] {
+            let file = match File::open(&path) {
+                Ok(f) => f,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(format!("&cfile &4{}&c read error: {}", path.display(), e)),
+            };
+            for (index, line) in BufReader::new(file).lines().enumerate() {
+                let line = line.map_err(|e| format!("&cfile &4{}&c read error: {}", path.display(), e))?;
+                if !line.contains(&needle) {
+                    continue;
+                }
+                let entry: AuditEntry = match serde_json::from_str(&line) {
+                    Ok(e) => e,
+                    Err(e) => {
+                        log::warn!(
+                            target: "inventory",
+                            "Audit log {} line {} is skipped: {}",
+                            path.display(),
+                            index + 1,
+                            e
+                        );
+                        continue;
+                    }
+                };
+                if entry.inventory == *inventory {
+                    f(entry);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> Result<(BufWriter<File>, u64), String> {
+    let file = match OpenOptions::new().create(true).append(true).open(path) {
+        Ok(f) => f,
+        Err(e) => return Err(format!("&cfile &4{}&c open error: {}", path.display(), e)),
+    };
+    let size = match file.metadata() {
+        Ok(metadata) => metadata.len(),
+        Err(e) => return Err(format!("&cfile &4{}&c read error: {}", path.display(), e)),
+    };
+    Ok((BufWriter::new(file), size))
+}
+
+fn get_rotated_path(path: &Path) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(".1");
+    PathBuf::from(rotated)
+}
+
+/// Slot contents at the `timestamp` for the slots changed after it
+pub fn get_rollback_slots(entries: &[AuditEntry], timestamp: i64) -> BTreeMap<usize, Option<Item>> {
+    let mut slots: BTreeMap<usize, Option<Item>> = Default::default();
+    for entry in entries.iter().filter(|entry| entry.timestamp > timestamp) {
+        // The earliest change after the point keeps the state at the point
+        slots.entry(entry.slot).or_insert_with(|| entry.before.clone());
+    }
+    slots
+}
+
+/// `30s`, `15m`, `2h`, `7d` ago or RFC 3339 date time; returns unix time in milliseconds
+pub fn parse_point_in_time(input: &str, now: i64) -> Result<i64, String> {
+    if let Ok(date_time) = chrono::DateTime::parse_from_rfc3339(input) {
+        return Ok(date_time.timestamp_millis());
+    }
+    let Some(duration) = parse_duration(input) else {
+        return Err(format!("time \"{}\" must be like 15m, 2h, 7d or RFC 3339", input));
+    };
+    Ok(now.saturating_sub(duration))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        get_rollback_slots, get_rotated_path, parse_point_in_time, AuditActor, AuditEntry, AuditInventory,
+        InventoryAuditLog,
+    };
+    use common::inventory::item::Item;
+
+    fn entry(timestamp: i64, slot: usize, before: Option<u16>, after: Option<u16>) -> AuditEntry {
+        AuditEntry {
+            timestamp,
+            actor: AuditActor::Player("test".to_string()),
+            inventory: AuditInventory::Player("test".to_string()),
+            slot,
+            before: before.map(|amount| Item::create("apple").amount(amount)),
+            after: after.map(|amount| Item::create("apple").amount(amount)),
+        }
+    }
+
+    #[test]
+    fn test_rollback_slots() {
+        let entries = vec![
+            entry(100, 0, None, Some(5)),
+            entry(200, 0, Some(5), Some(2)),
+            entry(300, 0, Some(2), None),
+            entry(300, 1, None, Some(3)),
+        ];
+
+        let slots = get_rollback_slots(&entries, 150);
+        assert_eq!(slots.len(), 2);
+        assert_eq!(slots[&0].as_ref().unwrap().get_amount(), 5);
+        assert!(slots[&1].is_none());
+
+        assert!(get_rollback_slots(&entries, 300).is_empty());
+    }
+
+    #[test]
+    fn test_parse_point_in_time() {
+        let now = 10_000_000;
+        assert_eq!(parse_point_in_time("30s", now), Ok(now - 30_000));
+        assert_eq!(parse_point_in_time("2h", now), Ok(now - 7_200_000));
+        assert_eq!(parse_point_in_time("1970-01-01T00:00:01Z", now), Ok(1_000));
+        assert!(parse_point_in_time("2x", now).is_err());
+        assert!(parse_point_in_time("", now).is_err());
+    }
+
+    #[test]
+    fn test_history_survives_rotation() {
+        let dir = std::env::temp_dir().join(format!("brilliance-audit-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut audit_log = InventoryAuditLog::open(&dir).unwrap();
+        audit_log.max_size = 4096;
+
+        let actor = AuditActor::Server;
+        let alice = AuditInventory::Player("alice".to_string());
+        let numeric = AuditInventory::Player("42".to_string());
+        for amount in 1..=60 {
+            audit_log.record(&actor, &alice, 0, None, Some(Item::create("apple").amount(amount)));
+            audit_log.record(&actor, &numeric, 0, None, Some(Item::create("apple").amount(amount)));
+        }
+        assert!(get_rotated_path(&dir.join(super::INVENTORY_AUDIT_FILE)).exists());
+
+        let reader = audit_log.reader().unwrap();
+        let (count, entries) = reader.read_history(&alice, 3).unwrap();
+        assert!(count > 3 && count < 60);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[2].after.as_ref().unwrap().get_amount(), 60);
+        assert!(entries.iter().all(|entry| entry.inventory == alice));
+        assert!(reader.read_history(&AuditInventory::World(42), 3).unwrap().1.is_empty());
+
+        // Unknown login has no entries at all
+        let (count, entries) = reader.read_changes_since(&alice, i64::MAX).unwrap();
+        assert!(count > 0 && entries.is_empty());
+        let unknown = AuditInventory::Player("alise".to_string());
+        assert_eq!(reader.read_changes_since(&unknown, 0).unwrap().0, 0);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,4 +1,5 @@
-use common::inventory::inventory::InventoryType;
+use common::chunks::block_position::BlockPosition;
+use common::inventory::inventory::{Inventory, InventoryType};
 use common::utils::debug::SmartRwLock;
 use std::sync::Arc;
 
@@ -7,7 +8,7 @@ use crate::{
     inventory::inventory_manager::InventoryManager,
     items_manager::items_manager::ItemsManager,
     network::sync_inventory::{send_inventory_start_to_client, send_inventory_stop_to_client},
-    worlds::worlds_manager::WorldsManager,
+    worlds::{world_manager::WorldManager, worlds_manager::WorldsManager},
 };
 
 pub fn open_inventory(
@@ -59,28 +60,60 @@ pub fn open_inventory(
         log::error!(target: "inventory", "chunk {:?} is not loaded", location.get_chunk_position());
         return;
     };
-    let chunk_column = chunk_column_arc.read();
-    let Some(block_inventory) = chunk_column
-        .get_chunk_storage()
-        .get_inventories()
-        .iter()
-        .find(|block_inventory| block_inventory.get_inventory().get_id() == inventory_id)
-    else {
+    let mut chunk_column = chunk_column_arc.write();
+    let Some(block_inventory) = chunk_column.get_chunk_storage_mut().get_inventory_mut(inventory_id) else {
         log::error!(target: "inventory", "inventory {} not found in chunk storage", inventory_id);
         return;
     };
 
+    // Loot is rolled once, when the inventory is opened for the first time
+    let items_manager = items_manager.read();
+    let loot_table = block_inventory.take_loot_table();
+    if let Some(loot_table) = loot_table.as_ref() {
+        let position = BlockPosition::from_chunk_position(
+            location.get_chunk_position(),
+            block_inventory.get_section(),
+            block_inventory.get_position(),
+        );
+        roll_loot(
+            &items_manager,
+            block_inventory.get_inventory_mut(),
+            loot_table,
+            world_manager,
+            &position,
+        );
+    }
+    let client_inventory = items_manager.to_client_inventory(block_inventory.get_inventory());
+    if loot_table.is_some() {
+        chunk_column.mark_dirty();
+    }
+
     let opened = inventory_manager
         .write()
         .open_inventory(world_entity.get_entity(), inventory_id);
     if opened {
-        send_inventory_start_to_client(
-            client,
-            InventoryType::WorldInventory(inventory_id),
-            items_manager
-                .read()
-                .to_client_inventory(block_inventory.get_inventory()),
-        );
+        send_inventory_start_to_client(client, InventoryType::WorldInventory(inventory_id), client_inventory);
+    }
+}
+
+/// Fills the inventory with items of the loot table; items which don't fit are lost
+fn roll_loot(
+    items_manager: &ItemsManager,
+    inventory: &mut Inventory,
+    loot_table: &str,
+    world_manager: &WorldManager,
+    position: &BlockPosition,
+) {
+    let Some(loot_table) = items_manager.get_loot_tables().get_loot_table(loot_table) else {
+        log::warn!(target: "inventory", "Loot table \"{}\" at {:?} is not found", loot_table, position);
+        return;
+    };
+    for item in loot_table.roll(world_manager.get_seed(), world_manager.get_slug(), position) {
+        let max_stack_size = items_manager.get_max_stack_size(&item);
+        if inventory.add_item(item, max_stack_size, |_slot, _item| {}).is_err() {
+            log::debug!(target: "inventory", "Loot table \"{}\" doesn't fit the inventory at {:?}", loot_table.get_slug(), position);
+            break;
+        }
     }
 }
 
@@ -139,6 +172,7 @@ pub fn get_or_create_inventory(
     };
 
     let mut chunk_column = chunk_column_arc.write();
+    chunk_column.mark_dirty();
     let chunk_storage = chunk_column.get_chunk_storage_mut();
     let inventory_id = rand::random::<u64>();
     let block_inventory =
@@ -0,0 +1,73 @@
+use common::inventory::item::Item;
+
+use crate::{
+    inventory::{
+        audit_log::{snapshot, AuditActor, AuditInventory},
+        inventory_manager::InventoryManager,
+    },
+    network::events::on_inventory_action::{InventoryAction, InventoryTarget},
+};
+
+use super::helpers::{with_inventory_ref, InventoryActionCtx};
+
+/// Slots of the inventories touched by the action before it's applied
+pub(crate) fn take_snapshots(
+    ctx: &InventoryActionCtx<'_>,
+    inventory_manager: &InventoryManager,
+    action: &InventoryAction,
+) -> Vec<(InventoryTarget, Vec<Option<Item>>)> {
+    let mut targets: Vec<&InventoryTarget> = match action {
+        InventoryAction::Move {
+            from_inventory,
+            to_inventory,
+            ..
+        } => vec![from_inventory, to_inventory],
+        InventoryAction::Drop { inventory, .. } => vec![inventory],
+        InventoryAction::Close { .. } => Default::default(),
+    };
+    targets.dedup();
+    targets
+        .into_iter()
+        .filter_map(|target| {
+            with_inventory_ref(ctx, inventory_manager, target, snapshot).map(|slots| (target.clone(), slots))
+        })
+        .collect()
+}
+
+/// Writes slots changed since the snapshots to the audit log
+pub(crate) fn record_action(
+    ctx: &InventoryActionCtx<'_>,
+    inventory_manager: &mut InventoryManager,
+    snapshots: Vec<(InventoryTarget, Vec<Option<Item>>)>,
+) {
+    let Some(login) = ctx.client.get_client_info().map(|info| info.get_login().clone()) else {
+        return;
+    };
+    let actor = AuditActor::Player(login);
+    for (target, before) in snapshots {
+        let Some(audit_inventory) = get_audit_inventory(ctx, &target) else {
+            continue;
+        };
+        let Some(after) = with_inventory_ref(ctx, inventory_manager, &target, snapshot) else {
+            continue;
+        };
+        inventory_manager
+            .get_audit_log_mut()
+            .record_diff(&actor, &audit_inventory, &before, &after);
+    }
+}
+
+fn get_audit_inventory(ctx: &InventoryActionCtx<'_>, target: &InventoryTarget) -> Option<AuditInventory> {
+    match target {
+        InventoryTarget::Client(client_id) => {
+            let clients = ctx.clients.read();
+            let client = match *client_id == ctx.client.get_client_id() {
+                true => ctx.client,
+                false => clients.get(client_id)?,
+            };
+            let login = client.get_client_info()?.get_login().clone();
+            Some(AuditInventory::Player(login))
+        }
+        InventoryTarget::World(inventory_id) => Some(AuditInventory::World(*inventory_id)),
+    }
+}
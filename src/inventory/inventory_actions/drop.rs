@@ -1,9 +1,12 @@
 use bevy_ecs::system::Commands;
-use common::inventory::inventory::Inventory;
+use common::inventory::{inventory::Inventory, item::Item};
 use network::messages::InventorySlotChange;
+use std::time::Duration;
 
 use crate::{
-    inventory::inventory_manager::InventoryManager, items_manager::items_manager::SharedItemsManager,
+    entities::dropped_item::{get_drop_position, spawn_dropped_item},
+    inventory::inventory_manager::InventoryManager,
+    items_manager::items_manager::SharedItemsManager,
     network::events::on_inventory_action::InventoryTarget,
 };
 
@@ -18,48 +21,81 @@ pub(crate) fn apply_drop(
     slot: u16,
     amount: u16,
 ) {
-    let changes = with_inventory_mut(ctx, inventory_manager, &inventory, |inventory_data| {
+    let dropped = with_inventory_mut(ctx, inventory_manager, &inventory, |inventory_data| {
         drop_stack(inventory_data, ctx.items_manager, slot as usize, amount)
     });
-    if let Some(changes) = changes.as_ref() {
-        after_inventory_modified(ctx, commands, &inventory, changes);
+    let (mut changes, dropped_item) = match dropped {
+        Some((changes, dropped_item)) => (Some(changes), dropped_item),
+        None => (None, None),
+    };
+    if let Some(changes) = changes.as_mut() {
+        after_inventory_modified(ctx, commands, inventory_manager, &inventory, changes);
     }
     broadcast_inventory_changes(ctx, inventory_manager, inventory, changes);
+
+    if let Some(item) = dropped_item {
+        spawn_in_front_of_player(ctx, item);
+    }
 }
 
+/// Returns slot changes and the removed part of the stack
 fn drop_stack(
     inventory: &mut Inventory,
     items_manager: &SharedItemsManager,
     slot: usize,
     amount: u16,
-) -> Vec<InventorySlotChange> {
+) -> (Vec<InventorySlotChange>, Option<Item>) {
     if amount == 0 {
-        return Vec::new();
+        return (Vec::new(), None);
     }
 
     let Some(mut source) = inventory.take_slot(slot) else {
-        return Vec::new();
+        return (Vec::new(), None);
     };
 
     let removed_amount = amount.min(source.get_amount());
     if removed_amount == 0 {
         inventory.set_slot_option(slot, Some(source));
-        return Vec::new();
+        return (Vec::new(), None);
     }
 
+    let dropped_item = source.clone().amount(removed_amount);
     if removed_amount < source.get_amount() {
         let remaining = source.get_amount() - removed_amount;
         source = source.amount(remaining);
         inventory.set_slot_option(slot, Some(source.clone()));
-        vec![InventorySlotChange {
+        let changes = vec![InventorySlotChange {
             slot,
             item: Some(items_manager.read().to_client_item(&source)),
-        }]
+        }];
+        (changes, Some(dropped_item))
     } else {
-        vec![InventorySlotChange { slot, item: None }]
+        (vec![InventorySlotChange { slot, item: None }], Some(dropped_item))
     }
 }
 
+fn spawn_in_front_of_player(ctx: &InventoryActionCtx<'_>, item: Item) {
+    let Some(world_entity) = ctx.client.get_world_entity() else {
+        log::error!(target: "inventory", "client {} dropped item outside of world", ctx.client.get_client_id());
+        return;
+    };
+    let worlds_manager = ctx.worlds_manager.read();
+    let Some(mut world_manager) = worlds_manager.get_world_manager_mut(world_entity.get_world_slug()) else {
+        log::error!(target: "inventory", "world {} not found for dropped item", world_entity.get_world_slug());
+        return;
+    };
+    let Some((position, rotation)) = world_manager.get_entity_location(world_entity.get_entity()) else {
+        return;
+    };
+    spawn_dropped_item(
+        &mut world_manager,
+        &ctx.items_manager.read(),
+        get_drop_position(&position, &rotation),
+        item,
+        Duration::ZERO,
+    );
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -82,9 +118,10 @@ mod tests {
         let mut inventory = Inventory::create(4);
         inventory.set_slot(0, Item::create("apple").amount(5));
 
-        let changes = drop_stack(&mut inventory, &items_manager, 0, 2);
+        let (changes, dropped_item) = drop_stack(&mut inventory, &items_manager, 0, 2);
 
         assert_eq!(inventory.get_slot(0).unwrap().get_amount(), 3);
+        assert_eq!(dropped_item.unwrap().get_amount(), 2);
         assert_eq!(changes.len(), 1);
         assert_eq!(changes[0].slot, 0);
         assert_eq!(changes[0].item.as_ref().unwrap().get_amount(), 3);
@@ -96,9 +133,10 @@ mod tests {
         let mut inventory = Inventory::create(4);
         inventory.set_slot(0, Item::create("apple").amount(5));
 
-        let changes = drop_stack(&mut inventory, &items_manager, 0, 5);
+        let (changes, dropped_item) = drop_stack(&mut inventory, &items_manager, 0, 5);
 
         assert!(inventory.get_slot(0).is_none());
+        assert_eq!(dropped_item.unwrap().get_amount(), 5);
         assert_eq!(changes.len(), 1);
         assert_eq!(changes[0].slot, 0);
         assert!(changes[0].item.is_none());
@@ -110,9 +148,10 @@ mod tests {
         let mut inventory = Inventory::create(4);
         inventory.set_slot(0, Item::create("apple").amount(5));
 
-        let changes = drop_stack(&mut inventory, &items_manager, 0, 0);
+        let (changes, dropped_item) = drop_stack(&mut inventory, &items_manager, 0, 0);
 
         assert!(changes.is_empty());
+        assert!(dropped_item.is_none());
         assert_eq!(inventory.get_slot(0).unwrap().get_amount(), 5);
     }
 
@@ -121,9 +160,10 @@ mod tests {
         let items_manager = shared_items_manager();
         let mut inventory = Inventory::create(4);
 
-        let changes = drop_stack(&mut inventory, &items_manager, 0, 2);
+        let (changes, dropped_item) = drop_stack(&mut inventory, &items_manager, 0, 2);
 
         assert!(changes.is_empty());
+        assert!(dropped_item.is_none());
         assert!(inventory.get_slot(0).is_none());
     }
 }
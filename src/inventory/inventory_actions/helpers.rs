@@ -64,6 +64,7 @@ pub(crate) fn with_inventory_mut<R>(
                 .get_chunks_map()
                 .get_chunk_column_arc(location.get_chunk_position())?;
             let mut chunk_column = chunk_column_arc.write();
+            chunk_column.mark_dirty();
             let chunk_storage = chunk_column.get_chunk_storage_mut();
             let block_inventory = chunk_storage.get_inventory_mut(*inventory_id)?;
             Some(f(block_inventory.get_inventory_mut()))
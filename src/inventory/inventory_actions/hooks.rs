@@ -2,13 +2,18 @@ use crate::items_manager::item_info::ItemType;
 use bevy_ecs::system::Commands;
 use crate::{
     clients::client::Client,
-    entities::{commands::UpdatePlayerComponent, skin::EntitySkinComponent, EntityComponent},
+    entities::{
+        commands::{UpdatePlayerComponent, UpdatePlayerStats},
+        skin::EntitySkinComponent,
+        stats::EQUIPMENT_SLOTS,
+        EntityComponent,
+    },
     inventory::inventory_manager::InventoryManager,
-    items_manager::items_manager::SharedItemsManager,
+    items_manager::{items_manager::SharedItemsManager, recipes::CRAFTING_RESULT_SLOT},
     network::events::on_inventory_action::{InventoryAction, InventoryTarget},
 };
 use common::{
-    inventory::{item::BodyPart, item::Item},
+    inventory::{inventory::Inventory, item::BodyPart, item::Item},
     INVENTORY_SLOTS, SPECIAL_INVENTORY_ARTIFACT_SLOT, SPECIAL_INVENTORY_BELT_SLOT, SPECIAL_INVENTORY_BOOTS_SLOT,
     SPECIAL_INVENTORY_BRACER_SLOT, SPECIAL_INVENTORY_CHEST_SLOT, SPECIAL_INVENTORY_GLOVES_SLOT,
     SPECIAL_INVENTORY_HEAD_SLOT, SPECIAL_INVENTORY_NECK_SLOT, SPECIAL_INVENTORY_OFFHAND_SLOT,
@@ -16,7 +21,7 @@ use common::{
 };
 use network::messages::InventorySlotChange;
 
-use super::helpers::{with_inventory_ref, InventoryActionCtx};
+use super::helpers::{calculate_transfer_amount, with_inventory_mut, with_inventory_ref, InventoryActionCtx};
 
 pub(crate) fn before_action(
     ctx: &InventoryActionCtx<'_>,
@@ -27,7 +32,7 @@ pub(crate) fn before_action(
         InventoryAction::Move {
             from_inventory,
             from_slot,
-            to_inventory: _,
+            to_inventory,
             to_slot,
             amount,
         } => {
@@ -62,6 +67,21 @@ pub(crate) fn before_action(
                 return Err(None);
             }
 
+            if is_crafting_result(inventory_manager, to_inventory, *to_slot) {
+                return Err(Some("crafting result slot is filled by the server".to_string()));
+            }
+            if is_crafting_result(inventory_manager, from_inventory, *from_slot) {
+                check_crafting_result_take(
+                    ctx,
+                    inventory_manager,
+                    from_inventory,
+                    &source_item,
+                    to_inventory,
+                    *to_slot,
+                    *amount,
+                )?;
+            }
+
             Ok(())
         }
         InventoryAction::Drop {
@@ -97,19 +117,136 @@ pub(crate) fn before_action(
                 return Err(Some(format!("item type does not fit slot: {}", slot)));
             }
 
+            if is_crafting_result(inventory_manager, inventory, *slot) {
+                return Err(Some("crafting result can't be dropped".to_string()));
+            }
+
             Ok(())
         }
         InventoryAction::Close { .. } => Ok(()),
     }
 }
 
+fn is_crafting_result(inventory_manager: &InventoryManager, inventory_target: &InventoryTarget, slot: u16) -> bool {
+    match inventory_target {
+        InventoryTarget::World(inventory_id) => {
+            slot as usize == CRAFTING_RESULT_SLOT && inventory_manager.state().is_crafting_inventory(inventory_id)
+        }
+        InventoryTarget::Client(_) => false,
+    }
+}
+
+fn get_crafting_grid(inventory: &Inventory) -> Vec<Option<Item>> {
+    (0..CRAFTING_RESULT_SLOT)
+        .map(|slot| inventory.get_slot(slot).cloned())
+        .collect()
+}
+
+/// Whole result must be taken at once and it must still match the grid
+fn check_crafting_result_take(
+    ctx: &InventoryActionCtx<'_>,
+    inventory_manager: &InventoryManager,
+    from_inventory: &InventoryTarget,
+    result: &Item,
+    to_inventory: &InventoryTarget,
+    to_slot: u16,
+    amount: u16,
+) -> Result<(), Option<String>> {
+    if amount != result.get_amount() {
+        return Err(Some("crafting result can be taken only as a whole".to_string()));
+    }
+    // Grid change would replace the result before it's taken
+    if to_inventory == from_inventory && (to_slot as usize) < CRAFTING_RESULT_SLOT {
+        return Err(Some("crafting result can't be moved into the grid".to_string()));
+    }
+
+    let grid = with_inventory_ref(ctx, inventory_manager, from_inventory, get_crafting_grid).unwrap_or_default();
+    let matches_grid = {
+        let items_manager = ctx.items_manager.read();
+        match items_manager.get_recipes().find_crafting_recipe(&grid) {
+            Some(recipe) => {
+                log::debug!(target: "inventory", "Client {} crafts \"{}\"", ctx.client.get_client_id(), recipe.get_slug());
+                recipe.get_result().can_stack_with(result) && recipe.get_result().get_amount() == result.get_amount()
+            }
+            None => false,
+        }
+    };
+    if !matches_grid {
+        return Err(Some("crafting result doesn't match the grid".to_string()));
+    }
+
+    let target = with_inventory_ref(ctx, inventory_manager, to_inventory, |inventory| {
+        inventory.get_slot(to_slot as usize).cloned()
+    })
+    .flatten();
+    if calculate_transfer_amount(ctx.items_manager, result, target.as_ref(), amount) != amount {
+        return Err(None);
+    }
+    Ok(())
+}
+
+/// Spends ingredients of the taken result and fills the result slot from the grid
+fn update_crafting_result(
+    ctx: &InventoryActionCtx<'_>,
+    inventory_manager: &InventoryManager,
+    inventory_target: &InventoryTarget,
+    changes: &mut Vec<InventorySlotChange>,
+) {
+    let InventoryTarget::World(inventory_id) = inventory_target else {
+        return;
+    };
+    if !inventory_manager.state().is_crafting_inventory(inventory_id) {
+        return;
+    }
+
+    // Result slot can't be filled by the player, so its change means the result was taken
+    let result_taken = changes.iter().any(|change| change.slot == CRAFTING_RESULT_SLOT);
+    let crafting_changes = with_inventory_mut(ctx, inventory_manager, inventory_target, |inventory| {
+        let items_manager = ctx.items_manager.read();
+        let mut crafting_changes: Vec<InventorySlotChange> = Default::default();
+        if result_taken {
+            for slot in 0..CRAFTING_RESULT_SLOT {
+                let Some(ingredient) = inventory.take_slot(slot) else {
+                    continue;
+                };
+                let remaining = match ingredient.get_amount() > 1 {
+                    true => Some(ingredient.clone().amount(ingredient.get_amount() - 1)),
+                    false => None,
+                };
+                inventory.set_slot_option(slot, remaining.clone());
+                crafting_changes.push(InventorySlotChange {
+                    slot,
+                    item: remaining.map(|item| items_manager.to_client_item(&item)),
+                });
+            }
+        }
+
+        let result = items_manager
+            .get_recipes()
+            .find_crafting_recipe(&get_crafting_grid(inventory))
+            .map(|recipe| recipe.get_result().clone());
+        inventory.set_slot_option(CRAFTING_RESULT_SLOT, result.clone());
+        crafting_changes.push(InventorySlotChange {
+            slot: CRAFTING_RESULT_SLOT,
+            item: result.map(|item| items_manager.to_client_item(&item)),
+        });
+        crafting_changes
+    });
+    if let Some(crafting_changes) = crafting_changes {
+        changes.extend(crafting_changes);
+    }
+}
+
 pub(crate) fn after_inventory_modified(
     ctx: &InventoryActionCtx<'_>,
     commands: &mut Commands,
+    inventory_manager: &InventoryManager,
     inventory_target: &InventoryTarget,
-    changes: &[InventorySlotChange],
+    changes: &mut Vec<InventorySlotChange>,
 ) {
-    if !armor_slot_changed(changes) {
+    update_crafting_result(ctx, inventory_manager, inventory_target, changes);
+
+    if !equipment_slot_changed(changes) {
         return;
     }
 
@@ -125,7 +262,12 @@ pub(crate) fn after_inventory_modified(
         InventoryTarget::World(_) => return,
     };
 
-    update_player_skin(&client, ctx.items_manager, commands);
+    if armor_slot_changed(changes) {
+        update_player_skin(&client, ctx.items_manager, commands);
+    }
+    if let Some(stats) = client.get_player_stats(ctx.items_manager) {
+        commands.queue(UpdatePlayerStats::create(client, stats));
+    }
 }
 
 fn update_player_skin(client: &Client, items_manager: &SharedItemsManager, commands: &mut Commands) {
@@ -148,6 +290,11 @@ pub(crate) fn armor_slot_changed(changes: &[InventorySlotChange]) -> bool {
     })
 }
 
+/// Any of the slots whose items modify the player stats
+pub(crate) fn equipment_slot_changed(changes: &[InventorySlotChange]) -> bool {
+    changes.iter().any(|change| EQUIPMENT_SLOTS.contains(&change.slot))
+}
+
 pub(crate) fn inventory_slot_allowed(slot: u16) -> bool {
     (slot as usize) < INVENTORY_SLOTS
 }
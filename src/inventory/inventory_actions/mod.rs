@@ -1,3 +1,4 @@
+mod audit;
 mod close;
 mod drop;
 mod helpers;
@@ -40,6 +41,7 @@ impl InventoryActions {
         Self::authorize_action(&ctx, inventory_manager, &action)?;
         hooks::before_action(&ctx, inventory_manager, &action)?;
 
+        let snapshots = audit::take_snapshots(&ctx, inventory_manager, &action);
         match action {
             InventoryAction::Move {
                 from_inventory,
@@ -64,6 +66,7 @@ impl InventoryActions {
             } => drop::apply_drop(&ctx, commands, inventory_manager, inventory, slot, amount),
             InventoryAction::Close { inventory } => close::apply_close(&ctx, inventory_manager, inventory),
         }
+        audit::record_action(&ctx, inventory_manager, snapshots);
         Ok(())
     }
 
@@ -145,15 +148,28 @@ mod tests {
 
     use super::*;
     use crate::{
-        clients::{client::ClientInfo, clients_container::SharedClientsContainer},
+        clients::{
+            client::{ClientInfo, WorldEntity},
+            clients_container::SharedClientsContainer,
+        },
+        inventory::audit_log::format_item,
         items_manager::item_info::{ItemDisplay, ItemInfo},
         items_manager::items_manager::ItemsManager,
+        items_manager::recipes::{RecipeManifest, CRAFTING_INVENTORY_SLOTS, CRAFTING_RESULT_SLOT},
         network::events::on_inventory_action::InventoryAction,
         plugins::plugins_manager::PluginsManager,
         utils::Shared,
         worlds::worlds_manager::SharedWorldsManager,
     };
-    use common::ServerStorageManager;
+    use common::{
+        chunks::{
+            block_position::{BlockPosition, BlockPositionTrait},
+            chunk_data::{ChunkData, ChunkSectionData},
+            chunk_storage::ChunkStorage,
+        },
+        worlds_storage::taits::WorldStorageData,
+        ServerStorageManager, WorldStorageManager,
+    };
     #[test]
     fn allows_own_client_inventory() {
         let inventory_manager = InventoryManager::default();
@@ -278,6 +294,7 @@ mod tests {
                 "test".to_string(),
                 "test".to_string(),
                 "test".to_string(),
+                None,
             ),
         ));
 
@@ -347,4 +364,214 @@ mod tests {
         });
     }
 
+    struct CraftingSetup {
+        client: Client,
+        clients: SharedClientsContainer,
+        items_manager: SharedItemsManager,
+        inventory_manager: InventoryManager,
+        worlds_manager: SharedWorldsManager,
+        inventory_id: u64,
+    }
+
+    impl CraftingSetup {
+        /// Client watching a crafting inventory with planks in the first two grid slots
+        fn new() -> Self {
+            let client = crate::clients::client::Client::test();
+            client.set_client_info(ClientInfo::new(
+                &crate::network::events::on_connection_info::PlayerConnectionInfoEvent::new(
+                    client.clone(),
+                    "test_player".to_string(),
+                    "test".to_string(),
+                    "test".to_string(),
+                    "test".to_string(),
+                    None,
+                ),
+            ));
+            let storage =
+                ServerStorageManager::init(StorageSettings::in_memory()).expect("in-memory storage must init");
+            client
+                .read_player_data(&storage)
+                .expect("player data must load from storage");
+            let entity = Entity::from_raw_u32(1).unwrap();
+            client.set_world_entity(Some(WorldEntity::new("default".to_string(), entity)));
+
+            let items_manager = Shared::new(Arc::new(timed_lock!(ItemsManager::default(), "test_items_manager")));
+            {
+                let mut items_manager = items_manager.write();
+                for slug in ["plank", "stick"] {
+                    let info = ItemInfo::create(
+                        slug,
+                        ItemType::other(),
+                        ItemDisplay::Icon("default://assets/resources/default/icons_artefacts/icon1.png".to_string()),
+                        slug,
+                        slug,
+                        64,
+                    );
+                    items_manager
+                        .add_item(&PluginsManager::default(), info)
+                        .expect("test item must be registered");
+                }
+                let recipe: RecipeManifest = serde_yaml::from_str(
+                    "{ type: shapeless, slug: sticks, ingredients: [{ item: plank }, { item: plank }], result: { item: stick, amount: 4 } }",
+                )
+                .unwrap();
+                items_manager.get_recipes_mut().add_recipe(recipe).unwrap();
+                items_manager.build_recipes(&Default::default()).unwrap();
+            }
+
+            let worlds_manager = SharedWorldsManager::new(Arc::new(timed_lock!(
+                crate::worlds::worlds_manager::WorldsManager::default(),
+                "test_worlds"
+            )));
+            let world_storage = WorldStorageManager::init(StorageSettings::in_memory(), "default").unwrap();
+            worlds_manager
+                .read()
+                .create_world("default".to_string(), world_storage, WorldStorageData::default())
+                .unwrap();
+
+            let position = BlockPosition::new(0, 0, 0);
+            let (section, block_position) = position.get_block_position();
+            let mut chunk_data = ChunkData::default();
+            chunk_data.push_section(ChunkSectionData::default());
+            let mut chunk_storage = ChunkStorage::create(chunk_data);
+            let block_inventory = chunk_storage.get_or_create_inventory_by_position_mut(
+                section,
+                block_position,
+                CRAFTING_INVENTORY_SLOTS,
+                100,
+            );
+            let inventory = block_inventory.get_inventory_mut();
+            inventory.set_slot(0, Item::create("plank").amount(2));
+            inventory.set_slot(1, Item::create("plank").amount(2));
+            inventory.set_slot(CRAFTING_RESULT_SLOT, Item::create("stick").amount(4));
+
+            let mut inventory_manager = InventoryManager::default();
+            let state = inventory_manager.state_mut();
+            state.register_chunk_inventories("default", position.get_chunk_position(), &chunk_storage);
+            state.mark_crafting_inventory(100);
+            state.watch_inventory(100, entity);
+
+            worlds_manager
+                .read()
+                .get_world_manager_mut(&"default".to_string())
+                .unwrap()
+                .get_chunks_map_mut()
+                .insert_loaded_chunk(&"default".to_string(), position.get_chunk_position(), chunk_storage);
+
+            let clients = SharedClientsContainer::new(Arc::new(timed_lock!(
+                crate::clients::clients_container::ClientsContainer::default(),
+                "test_clients"
+            )));
+            Self {
+                client,
+                clients,
+                items_manager,
+                inventory_manager,
+                worlds_manager,
+                inventory_id: 100,
+            }
+        }
+
+        fn apply(&mut self, action: InventoryAction) -> Result<(), Option<String>> {
+            let world = World::new();
+            let mut queue = CommandQueue::default();
+            let mut commands = Commands::new(&mut queue, &world);
+            InventoryActions::apply_action(
+                &self.client,
+                action,
+                &self.clients,
+                &self.items_manager,
+                &mut self.inventory_manager,
+                &self.worlds_manager,
+                &mut commands,
+            )
+        }
+
+        fn get_crafting_slot(&self, slot: usize) -> Option<(String, u16)> {
+            let ctx = InventoryActionCtx {
+                client: &self.client,
+                clients: &self.clients,
+                items_manager: &self.items_manager,
+                worlds_manager: &self.worlds_manager,
+            };
+            let target = InventoryTarget::World(self.inventory_id);
+            helpers::with_inventory_ref(&ctx, &self.inventory_manager, &target, |inventory| {
+                inventory
+                    .get_slot(slot)
+                    .map(|item| (format_item(Some(item)), item.get_amount()))
+            })
+            .flatten()
+        }
+    }
+
+    #[test]
+    fn takes_crafting_result_and_spends_grid() {
+        let mut setup = CraftingSetup::new();
+        let result = setup.apply(InventoryAction::Move {
+            from_inventory: InventoryTarget::World(setup.inventory_id),
+            from_slot: CRAFTING_RESULT_SLOT as u16,
+            to_inventory: InventoryTarget::Client(setup.client.get_client_id()),
+            to_slot: 0,
+            amount: 4,
+        });
+        assert!(result.is_ok(), "result take must be allowed: {:?}", result);
+
+        setup.client.with_player_data_mut(|player_data| {
+            let taken = player_data
+                .get_inventory()
+                .get_slot(0)
+                .expect("result must be in the player slot");
+            assert_eq!(format_item(Some(taken)), "4x stick");
+        });
+
+        // One plank of each grid slot is spent and the rest still makes the recipe
+        assert_eq!(setup.get_crafting_slot(0).map(|(_, amount)| amount), Some(1));
+        assert_eq!(setup.get_crafting_slot(1).map(|(_, amount)| amount), Some(1));
+        assert_eq!(
+            setup.get_crafting_slot(CRAFTING_RESULT_SLOT).map(|(item, _)| item),
+            Some("4x stick".to_string())
+        );
+    }
+
+    #[test]
+    fn empties_crafting_result_when_grid_is_spent() {
+        let mut setup = CraftingSetup::new();
+        for _ in 0..2 {
+            let result = setup.apply(InventoryAction::Move {
+                from_inventory: InventoryTarget::World(setup.inventory_id),
+                from_slot: CRAFTING_RESULT_SLOT as u16,
+                to_inventory: InventoryTarget::Client(setup.client.get_client_id()),
+                to_slot: 0,
+                amount: 4,
+            });
+            assert!(result.is_ok(), "result take must be allowed: {:?}", result);
+        }
+
+        assert_eq!(setup.get_crafting_slot(0), None);
+        assert_eq!(setup.get_crafting_slot(1), None);
+        assert_eq!(setup.get_crafting_slot(CRAFTING_RESULT_SLOT), None);
+        setup.client.with_player_data_mut(|player_data| {
+            assert_eq!(player_data.get_inventory().get_slot(0).unwrap().get_amount(), 8);
+        });
+    }
+
+    #[test]
+    fn rejects_crafting_result_move_into_grid() {
+        let mut setup = CraftingSetup::new();
+        let result = setup.apply(InventoryAction::Move {
+            from_inventory: InventoryTarget::World(setup.inventory_id),
+            from_slot: CRAFTING_RESULT_SLOT as u16,
+            to_inventory: InventoryTarget::World(setup.inventory_id),
+            to_slot: 2,
+            amount: 4,
+        });
+        assert!(result.is_err(), "result move into the grid must be rejected");
+
+        assert_eq!(setup.get_crafting_slot(2), None);
+        assert_eq!(setup.get_crafting_slot(0).map(|(_, amount)| amount), Some(2));
+        assert_eq!(
+            setup.get_crafting_slot(CRAFTING_RESULT_SLOT).map(|(item, _)| item),
+            Some("4x stick".to_string())
+        );
+    }
 }
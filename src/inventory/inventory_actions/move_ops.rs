@@ -23,7 +23,7 @@ pub(crate) fn apply_move(
     amount: u16,
 ) {
     if from_inventory == to_inventory {
-        let changes = with_inventory_mut(ctx, inventory_manager, &from_inventory, |inventory| {
+        let mut changes = with_inventory_mut(ctx, inventory_manager, &from_inventory, |inventory| {
             move_within_inventory(
                 inventory,
                 ctx.items_manager,
@@ -32,14 +32,14 @@ pub(crate) fn apply_move(
                 amount,
             )
         });
-        if let Some(changes) = changes.as_ref() {
-            after_inventory_modified(ctx, commands, &from_inventory, changes);
+        if let Some(changes) = changes.as_mut() {
+            after_inventory_modified(ctx, commands, inventory_manager, &from_inventory, changes);
         }
         broadcast_inventory_changes(ctx, inventory_manager, from_inventory, changes);
         return;
     }
 
-    let Some((from_type, from_changes, to_type, to_changes)) = move_between_inventories(
+    let Some((from_type, mut from_changes, to_type, mut to_changes)) = move_between_inventories(
         ctx,
         inventory_manager,
         from_inventory.clone(),
@@ -51,8 +51,8 @@ pub(crate) fn apply_move(
         return;
     };
 
-    after_inventory_modified(ctx, commands, &from_type, &from_changes);
-    after_inventory_modified(ctx, commands, &to_type, &to_changes);
+    after_inventory_modified(ctx, commands, inventory_manager, &from_type, &mut from_changes);
+    after_inventory_modified(ctx, commands, inventory_manager, &to_type, &mut to_changes);
     broadcast_inventory_changes(ctx, inventory_manager, from_type, Some(from_changes));
     broadcast_inventory_changes(ctx, inventory_manager, to_type, Some(to_changes));
 }
@@ -365,7 +365,7 @@ fn insert_item(
 mod tests {
     use std::sync::Arc;
 
-    use common::utils::debug::SmartRwLock;
+    use common::{inventory::item::ItemMetadata, utils::debug::SmartRwLock};
 
     use super::*;
     use crate::{items_manager::items_manager::ItemsManager, utils::Shared};
@@ -406,6 +406,22 @@ mod tests {
         assert_eq!(changes.len(), 2);
     }
 
+    #[test]
+    fn does_not_merge_items_with_different_metadata() {
+        let items_manager = shared_items_manager();
+        let mut inventory = Inventory::create(4);
+        let mut metadata = ItemMetadata::default();
+        metadata.set_display_name(Some("Lucky stone".to_string()));
+        inventory.set_slot(0, Item::create(1u16).amount(5).metadata(metadata));
+        inventory.set_slot(1, Item::create(1u16).amount(3));
+
+        let changes = move_within_inventory(&mut inventory, &items_manager, 0, 1, 2);
+
+        assert!(changes.is_empty());
+        assert_eq!(inventory.get_slot(0).unwrap().get_amount(), 5);
+        assert_eq!(inventory.get_slot(1).unwrap().get_amount(), 3);
+    }
+
     #[test]
     fn swaps_non_stackable_items_only_for_full_stack_move() {
         let items_manager = shared_items_manager();
@@ -85,6 +85,10 @@ impl InventoryWatchers {
         }
     }
 
+    pub fn get_entity_inventories(&self, entity: &Entity) -> Option<&Vec<u64>> {
+        self.by_entity.get(entity)
+    }
+
     pub fn get_inventory_watchers(&self, inventory_id: &u64) -> Option<&Vec<Entity>> {
         self.by_inventory.get(inventory_id)
     }
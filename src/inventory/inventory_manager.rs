@@ -7,6 +7,7 @@ use crate::{
     worlds::worlds_manager::SharedWorldsManager,
 };
 
+use super::audit_log::InventoryAuditLog;
 use super::inventory_actions::InventoryActions;
 use super::inventory_state::InventoryState;
 
@@ -15,6 +16,7 @@ use super::inventory_state::InventoryState;
 /// State only: no network synchronization happens here.
 pub struct InventoryManager {
     state: InventoryState,
+    audit_log: InventoryAuditLog,
 }
 
 impl InventoryManager {
@@ -36,6 +38,14 @@ impl InventoryManager {
         &mut self.state
     }
 
+    pub(crate) fn get_audit_log_mut(&mut self) -> &mut InventoryAuditLog {
+        &mut self.audit_log
+    }
+
+    pub(crate) fn set_audit_log(&mut self, audit_log: InventoryAuditLog) {
+        self.audit_log = audit_log;
+    }
+
     pub fn apply_action(
         &mut self,
         client: &Client,
@@ -1,4 +1,4 @@
-use ahash::AHashMap;
+use ahash::{AHashMap, AHashSet};
 use bevy::prelude::Entity;
 use common::chunks::{
     block_position::ChunkBlockPosition,
@@ -44,6 +44,9 @@ impl InventoryLocation {
 pub struct InventoryState {
     inventory_watchers: InventoryWatchers,
     world_inventories: AHashMap<u64, InventoryLocation>,
+
+    // World inventories of crafting blocks; their result slot is filled by the server
+    crafting_inventories: AHashSet<u64>,
 }
 
 impl InventoryState {
@@ -94,9 +97,18 @@ impl InventoryState {
             let inventory_id = block_inventory.get_inventory().get_id();
             self.inventory_watchers.remove_all_inventory_tickets(&inventory_id);
             self.world_inventories.remove(&inventory_id);
+            self.crafting_inventories.remove(&inventory_id);
         }
     }
 
+    pub fn mark_crafting_inventory(&mut self, inventory_id: u64) {
+        self.crafting_inventories.insert(inventory_id);
+    }
+
+    pub fn is_crafting_inventory(&self, inventory_id: &u64) -> bool {
+        self.crafting_inventories.contains(inventory_id)
+    }
+
     pub fn get_inventory_location(&self, inventory_id: &u64) -> Option<&InventoryLocation> {
         self.world_inventories.get(inventory_id)
     }
@@ -113,6 +125,14 @@ impl InventoryState {
         self.inventory_watchers.remove_all_entity_tickets(entity);
     }
 
+    /// World inventories currently opened by the entity
+    pub fn get_watched_inventories(&self, entity: &Entity) -> Vec<u64> {
+        match self.inventory_watchers.get_entity_inventories(entity) {
+            Some(inventories) => inventories.clone(),
+            None => Vec::new(),
+        }
+    }
+
     pub fn get_inventory_watchers(&self, inventory_id: &u64) -> Option<&Vec<Entity>> {
         self.inventory_watchers.get_inventory_watchers(inventory_id)
     }
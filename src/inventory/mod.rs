@@ -1,17 +1,25 @@
+pub mod audit_log;
 pub mod commands;
 pub mod inventory_actions;
 pub mod inventory_load_state;
 pub mod inventory_manager;
 pub mod inventory_state;
 
-use bevy_app::{App, Plugin, Startup};
+use bevy::time::common_conditions::on_timer;
+use bevy_app::{App, Plugin, Startup, Update};
+use bevy_ecs::schedule::IntoScheduleConfigs;
 use common::timed_lock;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
-use crate::{plugins::server_plugin::host_functions::set_inventory_manager_bridge, utils::Shared};
+use crate::{
+    launch_settings::LaunchSettings, plugins::server_plugin::host_functions::set_inventory_manager_bridge,
+    runtime_plugin::RuntimePlugin, utils::Shared,
+};
 
 pub type SharedInventoryManager = Shared<inventory_manager::InventoryManager>;
 
+pub const AUDIT_LOG_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
 #[derive(Default)]
 pub struct InventoryPlugin;
 
@@ -22,7 +30,11 @@ impl Plugin for InventoryPlugin {
             "inventory_manager"
         )));
         app.insert_resource(inventory_manager);
-        app.add_systems(Startup, register_inventory_manager_bridge);
+        app.add_systems(Startup, (register_inventory_manager_bridge, open_inventory_audit_log));
+        app.add_systems(
+            Update,
+            flush_inventory_audit_log.run_if(on_timer(AUDIT_LOG_FLUSH_INTERVAL)),
+        );
     }
 }
 
@@ -30,3 +42,24 @@ fn register_inventory_manager_bridge(inventory_manager: bevy_ecs::system::Res<Sh
     let _s = crate::span!("inventory.register_inventory_manager_bridge");
     set_inventory_manager_bridge(inventory_manager.clone_inner());
 }
+
+fn open_inventory_audit_log(
+    inventory_manager: bevy_ecs::system::Res<SharedInventoryManager>,
+    launch_settings: bevy_ecs::system::Res<LaunchSettings>,
+) {
+    let _s = crate::span!("inventory.open_inventory_audit_log");
+    match audit_log::InventoryAuditLog::open(&launch_settings.get_server_data_path()) {
+        Ok(audit_log) => inventory_manager.write().set_audit_log(audit_log),
+        Err(e) => {
+            log::error!(target: "inventory", "{}", e);
+            RuntimePlugin::stop();
+        }
+    }
+}
+
+fn flush_inventory_audit_log(inventory_manager: bevy_ecs::system::Res<SharedInventoryManager>) {
+    let _s = crate::span!("inventory.flush_inventory_audit_log");
+    if let Err(e) = inventory_manager.write().get_audit_log_mut().flush() {
+        log::error!(target: "inventory", "{}", e);
+    }
+}
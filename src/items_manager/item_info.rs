@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use strum_macros::Display;
 
 use common::inventory::item::{BodyPart, WeaponKind};
@@ -64,6 +65,10 @@ pub struct ItemInfo {
     description: String,
     #[serde(default = "ItemInfo::default_max_stack_size")]
     max_stack_size: u16,
+
+    // Stat modifiers applied while the item is equipped; see `EntityStats`
+    #[serde(default)]
+    stats: BTreeMap<String, f32>,
 }
 
 impl ItemInfo {
@@ -87,9 +92,15 @@ impl ItemInfo {
             title: title.into(),
             description: description.into(),
             max_stack_size,
+            stats: Default::default(),
         }
     }
 
+    pub(crate) fn stats(mut self, stats: BTreeMap<String, f32>) -> Self {
+        self.stats = stats;
+        self
+    }
+
     pub(crate) fn slug(&self) -> &String {
         &self.slug
     }
@@ -113,4 +124,8 @@ impl ItemInfo {
     pub(crate) fn max_stack_size(&self) -> u16 {
         self.max_stack_size
     }
+
+    pub(crate) fn get_stats(&self) -> &BTreeMap<String, f32> {
+        &self.stats
+    }
 }
@@ -1,11 +1,13 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use bevy_ecs::resource::Resource;
 use common::inventory::inventory::{ClientInventory, Inventory};
-use common::inventory::item::{ClientItem, ClientItemKind, Item, ItemKind};
+use common::inventory::item::{ClientItem, ClientItemKind, Item, ItemKind, ItemMetadata};
 
 use super::item_info::{ItemDisplay, ItemInfo, ItemType};
-use crate::plugins::plugins_manager::PluginsManager;
+use super::loot_tables::LootTablesRegistry;
+use super::recipes::RecipesRegistry;
+use crate::plugins::{plugins_manager::PluginsManager, server_settings::ServerSettings};
 use crate::utils::Shared;
 
 pub type SharedItemsManager = Shared<ItemsManager>;
@@ -13,6 +15,8 @@ pub type SharedItemsManager = Shared<ItemsManager>;
 #[derive(Resource)]
 pub struct ItemsManager {
     items: HashMap<String, ItemInfo>,
+    recipes: RecipesRegistry,
+    loot_tables: LootTablesRegistry,
 }
 
 impl Default for ItemsManager {
@@ -25,14 +29,23 @@ impl ItemsManager {
     const BLOCK_MAX_STACK_SIZE: u16 = 64;
 
     pub(crate) fn new() -> Self {
-        Self { items: HashMap::new() }
+        Self {
+            items: HashMap::new(),
+            recipes: Default::default(),
+            loot_tables: Default::default(),
+        }
     }
 
     pub(crate) fn to_client_item(&self, item: &Item) -> ClientItem {
+        let metadata = item.get_metadata();
         match item.get_item_kind() {
-            ItemKind::Block(block_id) => {
-                ClientItem::create(ClientItemKind::Block(*block_id), item.get_amount(), None, None, None)
-            }
+            ItemKind::Block(block_id) => ClientItem::create(
+                ClientItemKind::Block(*block_id),
+                item.get_amount(),
+                None,
+                metadata.get_display_name().cloned(),
+                get_client_description(None, metadata),
+            ),
             ItemKind::CustomItem(slug) => {
                 let Some(info) = self.items.get(slug) else {
                     return ClientItem::create(ClientItemKind::NotFound, item.get_amount(), None, Some(slug.clone()), None);
@@ -42,12 +55,13 @@ impl ItemsManager {
                     ItemDisplay::Icon(icon) => icon.clone(),
                 };
 
+                let title = metadata.get_display_name().unwrap_or(info.title()).clone();
                 ClientItem::create(
                     ClientItemKind::Icon(icon.clone()),
                     item.get_amount(),
                     Some(icon),
-                    Some(info.title().clone()),
-                    Some(info.description().clone()),
+                    Some(title),
+                    get_client_description(Some(info.description()), metadata),
                 )
             }
         }
@@ -96,11 +110,21 @@ impl ItemsManager {
             }
         }
 
+        for (stat, value) in item.get_stats() {
+            if !value.is_finite() {
+                return Err(format!("Item \"{}\" stat \"{}\" must be a finite number", slug, stat));
+            }
+        }
+
         self.items.insert(slug, item);
         Ok(())
     }
 
     pub(crate) fn get_max_stack_size(&self, item: &Item) -> u16 {
+        // Worn items are never stacked
+        if item.get_metadata().get_max_durability().is_some() {
+            return 1;
+        }
         match item.get_item_kind() {
             ItemKind::Block(_) => Self::BLOCK_MAX_STACK_SIZE,
             ItemKind::CustomItem(slug) => self.items.get(slug).map(ItemInfo::max_stack_size).unwrap_or(1),
@@ -114,6 +138,14 @@ impl ItemsManager {
         }
     }
 
+    /// Stat modifiers of the item while it is equipped
+    pub(crate) fn get_item_stats(&self, item: &Item) -> Option<&BTreeMap<String, f32>> {
+        match item.get_item_kind() {
+            ItemKind::Block(_) => None,
+            ItemKind::CustomItem(slug) => self.items.get(slug).map(ItemInfo::get_stats),
+        }
+    }
+
     pub(crate) fn has_item(&self, slug: &str) -> bool {
         self.items.contains_key(slug)
     }
@@ -121,4 +153,80 @@ impl ItemsManager {
     pub(crate) fn iter_slugs(&self) -> impl Iterator<Item = &String> {
         self.items.keys()
     }
+
+    pub(crate) fn get_recipes(&self) -> &RecipesRegistry {
+        &self.recipes
+    }
+
+    pub(crate) fn get_recipes_mut(&mut self) -> &mut RecipesRegistry {
+        &mut self.recipes
+    }
+
+    /// Recipes can reference blocks only after the block id map is generated
+    pub(crate) fn build_recipes(&mut self, server_settings: &ServerSettings) -> Result<(), String> {
+        let items = &self.items;
+        self.recipes.build(server_settings, |slug| items.contains_key(slug))
+    }
+
+    pub(crate) fn get_loot_tables(&self) -> &LootTablesRegistry {
+        &self.loot_tables
+    }
+
+    pub(crate) fn get_loot_tables_mut(&mut self) -> &mut LootTablesRegistry {
+        &mut self.loot_tables
+    }
+
+    pub(crate) fn build_loot_tables(&mut self, server_settings: &ServerSettings) -> Result<(), String> {
+        let items = &self.items;
+        self.loot_tables.build(server_settings, |slug| items.contains_key(slug))
+    }
+}
+
+/// Type description followed by the lore and the durability of the item instance
+fn get_client_description(description: Option<&String>, metadata: &ItemMetadata) -> Option<String> {
+    let mut lines: Vec<String> = Default::default();
+    if let Some(description) = description.filter(|d| !d.is_empty()) {
+        lines.push(description.clone());
+    }
+    lines.extend(metadata.get_lore().iter().cloned());
+    if let (Some(durability), Some(max_durability)) = (metadata.get_durability(), metadata.get_max_durability()) {
+        lines.push(format!("Durability: {}/{}", durability, max_durability));
+    }
+
+    match lines.is_empty() {
+        true => None,
+        false => Some(lines.join("\n")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_client_description, ItemsManager};
+    use common::inventory::item::{Item, ItemMetadata};
+
+    #[test]
+    fn test_client_description() {
+        let mut metadata = ItemMetadata::default();
+        assert_eq!(get_client_description(Some(&String::new()), &metadata), None);
+
+        metadata.set_lore(vec!["Forged by the dwarves".to_string()]);
+        metadata.set_durability(40, 100);
+        assert_eq!(
+            get_client_description(Some(&"Sharp sword".to_string()), &metadata).unwrap(),
+            "Sharp sword\nForged by the dwarves\nDurability: 40/100"
+        );
+    }
+
+    #[test]
+    fn test_worn_items_are_not_stacked() {
+        let items_manager = ItemsManager::default();
+        let mut metadata = ItemMetadata::default();
+        metadata.set_durability(100, 100);
+
+        assert_eq!(items_manager.get_max_stack_size(&Item::create(1u16)), 64);
+        assert_eq!(
+            items_manager.get_max_stack_size(&Item::create(1u16).metadata(metadata)),
+            1
+        );
+    }
 }
@@ -0,0 +1,331 @@
+use common::chunks::block_position::BlockPosition;
+use common::inventory::item::{Item, ItemKind};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::manifest_registry::{ManifestRegistry, RegistryManifest};
+use super::recipes::{resolve_item_manifest, RecipeItemManifest};
+use crate::plugins::server_settings::ServerSettings;
+
+/// `2` or `{ min: 1, max: 3 }`; both bounds are inclusive
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum LootRangeManifest {
+    Exact(u16),
+    Range { min: u16, max: u16 },
+}
+
+impl Default for LootRangeManifest {
+    fn default() -> Self {
+        Self::Exact(1)
+    }
+}
+
+impl LootRangeManifest {
+    fn bounds(&self) -> (u16, u16) {
+        match self {
+            LootRangeManifest::Exact(value) => (*value, *value),
+            LootRangeManifest::Range { min, max } => (*min, *max),
+        }
+    }
+}
+
+/// Entry is rolled only where all its conditions are met
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LootConditionManifest {
+    MinY(i64),
+    MaxY(i64),
+    World(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LootEntryManifest {
+    #[serde(flatten)]
+    pub item: RecipeItemManifest,
+
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+
+    #[serde(default)]
+    pub amount: LootRangeManifest,
+
+    #[serde(default)]
+    pub conditions: Vec<LootConditionManifest>,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+/// Loot table from the plugin manifest
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LootTableManifest {
+    pub slug: String,
+
+    // How many entries are picked
+    #[serde(default)]
+    pub rolls: LootRangeManifest,
+
+    pub entries: Vec<LootEntryManifest>,
+}
+
+impl RegistryManifest for LootTableManifest {
+    const KIND: &'static str = "loot table";
+
+    fn get_slug(&self) -> &String {
+        &self.slug
+    }
+}
+
+#[derive(Clone)]
+struct LootEntry {
+    item: ItemKind,
+    weight: u32,
+    amount: (u16, u16),
+    conditions: Vec<LootConditionManifest>,
+}
+
+impl LootEntry {
+    fn is_allowed(&self, world_slug: &str, position: &BlockPosition) -> bool {
+        self.conditions.iter().all(|condition| match condition {
+            LootConditionManifest::MinY(y) => position.y >= *y,
+            LootConditionManifest::MaxY(y) => position.y <= *y,
+            LootConditionManifest::World(slug) => slug == world_slug,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct LootTable {
+    slug: String,
+    rolls: (u16, u16),
+    entries: Vec<LootEntry>,
+}
+
+impl LootTable {
+    pub fn get_slug(&self) -> &String {
+        &self.slug
+    }
+
+    /// Items of the inventory at the position
+    ///
+    /// The result depends only on the world seed and the position, so the same chest gets the same loot.
+    pub fn roll(&self, world_seed: u64, world_slug: &str, position: &BlockPosition) -> Vec<Item> {
+        let mut rng = LootRng::create(world_seed, position);
+        let entries: Vec<&LootEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.is_allowed(world_slug, position))
+            .collect();
+        let total_weight: u64 = entries.iter().map(|entry| entry.weight as u64).sum();
+        if total_weight == 0 {
+            return Default::default();
+        }
+
+        let rolls = rng.range(self.rolls.0 as u64, self.rolls.1 as u64);
+        let mut items: Vec<Item> = Default::default();
+        for _ in 0..rolls {
+            let mut target = rng.range(0, total_weight - 1);
+            for entry in entries.iter() {
+                if target < entry.weight as u64 {
+                    let amount = rng.range(entry.amount.0 as u64, entry.amount.1 as u64) as u16;
+                    items.push(Item::create(entry.item.clone()).amount(amount));
+                    break;
+                }
+                target -= entry.weight as u64;
+            }
+        }
+        items
+    }
+}
+
+/// SplitMix64; unlike `rand` generators its sequence never changes between versions
+struct LootRng {
+    state: u64,
+}
+
+impl LootRng {
+    fn create(world_seed: u64, position: &BlockPosition) -> Self {
+        let mut rng = Self { state: world_seed };
+        for coordinate in [position.x, position.y, position.z] {
+            rng.state ^= coordinate as u64;
+            rng.state = rng.next_u64();
+        }
+        rng
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Inclusive
+    fn range(&mut self, min: u64, max: u64) -> u64 {
+        min + self.next_u64() % (max - min + 1)
+    }
+}
+
+/// Loot tables of all plugins
+#[derive(Default)]
+pub struct LootTablesRegistry {
+    manifests: ManifestRegistry<LootTableManifest>,
+    loot_tables: HashMap<String, LootTable>,
+}
+
+impl LootTablesRegistry {
+    pub(crate) fn add_loot_table(&mut self, loot_table: LootTableManifest) -> Result<(), String> {
+        self.manifests.add(loot_table)
+    }
+
+    /// Resolves block slugs and checks that all items are registered
+    pub(crate) fn build(
+        &mut self,
+        server_settings: &ServerSettings,
+        has_item: impl Fn(&str) -> bool,
+    ) -> Result<(), String> {
+        let loot_tables = self
+            .manifests
+            .build(|manifest| Self::resolve(manifest, server_settings, &has_item))?;
+        self.loot_tables = loot_tables
+            .into_iter()
+            .map(|loot_table| (loot_table.slug.clone(), loot_table))
+            .collect();
+        Ok(())
+    }
+
+    fn resolve(
+        manifest: &LootTableManifest,
+        server_settings: &ServerSettings,
+        has_item: &impl Fn(&str) -> bool,
+    ) -> Result<LootTable, String> {
+        let rolls = manifest.rolls.bounds();
+        if rolls.0 > rolls.1 {
+            return Err(format!("rolls min {} is greater than max {}", rolls.0, rolls.1));
+        }
+        if manifest.entries.is_empty() {
+            return Err("entries are empty".to_string());
+        }
+
+        let mut entries: Vec<LootEntry> = Default::default();
+        for entry in manifest.entries.iter() {
+            let amount = entry.amount.bounds();
+            if amount.0 == 0 || amount.0 > amount.1 {
+                return Err(format!(
+                    "amount must be from 1 and min <= max; got {}..{}",
+                    amount.0, amount.1
+                ));
+            }
+            if entry.weight == 0 {
+                return Err("weight must be greater than 0".to_string());
+            }
+            entries.push(LootEntry {
+                item: resolve_item_manifest(&entry.item, server_settings, has_item)?,
+                weight: entry.weight,
+                amount,
+                conditions: entry.conditions.clone(),
+            });
+        }
+
+        Ok(LootTable {
+            slug: manifest.slug.clone(),
+            rolls,
+            entries,
+        })
+    }
+
+    pub fn get_loot_table(&self, slug: &str) -> Option<&LootTable> {
+        self.loot_tables.get(slug)
+    }
+
+    pub fn get_loot_tables_count(&self) -> usize {
+        self.loot_tables.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LootConditionManifest, LootEntry, LootTable};
+    use common::chunks::block_position::BlockPosition;
+    use common::inventory::item::ItemKind;
+
+    fn entry(slug: &str, weight: u32, amount: (u16, u16)) -> LootEntry {
+        LootEntry {
+            item: ItemKind::CustomItem(slug.to_string()),
+            weight,
+            amount,
+            conditions: Default::default(),
+        }
+    }
+
+    fn table(rolls: (u16, u16), entries: Vec<LootEntry>) -> LootTable {
+        LootTable {
+            slug: "chest".to_string(),
+            rolls,
+            entries,
+        }
+    }
+
+    /// Loot of chests placed along the x axis
+    fn roll_many(table: &LootTable, count: i64) -> Vec<(String, u16)> {
+        (0..count)
+            .flat_map(|x| table.roll(42, "default", &BlockPosition::new(x, 20, 0)))
+            .map(|item| match item.get_item_kind() {
+                ItemKind::CustomItem(slug) => (slug.clone(), item.get_amount()),
+                ItemKind::Block(id) => (format!("block:{}", id), item.get_amount()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_roll_is_reproducible() {
+        let chest = table((2, 5), vec![entry("coin", 5, (1, 16)), entry("sword", 1, (1, 1))]);
+        let position = BlockPosition::new(10, 20, -30);
+        let items = chest.roll(42, "default", &position);
+        assert!((2..=5).contains(&items.len()));
+
+        let same = chest.roll(42, "default", &position);
+        assert_eq!(items.len(), same.len());
+        for (a, b) in items.iter().zip(same.iter()) {
+            assert!(a.can_stack_with(b) && a.get_amount() == b.get_amount());
+        }
+    }
+
+    #[test]
+    fn test_weights() {
+        let chest = table((1, 1), vec![entry("coin", 9, (1, 1)), entry("gem", 1, (1, 1))]);
+        let items = roll_many(&chest, 1000);
+        assert_eq!(items.len(), 1000);
+
+        let gems = items.iter().filter(|(slug, _)| slug == "gem").count();
+        assert!(
+            (50..=150).contains(&gems),
+            "gem must drop about once in ten rolls; got {}",
+            gems
+        );
+    }
+
+    #[test]
+    fn test_amount_range() {
+        let chest = table((1, 1), vec![entry("coin", 1, (3, 5))]);
+        let amounts: Vec<u16> = roll_many(&chest, 200).into_iter().map(|(_, amount)| amount).collect();
+        assert!(amounts.iter().all(|amount| (3..=5).contains(amount)));
+        for amount in 3..=5 {
+            assert!(amounts.contains(&amount), "amount {} is never rolled", amount);
+        }
+    }
+
+    #[test]
+    fn test_empty_pool() {
+        // Every entry is filtered out by its condition
+        let mut deep = entry("diamond", 1, (1, 1));
+        deep.conditions = vec![LootConditionManifest::MaxY(0)];
+        assert!(roll_many(&table((1, 3), vec![deep]), 50).is_empty());
+
+        let nothing = table((0, 0), vec![entry("coin", 1, (1, 1))]);
+        assert!(roll_many(&nothing, 50).is_empty());
+    }
+}
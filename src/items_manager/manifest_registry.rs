@@ -0,0 +1,43 @@
+/// Manifest registered by plugins under a unique slug
+pub trait RegistryManifest {
+    /// Name of the manifest in errors
+    const KIND: &'static str;
+
+    fn get_slug(&self) -> &String;
+}
+
+/// Manifests of all plugins
+///
+/// Manifests are collected while plugins are loading and resolved
+/// by `build` when block ids are known.
+pub struct ManifestRegistry<M: RegistryManifest> {
+    manifests: Vec<M>,
+}
+
+impl<M: RegistryManifest> Default for ManifestRegistry<M> {
+    fn default() -> Self {
+        Self {
+            manifests: Default::default(),
+        }
+    }
+}
+
+impl<M: RegistryManifest> ManifestRegistry<M> {
+    pub(crate) fn add(&mut self, manifest: M) -> Result<(), String> {
+        if self.manifests.iter().any(|m| m.get_slug() == manifest.get_slug()) {
+            return Err(format!("{} \"{}\" already exists", M::KIND, manifest.get_slug()));
+        }
+        self.manifests.push(manifest);
+        Ok(())
+    }
+
+    /// Resolves all manifests; the error names the manifest which failed
+    pub(crate) fn build<T>(&self, resolve: impl Fn(&M) -> Result<T, String>) -> Result<Vec<T>, String> {
+        self.manifests
+            .iter()
+            .map(|manifest| {
+                resolve(manifest).map_err(|e| format!("&c{} &4\"{}\"&c: {}", M::KIND, manifest.get_slug(), e))
+            })
+            .collect()
+    }
+}
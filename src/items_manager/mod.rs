@@ -1,12 +1,21 @@
 pub mod item_info;
 pub mod items_manager;
+pub mod loot_tables;
+pub mod manifest_registry;
+pub mod recipes;
 
 pub use item_info::{ItemDisplay, ItemInfo, ItemType};
 
 use std::sync::Arc;
 
-use crate::plugins::server_plugin::host_functions::set_items_manager_bridge;
+use crate::plugins::{
+    plugins_manager::PluginsManager,
+    server_plugin::host_functions::set_items_manager_bridge,
+    server_settings::{rescan_server_settings, ServerSettings},
+};
+use crate::runtime_plugin::RuntimePlugin;
 use bevy_app::{App, Plugin};
+use bevy_ecs::schedule::IntoScheduleConfigs;
 use bevy_ecs::system::Res;
 use common::timed_lock;
 use items_manager::{ItemsManager, SharedItemsManager};
@@ -19,6 +28,8 @@ impl Plugin for ItemsManagerPlugin {
         let items_manager = SharedItemsManager::new(Arc::new(timed_lock!(ItemsManager::default(), "items_manager")));
         app.insert_resource(items_manager);
         app.add_systems(bevy_app::Startup, register_items_manager_bridge);
+        app.add_systems(bevy_app::Startup, build_recipes.after(rescan_server_settings));
+        app.add_systems(bevy_app::Startup, build_loot_tables.after(rescan_server_settings));
     }
 }
 
@@ -26,3 +37,71 @@ fn register_items_manager_bridge(items_manager: Res<SharedItemsManager>) {
     let _s = crate::span!("items_manager.register_items_manager_bridge");
     set_items_manager_bridge(items_manager.clone_inner());
 }
+
+/// Takes recipes from plugin manifests and resolves all registered recipes
+fn build_recipes(
+    plugins_manager: Res<PluginsManager>,
+    server_settings: Res<ServerSettings>,
+    items_manager: Res<SharedItemsManager>,
+) {
+    let _s = crate::span!("items_manager.build_recipes");
+    if RuntimePlugin::is_stopped() {
+        return;
+    }
+
+    let mut items_manager = items_manager.write();
+    for plugin in plugins_manager.iter_plugins() {
+        let recipes = items_manager.get_recipes_mut();
+        for recipe in plugin.get_recipes().iter() {
+            if let Err(e) = recipes.add_recipe(recipe.clone()) {
+                log::error!(target: "resources", "&cPlugin &4\"{}\"&c recipes error:", plugin.get_slug());
+                log::error!(target: "resources", "{}", e);
+                RuntimePlugin::stop();
+                return;
+            }
+        }
+        for block_slug in plugin.get_crafting_blocks().iter() {
+            recipes.add_crafting_block(block_slug.clone());
+        }
+    }
+
+    if let Err(e) = items_manager.build_recipes(&server_settings) {
+        log::error!(target: "resources", "&cRecipes loading error:");
+        log::error!(target: "resources", "{}", e);
+        RuntimePlugin::stop();
+        return;
+    }
+    log::info!(target: "resources", "Recipes loaded: &e{}", items_manager.get_recipes().get_recipes_count());
+}
+
+/// Takes loot tables from plugin manifests and resolves them
+fn build_loot_tables(
+    plugins_manager: Res<PluginsManager>,
+    server_settings: Res<ServerSettings>,
+    items_manager: Res<SharedItemsManager>,
+) {
+    let _s = crate::span!("items_manager.build_loot_tables");
+    if RuntimePlugin::is_stopped() {
+        return;
+    }
+
+    let mut items_manager = items_manager.write();
+    for plugin in plugins_manager.iter_plugins() {
+        for loot_table in plugin.get_loot_tables().iter() {
+            if let Err(e) = items_manager.get_loot_tables_mut().add_loot_table(loot_table.clone()) {
+                log::error!(target: "resources", "&cPlugin &4\"{}\"&c loot tables error:", plugin.get_slug());
+                log::error!(target: "resources", "{}", e);
+                RuntimePlugin::stop();
+                return;
+            }
+        }
+    }
+
+    if let Err(e) = items_manager.build_loot_tables(&server_settings) {
+        log::error!(target: "resources", "&cLoot tables loading error:");
+        log::error!(target: "resources", "{}", e);
+        RuntimePlugin::stop();
+        return;
+    }
+    log::info!(target: "resources", "Loot tables loaded: &e{}", items_manager.get_loot_tables().get_loot_tables_count());
+}
@@ -0,0 +1,479 @@
+use common::chunks::chunk_data::BlockIndexType;
+use common::inventory::item::{Item, ItemKind};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashSet},
+    time::Duration,
+};
+
+use super::manifest_registry::{ManifestRegistry, RegistryManifest};
+use crate::plugins::server_settings::ServerSettings;
+
+/// Width and height of the crafting grid
+pub const CRAFTING_GRID_SIZE: usize = 3;
+
+/// Slot of the crafting inventory filled by the server
+pub const CRAFTING_RESULT_SLOT: usize = CRAFTING_GRID_SIZE * CRAFTING_GRID_SIZE;
+
+pub const CRAFTING_INVENTORY_SLOTS: usize = CRAFTING_RESULT_SLOT + 1;
+
+/// Item reference inside the manifest: `{ block: stone }` or `{ item: iron_ingot }`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecipeItemManifest {
+    Block(String),
+    Item(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecipeResultManifest {
+    #[serde(flatten)]
+    pub item: RecipeItemManifest,
+
+    #[serde(default = "default_result_amount")]
+    pub amount: u16,
+}
+
+fn default_result_amount() -> u16 {
+    1
+}
+
+/// Recipe from the plugin manifest or from WASM
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RecipeManifest {
+    Shaped {
+        slug: String,
+
+        // Rows of the grid; space is an empty cell, any other char is the key of the ingredient
+        pattern: Vec<String>,
+        key: BTreeMap<char, RecipeItemManifest>,
+        result: RecipeResultManifest,
+    },
+    Shapeless {
+        slug: String,
+        ingredients: Vec<RecipeItemManifest>,
+        result: RecipeResultManifest,
+    },
+
+    // Registry only: the server doesn't smelt, plugins look it up and run the timer
+    Smelting {
+        slug: String,
+        input: RecipeItemManifest,
+        result: RecipeResultManifest,
+
+        // Seconds
+        time: f32,
+    },
+}
+
+impl RegistryManifest for RecipeManifest {
+    const KIND: &'static str = "recipe";
+
+    fn get_slug(&self) -> &String {
+        match self {
+            RecipeManifest::Shaped { slug, .. }
+            | RecipeManifest::Shapeless { slug, .. }
+            | RecipeManifest::Smelting { slug, .. } => slug,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum RecipeKind {
+    /// Cells of the pattern without empty border rows and columns
+    Shaped {
+        width: usize,
+        height: usize,
+        cells: Vec<Option<ItemKind>>,
+    },
+    Shapeless {
+        ingredients: Vec<ItemKind>,
+    },
+    Smelting {
+        input: ItemKind,
+        time: Duration,
+    },
+}
+
+#[derive(Clone)]
+pub struct Recipe {
+    slug: String,
+    kind: RecipeKind,
+    result: Item,
+}
+
+impl Recipe {
+    pub fn get_slug(&self) -> &String {
+        &self.slug
+    }
+
+    pub fn get_kind(&self) -> &RecipeKind {
+        &self.kind
+    }
+
+    pub fn get_result(&self) -> &Item {
+        &self.result
+    }
+
+    /// Grid must contain CRAFTING_GRID_SIZE rows; every cell is one ingredient
+    pub fn matches_grid(&self, grid: &[Option<ItemKind>]) -> bool {
+        match &self.kind {
+            RecipeKind::Shaped { width, height, cells } => {
+                let Some((min_row, min_column, grid_width, grid_height)) = get_grid_bounds(grid) else {
+                    return false;
+                };
+                if grid_width != *width || grid_height != *height {
+                    return false;
+                }
+                (0..*height).all(|row| {
+                    (0..*width).all(|column| {
+                        grid[(min_row + row) * CRAFTING_GRID_SIZE + min_column + column] == cells[row * width + column]
+                    })
+                })
+            }
+            RecipeKind::Shapeless { ingredients } => {
+                let mut left = ingredients.clone();
+                for kind in grid.iter().flatten() {
+                    let Some(index) = left.iter().position(|ingredient| ingredient == kind) else {
+                        return false;
+                    };
+                    left.swap_remove(index);
+                }
+                left.is_empty()
+            }
+            RecipeKind::Smelting { .. } => false,
+        }
+    }
+}
+
+/// First row, first column, width and height of the filled part of the grid
+fn get_grid_bounds(grid: &[Option<ItemKind>]) -> Option<(usize, usize, usize, usize)> {
+    let filled: Vec<usize> = (0..grid.len()).filter(|index| grid[*index].is_some()).collect();
+    let min_row = filled.iter().map(|index| index / CRAFTING_GRID_SIZE).min()?;
+    let max_row = filled.iter().map(|index| index / CRAFTING_GRID_SIZE).max()?;
+    let min_column = filled.iter().map(|index| index % CRAFTING_GRID_SIZE).min()?;
+    let max_column = filled.iter().map(|index| index % CRAFTING_GRID_SIZE).max()?;
+    Some((min_row, min_column, max_column - min_column + 1, max_row - min_row + 1))
+}
+
+/// Block id or the slug of the registered item
+pub(crate) fn resolve_item_manifest(
+    item: &RecipeItemManifest,
+    server_settings: &ServerSettings,
+    has_item: &impl Fn(&str) -> bool,
+) -> Result<ItemKind, String> {
+    match item {
+        RecipeItemManifest::Block(slug) => match server_settings.get_block_id_by_slug(slug) {
+            Some(id) => Ok(ItemKind::Block(id)),
+            None => Err(format!("block &4\"{}\"&c not found", slug)),
+        },
+        RecipeItemManifest::Item(slug) => match has_item(slug) {
+            true => Ok(ItemKind::CustomItem(slug.clone())),
+            false => Err(format!("item &4\"{}\"&c not found", slug)),
+        },
+    }
+}
+
+/// Recipes and crafting blocks of all plugins
+#[derive(Default)]
+pub struct RecipesRegistry {
+    manifests: ManifestRegistry<RecipeManifest>,
+    crafting_block_slugs: Vec<String>,
+
+    recipes: Vec<Recipe>,
+    crafting_blocks: HashSet<BlockIndexType>,
+    built: bool,
+}
+
+impl RecipesRegistry {
+    pub(crate) fn add_recipe(&mut self, recipe: RecipeManifest) -> Result<(), String> {
+        // WASM plugins can try to add recipes at any time
+        if self.built {
+            return Err(format!(
+                "Recipe \"{}\" can be added only while plugins are loading",
+                recipe.get_slug()
+            ));
+        }
+        self.manifests.add(recipe)
+    }
+
+    pub(crate) fn add_crafting_block(&mut self, block_slug: String) {
+        self.crafting_block_slugs.push(block_slug);
+    }
+
+    /// Resolves block slugs and checks that all items are registered
+    pub(crate) fn build(
+        &mut self,
+        server_settings: &ServerSettings,
+        has_item: impl Fn(&str) -> bool,
+    ) -> Result<(), String> {
+        let resolve_item = |item: &RecipeItemManifest| -> Result<ItemKind, String> {
+            resolve_item_manifest(item, server_settings, &has_item)
+        };
+
+        let recipes = self
+            .manifests
+            .build(|manifest| Self::resolve(manifest, &resolve_item))?;
+
+        let mut crafting_blocks: HashSet<BlockIndexType> = Default::default();
+        for slug in self.crafting_block_slugs.iter() {
+            let Some(id) = server_settings.get_block_id_by_slug(slug) else {
+                return Err(format!("&ccrafting block &4\"{}\"&c not found", slug));
+            };
+            crafting_blocks.insert(id);
+        }
+
+        self.recipes = recipes;
+        self.crafting_blocks = crafting_blocks;
+        self.built = true;
+        Ok(())
+    }
+
+    fn resolve(
+        manifest: &RecipeManifest,
+        resolve_item: &impl Fn(&RecipeItemManifest) -> Result<ItemKind, String>,
+    ) -> Result<Recipe, String> {
+        let (slug, kind, result) = match manifest {
+            RecipeManifest::Shaped {
+                slug,
+                pattern,
+                key,
+                result,
+            } => {
+                let width = pattern.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+                if pattern.len() > CRAFTING_GRID_SIZE || width > CRAFTING_GRID_SIZE {
+                    return Err(format!("pattern is larger than {0}x{0}", CRAFTING_GRID_SIZE));
+                }
+
+                let mut grid: Vec<Option<ItemKind>> = vec![None; CRAFTING_GRID_SIZE * CRAFTING_GRID_SIZE];
+                for (row, line) in pattern.iter().enumerate() {
+                    for (column, c) in line.chars().enumerate() {
+                        if c == ' ' {
+                            continue;
+                        }
+                        let Some(item) = key.get(&c) else {
+                            return Err(format!("key &4'{}'&c is not defined", c));
+                        };
+                        grid[row * CRAFTING_GRID_SIZE + column] = Some(resolve_item(item)?);
+                    }
+                }
+
+                let Some((min_row, min_column, width, height)) = get_grid_bounds(&grid) else {
+                    return Err("pattern is empty".to_string());
+                };
+                let mut cells: Vec<Option<ItemKind>> = Default::default();
+                for row in min_row..min_row + height {
+                    for column in min_column..min_column + width {
+                        cells.push(grid[row * CRAFTING_GRID_SIZE + column].clone());
+                    }
+                }
+                (slug, RecipeKind::Shaped { width, height, cells }, result)
+            }
+            RecipeManifest::Shapeless {
+                slug,
+                ingredients,
+                result,
+            } => {
+                if ingredients.is_empty() || ingredients.len() > CRAFTING_GRID_SIZE * CRAFTING_GRID_SIZE {
+                    return Err(format!(
+                        "ingredients count must be from 1 to {}",
+                        CRAFTING_GRID_SIZE * CRAFTING_GRID_SIZE
+                    ));
+                }
+                let ingredients = ingredients.iter().map(resolve_item).collect::<Result<Vec<_>, _>>()?;
+                (slug, RecipeKind::Shapeless { ingredients }, result)
+            }
+            RecipeManifest::Smelting {
+                slug,
+                input,
+                result,
+                time,
+            } => {
+                if !time.is_finite() || *time <= 0.0 {
+                    return Err(format!("time must be greater than 0; got {}", time));
+                }
+                let kind = RecipeKind::Smelting {
+                    input: resolve_item(input)?,
+                    time: Duration::from_secs_f32(*time),
+                };
+                (slug, kind, result)
+            }
+        };
+
+        if result.amount == 0 {
+            return Err("result amount must be greater than 0".to_string());
+        }
+        Ok(Recipe {
+            slug: slug.clone(),
+            kind,
+            result: Item::create(resolve_item(&result.item)?).amount(result.amount),
+        })
+    }
+
+    pub fn is_crafting_block(&self, block_id: BlockIndexType) -> bool {
+        self.crafting_blocks.contains(&block_id)
+    }
+
+    /// Recipe crafted from the grid slots of the crafting inventory
+    pub fn find_crafting_recipe(&self, grid: &[Option<Item>]) -> Option<&Recipe> {
+        let kinds: Vec<Option<ItemKind>> = grid
+            .iter()
+            .map(|slot| slot.as_ref().map(|item| item.get_item_kind().clone()))
+            .collect();
+        self.recipes.iter().find(|recipe| recipe.matches_grid(&kinds))
+    }
+
+    /// Lookup for plugins; the server itself never processes smelting recipes
+    pub fn find_smelting_recipe(&self, item: &Item) -> Option<&Recipe> {
+        self.recipes.iter().find(|recipe| match recipe.get_kind() {
+            RecipeKind::Smelting { input, .. } => input == item.get_item_kind(),
+            _ => false,
+        })
+    }
+
+    pub fn get_recipes_count(&self) -> usize {
+        self.recipes.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RecipeManifest, RecipesRegistry, CRAFTING_GRID_SIZE};
+    use common::inventory::item::Item;
+
+    fn build_registry(manifests: &str) -> RecipesRegistry {
+        let manifests: Vec<RecipeManifest> = serde_yaml::from_str(manifests).unwrap();
+        let mut registry = RecipesRegistry::default();
+        for manifest in manifests {
+            registry.add_recipe(manifest).unwrap();
+        }
+        registry
+            .build(&Default::default(), |slug| {
+                ["stick", "coal", "torch", "plank"].contains(&slug)
+            })
+            .unwrap();
+        registry
+    }
+
+    fn grid(cells: [Option<&str>; CRAFTING_GRID_SIZE * CRAFTING_GRID_SIZE]) -> Vec<Option<Item>> {
+        cells.iter().map(|cell| cell.map(|slug| Item::create(slug))).collect()
+    }
+
+    #[test]
+    fn test_shaped_recipe() {
+        let registry = build_registry(
+            r#"
+- type: shaped
+  slug: torch
+  pattern: ["c", "s"]
+  key:
+    c: { item: coal }
+    s: { item: stick }
+  result: { item: torch, amount: 4 }
+"#,
+        );
+
+        let recipe = registry
+            .find_crafting_recipe(&grid([
+                None,
+                None,
+                Some("coal"),
+                None,
+                None,
+                Some("stick"),
+                None,
+                None,
+                None,
+            ]))
+            .expect("pattern may be placed anywhere in the grid");
+        assert_eq!(recipe.get_result().get_amount(), 4);
+
+        let upside_down = grid([Some("stick"), None, None, Some("coal"), None, None, None, None, None]);
+        assert!(registry.find_crafting_recipe(&upside_down).is_none());
+
+        let extra = grid([
+            Some("coal"),
+            None,
+            None,
+            Some("stick"),
+            None,
+            None,
+            Some("stick"),
+            None,
+            None,
+        ]);
+        assert!(registry.find_crafting_recipe(&extra).is_none());
+    }
+
+    #[test]
+    fn test_shapeless_recipe() {
+        let registry = build_registry(
+            r#"
+- type: shapeless
+  slug: sticks
+  ingredients: [{ item: plank }, { item: plank }]
+  result: { item: stick }
+"#,
+        );
+
+        let recipe = registry
+            .find_crafting_recipe(&grid([
+                Some("plank"),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some("plank"),
+            ]))
+            .unwrap();
+        assert_eq!(recipe.get_slug(), "sticks");
+        assert_eq!(recipe.get_result().get_amount(), 1);
+
+        let one = grid([Some("plank"), None, None, None, None, None, None, None, None]);
+        assert!(registry.find_crafting_recipe(&one).is_none());
+    }
+
+    #[test]
+    fn test_smelting_recipe() {
+        let registry = build_registry(
+            r#"
+- type: smelting
+  slug: charcoal
+  input: { item: plank }
+  result: { item: coal }
+  time: 10
+"#,
+        );
+
+        let recipe = registry.find_smelting_recipe(&Item::create("plank")).unwrap();
+        assert_eq!(recipe.get_slug(), "charcoal");
+        assert!(registry.find_smelting_recipe(&Item::create("coal")).is_none());
+
+        // Smelting recipes are not crafted in the grid
+        let plank = grid([Some("plank"), None, None, None, None, None, None, None, None]);
+        assert!(registry.find_crafting_recipe(&plank).is_none());
+    }
+
+    #[test]
+    fn test_unknown_item() {
+        let manifests: Vec<RecipeManifest> = serde_yaml::from_str(
+            r#"
+- type: smelting
+  slug: charcoal
+  input: { item: log }
+  result: { item: coal }
+  time: 10
+"#,
+        )
+        .unwrap();
+        let mut registry = RecipesRegistry::default();
+        for manifest in manifests {
+            registry.add_recipe(manifest).unwrap();
+        }
+        assert!(registry.build(&Default::default(), |slug| slug == "coal").is_err());
+    }
+}
@@ -8,11 +8,13 @@ use log::LevelFilter;
 #[derive(Parser, Debug, Clone)]
 #[command(author, version, about, long_about = None)]
 pub struct MainCommand {
-    #[arg(short, long, default_value_t = String::from("0.0.0.0"))]
-    pub ip: String,
+    /// Overrides `ip` from server.yml
+    #[arg(short, long)]
+    pub ip: Option<String>,
 
-    #[arg(short, long, default_value_t = String::from("19132"))]
-    pub port: String,
+    /// Overrides `port` from server.yml
+    #[arg(short, long)]
+    pub port: Option<u16>,
 
     #[arg(long, default_value_t = String::from("info"))]
     pub logs: String,
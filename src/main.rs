@@ -2,7 +2,6 @@ use bevy::{prelude::TaskPoolPlugin, time::TimePlugin};
 use bevy_app::{App, ScheduleRunnerPlugin};
 use common::utils::print_logo;
 use items_manager::ItemsManagerPlugin;
-use std::time::Duration;
 use tracing_subscriber::layer::SubscriberExt;
 
 use crate::console::ConsolePlugin;
@@ -16,6 +15,7 @@ use debug::DebugPlugin;
 use inventory::InventoryPlugin;
 use launch_settings::{get_log_level, LaunchSettings};
 use plugins::PluginApp;
+use server_config::ServerConfig;
 use storage::StoragePlugin;
 use worlds::WorldsHandlerPlugin;
 
@@ -33,16 +33,13 @@ mod logger;
 mod network;
 mod plugins;
 mod runtime_plugin;
+pub mod server_config;
 mod storage;
 mod utils;
 mod worlds;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-pub const CHUNKS_DISTANCE: u16 = 12;
-pub const CHUNKS_DESPAWN_TIMER: Duration = Duration::from_secs(5);
-pub static SEND_CHUNK_QUEUE_LIMIT: usize = 64;
-
 fn main() {
     log::set_logger(&CONSOLE_LOGGER).unwrap();
 
@@ -71,12 +68,22 @@ fn main() {
     log::debug!(target: "main", "Log level using: {}", log_level);
     log::info!(target: "main", "In Its Brilliance Server version &d{}", VERSION);
 
+    let server_config = match ServerConfig::load(&server_settings) {
+        Ok(c) => c,
+        Err(e) => {
+            log::error!(target: "settings", "&cServer config loading error:");
+            log::error!(target: "settings", "{}", e);
+            std::process::exit(1);
+        }
+    };
+
     let mut app = App::new();
     app.insert_resource(server_settings);
+    app.insert_resource(server_config.clone());
     app.add_plugins((
         TimePlugin::default(),
         TaskPoolPlugin::default(),
-        ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(1.0 / common::TARGET_TPS)),
+        ScheduleRunnerPlugin::run_loop(server_config.get_tick_duration()),
         RuntimePlugin::default(),
         StoragePlugin::default(),
         InventoryPlugin::default(),
@@ -2,8 +2,8 @@ use crate::{
     clients::client::{Client, WorldEntity},
     clients::clients_container::SharedClientsContainer,
     entities::entity::Position,
+    server_config::ServerConfig,
     worlds::{world_manager::WorldManager, worlds_manager::SharedWorldsManager},
-    CHUNKS_DISTANCE, SEND_CHUNK_QUEUE_LIMIT,
 };
 use ahash::AHashSet;
 use bevy_ecs::{resource::Resource, system::Res};
@@ -43,6 +43,7 @@ pub fn send_chunks(
     clients: Res<SharedClientsContainer>,
     network_container: Res<NetworkContainer>,
     compress_queue: Res<ChunkCompressQueue>,
+    server_config: Res<ServerConfig>,
 ) {
     #[cfg(feature = "trace")]
     let _span = bevy_utils::tracing::info_span!("chunks_sender.send_chunks").entered();
@@ -57,7 +58,7 @@ pub fn send_chunks(
         }
 
         // skip if send queue is full
-        if network_client.is_queue_limit() {
+        if network_client.is_queue_limit(server_config.get_send_chunk_queue_limit()) {
             continue;
         }
 
@@ -89,6 +90,7 @@ pub fn send_chunks(
             &network_client,
             player_watching_chunks,
             &compress_queue.sender,
+            &server_config,
         );
     }
 }
@@ -104,6 +106,7 @@ fn send_chunks_to_client(
     network_client: &Client,
     player_watching_chunks: &Vec<ChunkPosition>,
     sender: &flume::Sender<PreparedChunk>,
+    server_config: &ServerConfig,
 ) {
     let ecs = world_manager.get_ecs();
     let entity_ref = ecs.get_entity(world_entity.get_entity()).unwrap();
@@ -119,11 +122,12 @@ fn send_chunks_to_client(
     let (mut already_sent, mut queue_count) = network_client.snapshot_already_sended();
 
     // iterate chunks in spiral order around the player
-    let iter = SpiralIterator::new(center.x as i64, center.z as i64, CHUNKS_DISTANCE as i64);
+    let chunks_distance = network_client.get_view_distance();
+    let iter = SpiralIterator::new(center.x as i64, center.z as i64, chunks_distance as i64);
 
     for (x, z) in iter {
         // stop sending if queue limit is reached
-        if queue_count >= SEND_CHUNK_QUEUE_LIMIT {
+        if queue_count >= server_config.get_send_chunk_queue_limit() {
             return;
         }
 
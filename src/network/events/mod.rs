@@ -1,5 +1,8 @@
 // Events from the network
 
+pub mod on_auth_response;
+pub mod on_block_action;
+pub mod on_block_use;
 pub mod on_client_script_event;
 pub mod on_connection;
 pub mod on_connection_info;
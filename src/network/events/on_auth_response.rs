@@ -0,0 +1,62 @@
+use bevy_ecs::message::Message;
+use bevy_ecs::system::{Res, ResMut};
+use common::utils::events::{EventInterface, EventReader};
+use std::time::Instant;
+
+use crate::{
+    clients::{
+        auth::{AuthCredentials, AuthHandshakes, AUTH_FAILED_MESSAGE},
+        bans::normalize_ip,
+        client::Client,
+    },
+    network::{
+        events::on_connection_info::PlayerConnectionInfoEvent,
+        server::{NetworkEventChannel, NetworkEventListener},
+    },
+};
+
+/// Answer of the client to the `AuthChallenge`
+#[derive(Message)]
+pub struct PlayerAuthResponseEvent {
+    client: Client,
+    proof: String,
+}
+
+impl PlayerAuthResponseEvent {
+    pub fn new(client: Client, proof: String) -> Self {
+        Self { client, proof }
+    }
+}
+
+pub fn on_auth_response(
+    auth_response_events: Res<NetworkEventListener<PlayerAuthResponseEvent>>,
+    connection_info_channel: Res<NetworkEventChannel<PlayerConnectionInfoEvent>>,
+    credentials: Res<AuthCredentials>,
+    mut handshakes: ResMut<AuthHandshakes>,
+) {
+    let _s = crate::span!("events.on_auth_response");
+    for event in auth_response_events.0.iter_events() {
+        let Some(mut pending) = handshakes.take(event.client.get_client_id()) else {
+            log::debug!(target: "network", "Unexpected auth response from {}", event.client);
+            continue;
+        };
+
+        if !credentials.verify(&pending.event.login, &pending.nonce, &event.proof) {
+            let ip = normalize_ip(event.client.get_client_ip());
+            handshakes.record_failure(&ip, Instant::now());
+            log::info!(
+                target: "network",
+                "&c✱ {}&r failed authentication &8ip: &7{} &8registered: &7{}",
+                pending.event.login,
+                ip,
+                credentials.has_credential(&pending.event.login)
+            );
+            event.client.disconnect(Some(AUTH_FAILED_MESSAGE.to_string()));
+            continue;
+        }
+
+        // Connection continues in on_connection_info
+        pending.event.set_authenticated();
+        connection_info_channel.0.emit_event(pending.event);
+    }
+}
@@ -0,0 +1,332 @@
+use bevy_ecs::message::Message;
+use bevy_ecs::system::Res;
+use common::chunks::{
+    block_position::{BlockPosition, BlockPositionTrait},
+    chunk_data::BlockDataInfo,
+};
+use common::inventory::item::{Item, ItemKind};
+use common::utils::events::EventReader;
+use network::messages::{BlockAction, InventorySlotChange, NetworkMessageType, ServerMessages};
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+use crate::{
+    clients::client::Client,
+    entities::entity::Position,
+    items_manager::items_manager::SharedItemsManager,
+    network::{
+        events::on_inventory_action::InventoryTarget, server::NetworkEventListener,
+        sync_inventory::send_inventory_changes_to_client, sync_world_change::sync_world_block_change,
+    },
+    plugins::server_settings::ServerSettings,
+    server_config::ServerConfig,
+    worlds::{chunks::chunk_column::ChunkColumn, world_manager::WorldManager, worlds_manager::SharedWorldsManager},
+};
+
+/// Half of the player collider width
+const PLAYER_COLLIDER_RADIUS: f32 = 0.3;
+const PLAYER_COLLIDER_HEIGHT: f32 = 1.8;
+
+/// Eyes height over the player position; the reach is measured from there
+const PLAYER_EYES_HEIGHT: f32 = 1.6;
+
+#[derive(Message)]
+pub struct BlockActionEvent {
+    client: Client,
+    action: BlockAction,
+}
+
+impl BlockActionEvent {
+    pub fn new(client: Client, action: BlockAction) -> Self {
+        Self { client, action }
+    }
+
+    pub fn get_client(&self) -> &Client {
+        &self.client
+    }
+
+    pub fn get_action(&self) -> &BlockAction {
+        &self.action
+    }
+}
+
+pub fn on_block_action(
+    events: Res<NetworkEventListener<BlockActionEvent>>,
+    worlds_manager: Res<SharedWorldsManager>,
+    items_manager: Res<SharedItemsManager>,
+    server_settings: Res<ServerSettings>,
+    server_config: Res<ServerConfig>,
+) {
+    let _s = crate::span!("events.on_block_action");
+    for event in events.0.iter_events() {
+        let client = event.get_client();
+        let Some(world_entity) = client.get_world_entity() else {
+            continue;
+        };
+
+        let worlds_manager = worlds_manager.read();
+        let Some(world_manager) = worlds_manager.get_world_manager(world_entity.get_world_slug()) else {
+            continue;
+        };
+        let Some((player_position, _rotation)) = world_manager.get_entity_location(world_entity.get_entity()) else {
+            continue;
+        };
+
+        let position = match event.get_action() {
+            BlockAction::Break { position } => position,
+            BlockAction::Place { position, .. } => position,
+        };
+        let result = match get_target_chunk(
+            &world_manager,
+            &player_position,
+            position,
+            server_config.get_block_reach_distance(),
+        ) {
+            Ok(chunk_column) => match event.get_action().clone() {
+                BlockAction::Break { position } => {
+                    break_block(&world_manager, client, &items_manager, &chunk_column, position)
+                }
+                BlockAction::Place {
+                    position,
+                    slot,
+                    block_info,
+                } => place_block(
+                    &world_manager,
+                    client,
+                    &items_manager,
+                    &server_settings,
+                    &chunk_column,
+                    &player_position,
+                    position,
+                    slot,
+                    block_info,
+                ),
+            },
+            Err(e) => Err(e),
+        };
+
+        if let Err(e) = result {
+            client.send_console_message(format!("&4Block action rejected: &c{}", e));
+            resend_block(&world_manager, client, position);
+        }
+    }
+}
+
+/// Checks that the block can be reached by the player and returns its loaded chunk
+pub(crate) fn get_target_chunk(
+    world_manager: &WorldManager,
+    player_position: &Position,
+    position: &BlockPosition,
+    reach_distance: f32,
+) -> Result<Arc<RwLock<ChunkColumn>>, String> {
+    if !is_in_reach(player_position, position, reach_distance) {
+        return Err(format!("block {:?} is out of reach", position));
+    }
+
+    let chunk_position = position.get_chunk_position();
+    let Some(chunk_column) = world_manager.get_chunks_map().get_chunk_column_arc(&chunk_position) else {
+        return Err(format!("chunk {} is not loaded", chunk_position));
+    };
+    {
+        let column = chunk_column.read();
+        if !column.is_loaded() || column.is_placeholder() {
+            return Err(format!("chunk {} is not loaded", chunk_position));
+        }
+    }
+    Ok(chunk_column)
+}
+
+fn break_block(
+    world_manager: &WorldManager,
+    client: &Client,
+    items_manager: &SharedItemsManager,
+    chunk_column: &RwLock<ChunkColumn>,
+    position: BlockPosition,
+) -> Result<(), String> {
+    let block_info = {
+        let chunk_column = chunk_column.read();
+        let chunk_storage = chunk_column.get_chunk_storage();
+        let Some(block_info) = chunk_storage.get_chunk_data().get_block_info(&position) else {
+            return Err("there is no block".to_string());
+        };
+
+        let (section, chunk_block) = position.get_block_position();
+        let has_inventory = chunk_storage.get_inventories().iter().any(|block_inventory| {
+            block_inventory.get_section() == section && *block_inventory.get_position() == chunk_block
+        });
+        if has_inventory {
+            return Err("block with inventory can't be broken".to_string());
+        }
+        block_info
+    };
+
+    // The broken block goes to the player inventory; the block is restored if it doesn't fit
+    world_manager.get_chunks_map().edit_block(position.clone(), None)?;
+    let item = Item::create(block_info.get_id());
+    let mut changes: Vec<InventorySlotChange> = Default::default();
+    let added = client.with_player_data_mut(|player_data| {
+        let max_stack_size = items_manager.read().get_max_stack_size(&item);
+        player_data
+            .get_inventory_mut()
+            .add_item(item.clone(), max_stack_size, |slot, updated_item| {
+                changes.push(InventorySlotChange {
+                    slot,
+                    item: updated_item.map(|item| items_manager.read().to_client_item(item)),
+                });
+            })
+    });
+    let Some(Ok(())) = added else {
+        world_manager
+            .get_chunks_map()
+            .edit_block(position.clone(), Some(block_info))?;
+        return Err("inventory is full".to_string());
+    };
+
+    send_inventory_changes_to_client(client, &InventoryTarget::Client(client.get_client_id()), changes);
+    sync_world_block_change(world_manager, position, None);
+    Ok(())
+}
+
+fn place_block(
+    world_manager: &WorldManager,
+    client: &Client,
+    items_manager: &SharedItemsManager,
+    server_settings: &ServerSettings,
+    chunk_column: &RwLock<ChunkColumn>,
+    player_position: &Position,
+    position: BlockPosition,
+    slot: u16,
+    block_info: BlockDataInfo,
+) -> Result<(), String> {
+    let Some(block_type) = server_settings.get_block_type_by_id(block_info.get_id()) else {
+        return Err(format!("block id {} is not found", block_info.get_id()));
+    };
+    if chunk_column
+        .read()
+        .get_chunk_storage()
+        .get_chunk_data()
+        .get_block_info(&position)
+        .is_some()
+    {
+        return Err("place is occupied".to_string());
+    }
+    if !block_type.get_collider_type().is_sensor() && intersects_player(player_position, &position) {
+        return Err("block intersects the player".to_string());
+    }
+
+    // One block is taken from the slot; the block is removed again if the slot doesn't have it
+    world_manager
+        .get_chunks_map()
+        .edit_block(position.clone(), Some(block_info.clone()))?;
+    let taken = client.with_player_data_mut(|player_data| {
+        let inventory = player_data.get_inventory_mut();
+        let is_same_block = match inventory.get_slot(slot as usize) {
+            Some(item) => matches!(item.get_item_kind(), ItemKind::Block(id) if *id == block_info.get_id()),
+            None => false,
+        };
+        if !is_same_block {
+            return None;
+        }
+
+        let source = inventory.take_slot(slot as usize)?;
+        let remaining = match source.get_amount() > 1 {
+            true => Some(source.clone().amount(source.get_amount() - 1)),
+            false => None,
+        };
+        inventory.set_slot_option(slot as usize, remaining.clone());
+        Some(InventorySlotChange {
+            slot: slot as usize,
+            item: remaining.map(|item| items_manager.read().to_client_item(&item)),
+        })
+    });
+    let Some(Some(change)) = taken else {
+        world_manager.get_chunks_map().edit_block(position.clone(), None)?;
+        return Err(format!("slot {} doesn't contain the block", slot));
+    };
+
+    send_inventory_changes_to_client(client, &InventoryTarget::Client(client.get_client_id()), vec![change]);
+    sync_world_block_change(world_manager, position, Some(block_info));
+    Ok(())
+}
+
+/// Reverts the client prediction of the rejected action
+fn resend_block(world_manager: &WorldManager, client: &Client, position: &BlockPosition) {
+    let Some(chunk_column) = world_manager
+        .get_chunks_map()
+        .get_chunk_column_arc(&position.get_chunk_position())
+    else {
+        return;
+    };
+    let chunk_column = chunk_column.read();
+    if !chunk_column.is_loaded() {
+        return;
+    }
+    let msg = ServerMessages::EditBlock {
+        world_slug: world_manager.get_slug().clone(),
+        position: position.clone(),
+        new_block_info: chunk_column
+            .get_chunk_storage()
+            .get_chunk_data()
+            .get_block_info(position),
+    };
+    client.send_message(NetworkMessageType::WorldInfo, &msg);
+}
+
+fn is_in_reach(player_position: &Position, position: &BlockPosition, reach_distance: f32) -> bool {
+    let eyes = Position::new(
+        player_position.get_x(),
+        player_position.get_y() + PLAYER_EYES_HEIGHT,
+        player_position.get_z(),
+    );
+    let block_center = Position::new(
+        position.x as f32 + 0.5,
+        position.y as f32 + 0.5,
+        position.z as f32 + 0.5,
+    );
+    eyes.distance(&block_center) <= reach_distance
+}
+
+fn intersects_player(player_position: &Position, position: &BlockPosition) -> bool {
+    let overlaps = |min: f32, max: f32, block: i64| min < (block + 1) as f32 && max > block as f32;
+    overlaps(
+        player_position.get_x() - PLAYER_COLLIDER_RADIUS,
+        player_position.get_x() + PLAYER_COLLIDER_RADIUS,
+        position.x,
+    ) && overlaps(
+        player_position.get_y(),
+        player_position.get_y() + PLAYER_COLLIDER_HEIGHT,
+        position.y,
+    ) && overlaps(
+        player_position.get_z() - PLAYER_COLLIDER_RADIUS,
+        player_position.get_z() + PLAYER_COLLIDER_RADIUS,
+        position.z,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{intersects_player, is_in_reach};
+    use crate::entities::entity::Position;
+    use common::chunks::block_position::BlockPosition;
+
+    #[test]
+    fn test_reach() {
+        let player = Position::new(0.5, 10.0, 0.5);
+        assert!(is_in_reach(&player, &BlockPosition::new(0, 9, 0), 6.0));
+        assert!(is_in_reach(&player, &BlockPosition::new(4, 11, 0), 6.0));
+        assert!(!is_in_reach(&player, &BlockPosition::new(7, 11, 0), 6.0));
+    }
+
+    #[test]
+    fn test_intersects_player() {
+        let player = Position::new(0.5, 10.0, 0.5);
+        assert!(intersects_player(&player, &BlockPosition::new(0, 10, 0)));
+        assert!(intersects_player(&player, &BlockPosition::new(0, 11, 0)));
+        assert!(!intersects_player(&player, &BlockPosition::new(0, 9, 0)));
+        assert!(!intersects_player(&player, &BlockPosition::new(1, 10, 0)));
+
+        // Standing on the edge of two blocks
+        let player = Position::new(1.1, 10.0, 0.5);
+        assert!(intersects_player(&player, &BlockPosition::new(0, 10, 0)));
+    }
+}
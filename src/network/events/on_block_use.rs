@@ -0,0 +1,203 @@
+use bevy_ecs::message::Message;
+use bevy_ecs::system::Res;
+use common::chunks::block_position::{BlockPosition, BlockPositionTrait};
+use common::plugin_api::events::block_interact::BlockInteractEvent;
+use common::utils::events::EventReader;
+
+use crate::{
+    clients::{client::Client, clients_container::SharedClientsContainer},
+    inventory::{
+        commands::{get_or_create_inventory, open_inventory},
+        SharedInventoryManager,
+    },
+    items_manager::{items_manager::SharedItemsManager, recipes::CRAFTING_INVENTORY_SLOTS},
+    network::{events::on_block_action::get_target_chunk, server::NetworkEventListener},
+    plugins::plugins_manager::PluginsManager,
+    server_config::ServerConfig,
+    worlds::worlds_manager::SharedWorldsManager,
+};
+
+#[derive(Message)]
+pub struct BlockUseEvent {
+    client: Client,
+    position: BlockPosition,
+}
+
+impl BlockUseEvent {
+    pub fn new(client: Client, position: BlockPosition) -> Self {
+        Self { client, position }
+    }
+
+    pub fn get_client(&self) -> &Client {
+        &self.client
+    }
+
+    pub fn get_position(&self) -> &BlockPosition {
+        &self.position
+    }
+}
+
+/// Default server action of the used block
+#[derive(Debug, PartialEq)]
+enum BlockUse {
+    OpenInventory { inventory_id: u64, crafting: bool },
+
+    // Crafting block is used for the first time
+    CreateCrafting,
+}
+
+/// Plugins may cancel the interaction, then the block does nothing
+fn get_block_use(inventory_id: Option<u64>, crafting: bool, cancelled: bool) -> Option<BlockUse> {
+    if cancelled {
+        return None;
+    }
+    match inventory_id {
+        Some(inventory_id) => Some(BlockUse::OpenInventory { inventory_id, crafting }),
+        None if crafting => Some(BlockUse::CreateCrafting),
+        None => None,
+    }
+}
+
+/// Passes the interaction to plugins and opens the inventory of the used block unless it's cancelled
+///
+/// Crafting blocks get their crafting inventory on the first use.
+pub fn on_block_use(
+    events: Res<NetworkEventListener<BlockUseEvent>>,
+    worlds_manager: Res<SharedWorldsManager>,
+    clients: Res<SharedClientsContainer>,
+    inventory_manager: Res<SharedInventoryManager>,
+    items_manager: Res<SharedItemsManager>,
+    plugins_manager: Res<PluginsManager>,
+    server_config: Res<ServerConfig>,
+) {
+    let _s = crate::span!("events.on_block_use");
+    for event in events.0.iter_events() {
+        let client = event.get_client();
+        let position = event.get_position();
+
+        // World lock must be released before the inventory is opened
+        let (world_slug, block_id, inventory_id, crafting) = {
+            let Some(world_entity) = client.get_world_entity() else {
+                continue;
+            };
+            let worlds_manager = worlds_manager.read();
+            let Some(world_manager) = worlds_manager.get_world_manager(world_entity.get_world_slug()) else {
+                continue;
+            };
+            let Some((player_position, _rotation)) = world_manager.get_entity_location(world_entity.get_entity())
+            else {
+                continue;
+            };
+
+            let chunk_column = match get_target_chunk(
+                &world_manager,
+                &player_position,
+                position,
+                server_config.get_block_reach_distance(),
+            ) {
+                Ok(c) => c,
+                Err(e) => {
+                    log::debug!(target: "network", "Block use by {} is rejected: {}", client.get_client_ip(), e);
+                    continue;
+                }
+            };
+            let chunk_column = chunk_column.read();
+            let chunk_storage = chunk_column.get_chunk_storage();
+            let Some(block_info) = chunk_storage.get_chunk_data().get_block_info(position) else {
+                continue;
+            };
+
+            let crafting = items_manager
+                .read()
+                .get_recipes()
+                .is_crafting_block(block_info.get_id());
+            let (section, chunk_block) = position.get_block_position();
+            let inventory_id = chunk_storage
+                .get_inventories()
+                .iter()
+                .find(|block_inventory| {
+                    block_inventory.get_section() == section && *block_inventory.get_position() == chunk_block
+                })
+                .map(|block_inventory| block_inventory.get_inventory().get_id());
+            (
+                world_manager.get_slug().clone(),
+                block_info.get_id(),
+                inventory_id,
+                crafting,
+            )
+        };
+
+        let plugin_event =
+            BlockInteractEvent::create(client.get_client_id(), world_slug.clone(), position.clone(), block_id);
+        let cancelled = plugins_manager.dispatch_block_interact_event(&plugin_event);
+        if cancelled {
+            log::debug!(target: "scripts", "Block interaction at {:?} is cancelled", position);
+        }
+
+        let Some(block_use) = get_block_use(inventory_id, crafting, cancelled) else {
+            continue;
+        };
+        let inventory_id = match block_use {
+            BlockUse::OpenInventory { inventory_id, crafting } => {
+                if crafting {
+                    inventory_manager
+                        .write()
+                        .state_mut()
+                        .mark_crafting_inventory(inventory_id);
+                }
+                inventory_id
+            }
+            BlockUse::CreateCrafting => {
+                match get_or_create_inventory(
+                    world_slug,
+                    position.clone(),
+                    CRAFTING_INVENTORY_SLOTS,
+                    &worlds_manager.clone_inner(),
+                    &inventory_manager.clone_inner(),
+                ) {
+                    Ok(inventory_id) => {
+                        inventory_manager
+                            .write()
+                            .state_mut()
+                            .mark_crafting_inventory(inventory_id);
+                        inventory_id
+                    }
+                    Err(e) => {
+                        log::error!(target: "inventory", "Crafting inventory at {:?} error: {}", position, e);
+                        continue;
+                    }
+                }
+            }
+        };
+
+        open_inventory(
+            client.get_client_id(),
+            inventory_id,
+            &clients.clone_inner(),
+            &inventory_manager.clone_inner(),
+            &worlds_manager.clone_inner(),
+            &items_manager.clone_inner(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_block_use, BlockUse};
+
+    #[test]
+    fn test_cancelled_block_use() {
+        let open = BlockUse::OpenInventory {
+            inventory_id: 7,
+            crafting: false,
+        };
+        assert_eq!(get_block_use(Some(7), false, false), Some(open));
+        assert_eq!(get_block_use(None, true, false), Some(BlockUse::CreateCrafting));
+        assert_eq!(get_block_use(None, false, false), None);
+
+        // Cancelled interaction skips the default action
+        assert_eq!(get_block_use(Some(7), false, true), None);
+        assert_eq!(get_block_use(Some(7), true, true), None);
+        assert_eq!(get_block_use(None, true, true), None);
+    }
+}
@@ -1,18 +1,24 @@
 use bevy_ecs::message::Message;
-use bevy_ecs::system::Res;
+use bevy_ecs::system::{Res, ResMut};
 use common::inventory::inventory::InventoryType;
 use common::utils::events::{EventInterface, EventReader};
 use network::messages::{NetworkMessageType, ServerMessages};
+use std::time::Instant;
 
+use crate::clients::auth::{AuthCredentials, AuthHandshakes, AuthMode};
+use crate::clients::bans::{normalize_ip, BanList};
 use crate::clients::client::Client;
 use crate::clients::client::ClientInfo;
+use crate::clients::client_version::ClientCompatibility;
 use crate::clients::clients_container::SharedClientsContainer;
+use crate::clients::whitelist::Whitelist;
 use crate::items_manager::items_manager::SharedItemsManager;
 use crate::network::events::on_media_loaded::PlayerMediaLoadedEvent;
 use crate::network::server::{NetworkEventChannel, NetworkEventListener};
 use crate::network::sync_inventory::send_inventory_start_to_client;
 use crate::plugins::plugins_manager::PluginsManager;
 use crate::runtime_plugin::RuntimePlugin;
+use crate::server_config::ServerConfig;
 use crate::storage::storage_manager::SharedStorageManager;
 
 #[derive(Message)]
@@ -22,18 +28,39 @@ pub struct PlayerConnectionInfoEvent {
     pub version: String,
     pub architecture: String,
     pub rendering_device: String,
+    pub view_distance: Option<u16>,
+
+    // The login is proven by the auth handshake
+    authenticated: bool,
 }
 
 impl PlayerConnectionInfoEvent {
-    pub fn new(client: Client, login: String, version: String, architecture: String, rendering_device: String) -> Self {
+    pub fn new(
+        client: Client,
+        login: String,
+        version: String,
+        architecture: String,
+        rendering_device: String,
+        view_distance: Option<u16>,
+    ) -> Self {
         Self {
             client,
             login,
             version,
             architecture,
             rendering_device,
+            view_distance,
+            authenticated: false,
         }
     }
+
+    pub fn get_client(&self) -> &Client {
+        &self.client
+    }
+
+    pub fn set_authenticated(&mut self) {
+        self.authenticated = true;
+    }
 }
 
 pub fn on_connection_info(
@@ -43,6 +70,11 @@ pub fn on_connection_info(
     clients: Res<SharedClientsContainer>,
     items_manager: Res<SharedItemsManager>,
     storage: Res<SharedStorageManager>,
+    bans: Res<BanList>,
+    whitelist: Res<Whitelist>,
+    server_config: Res<ServerConfig>,
+    credentials: Res<AuthCredentials>,
+    mut handshakes: ResMut<AuthHandshakes>,
 ) {
     let _s = crate::span!("events.on_connection_info");
     if RuntimePlugin::is_stopped() {
@@ -50,6 +82,33 @@ pub fn on_connection_info(
     }
 
     for event in connection_info_events.0.iter_events() {
+        let ip = event.client.get_client_ip();
+        let compatibility = ClientCompatibility::check(&event.version);
+        if !compatibility.is_compatible() {
+            log::info!(
+                target: "network",
+                "&c✱ {}&r has {} client version &e{} &8ip: &7{}",
+                event.login,
+                compatibility,
+                event.version,
+                ip
+            );
+            event.client.disconnect(Some(compatibility.get_message(&event.version)));
+            continue;
+        }
+        if let Some(ban) = bans.get_ban(&event.login, ip, chrono::Utc::now().timestamp_millis()) {
+            log::info!(target: "network", "&c✱ {}&r is banned &8ip: &7{}", event.login, ip);
+            event.client.disconnect(Some(ban.get_message()));
+            continue;
+        }
+        if !whitelist.is_allowed(&event.login) {
+            log::info!(target: "network", "&c✱ {}&r is not whitelisted &8ip: &7{}", event.login, ip);
+            event
+                .client
+                .disconnect(Some("You are not whitelisted on this server".to_string()));
+            continue;
+        }
+
         let clients_guard = clients.read();
         for (client_id, client) in clients_guard.iter() {
             if *client_id != event.client.get_client_id() {
@@ -64,8 +123,33 @@ pub fn on_connection_info(
             }
         }
 
+        if server_config.get_auth_mode() == AuthMode::Password && !event.authenticated {
+            let ip = normalize_ip(event.client.get_client_ip());
+            let now = Instant::now();
+            if handshakes.is_rate_limited(&ip, now) {
+                log::info!(target: "network", "&c✱ {}&r is rate limited &8ip: &7{}", event.login, ip);
+                let message = "Too many failed login attempts, try again later";
+                event.client.disconnect(Some(message.to_string()));
+                continue;
+            }
+            // Unknown logins get a challenge too and fail in on_auth_response like wrong passwords
+            let (salt, iterations) = credentials.get_salt(&event.login);
+
+            // ClientInfo is accepted only after the answer in on_auth_response
+            let client = event.client.clone();
+            let nonce = handshakes.start(client.get_client_id(), event);
+            let challenge = ServerMessages::AuthChallenge {
+                salt,
+                iterations,
+                nonce,
+            };
+            client.send_message(NetworkMessageType::ReliableOrdered, &challenge);
+            continue;
+        }
+
         let client_info = ClientInfo::new(&event);
         event.client.set_client_info(client_info.clone());
+        event.client.set_requested_view_distance(event.view_distance);
         let storage_guard = storage.read();
         if let Err(e) = event.client.read_player_data(&storage_guard.read_server_storage()) {
             log::error!(target: "storage", "&cFailed to load player data for &4{}&c: {}", client_info.get_login(), e);
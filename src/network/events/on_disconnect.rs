@@ -1,8 +1,11 @@
-use bevy_ecs::{message::Message, system::Res};
+use bevy_ecs::{
+    message::Message,
+    system::{Res, ResMut},
+};
 use common::utils::events::EventReader;
 
 use crate::{
-    clients::{client::Client, clients_container::SharedClientsContainer},
+    clients::{auth::AuthHandshakes, client::Client, clients_container::SharedClientsContainer},
     entities::skin::EntitySkinComponent,
     inventory::SharedInventoryManager,
     network::{server::NetworkEventListener, sync_entities::sync_entity_despawn},
@@ -28,9 +31,12 @@ pub fn on_disconnect(
     inventory_manager: Res<SharedInventoryManager>,
     storage: Res<SharedStorageManager>,
     worlds_manager: Res<SharedWorldsManager>,
+    mut handshakes: ResMut<AuthHandshakes>,
 ) {
     let _s = crate::span!("events.on_disconnect");
     for event in disconnection_events.0.iter_events() {
+        handshakes.take(event.client.get_client_id());
+
         if let Some(i) = event.client.get_client_info() {
             log::info!(
                 target: "network",
@@ -40,6 +46,8 @@ pub fn on_disconnect(
             );
         }
 
+        event.client.store_location(&worlds_manager.read());
+
         let storage_guard = storage.read();
         if let Err(e) = event.client.save_player_data(&storage_guard.read_server_storage()) {
             log::error!(target: "storage", "&cFailed to save player data for client &4{}&c: {}", event.client.get_client_id(), e);
@@ -404,6 +404,7 @@ mod tests {
                 "test".to_string(),
                 "test".to_string(),
                 "test".to_string(),
+                None,
             ),
         ));
 
@@ -10,6 +10,7 @@ use crate::{
         plugins_manager::PluginsManager, resources_archive::ARCHIVE_CHUNK_SIZE, server_settings::ServerSettings,
     },
     runtime_plugin::RuntimePlugin,
+    server_config::ServerConfig,
 };
 
 #[derive(Message)]
@@ -31,6 +32,7 @@ pub fn on_media_loaded(
     events: Res<NetworkEventListener<PlayerMediaLoadedEvent>>,
     server_settings: Res<ServerSettings>,
     plugins_manager: Res<PluginsManager>,
+    server_config: Res<ServerConfig>,
 ) {
     let _s = crate::span!("events.on_media_loaded");
     if RuntimePlugin::is_stopped() {
@@ -60,10 +62,18 @@ pub fn on_media_loaded(
             None => (),
         }
 
+        // The world is not known yet; if the player spawns in a world
+        // with a lower limit, SpawnPlayer sends the corrected value
+        let view_distance = server_config.get_view_distance(
+            server_config.get_default_world(),
+            event.client.get_requested_view_distance(),
+        );
+        event.client.set_view_distance(view_distance);
+
         // Send server settings
         event.client.send_message(
             NetworkMessageType::ReliableOrdered,
-            &server_settings.get_network_settings(),
+            &server_settings.get_network_settings(view_distance),
         );
     }
 }
@@ -11,8 +11,10 @@ use network::entities::AnimationState;
 use crate::clients::client::{Client, WorldEntity};
 use crate::entities::entity::Position;
 use crate::entities::entity::Rotation;
+use crate::entities::health::apply_movement_damage;
 use crate::network::server::NetworkEventListener;
 use crate::network::sync_players::sync_player_move;
+use crate::plugins::server_settings::ServerSettings;
 use crate::worlds::world_manager::WorldManager;
 use crate::worlds::worlds_manager::SharedWorldsManager;
 
@@ -38,6 +40,7 @@ impl PlayerMoveEvent {
 pub fn on_player_move(
     player_move_events: Res<NetworkEventListener<PlayerMoveEvent>>,
     worlds_manager: Res<SharedWorldsManager>,
+    server_settings: Res<ServerSettings>,
     time: Res<Time>,
 ) {
     let _s = crate::span!("events.on_player_move");
@@ -83,6 +86,14 @@ pub fn on_player_move(
             event.rotation,
             event.animation_state,
             server_time,
+            event.client.get_view_distance(),
+        );
+        apply_movement_damage(
+            &mut *world_manager,
+            &event.client,
+            world_entity.get_entity(),
+            &event.position,
+            &server_settings,
         );
     }
 }
@@ -95,8 +106,9 @@ pub fn move_player(
     rotation: Rotation,
     animation_state: AnimationState,
     server_time: f64,
+    chunks_distance: u16,
 ) {
-    let chunks_changed = world_manager.player_move(&world_entity, position, rotation);
+    let chunks_changed = world_manager.player_move(&world_entity, position, rotation, chunks_distance);
 
     if let Some(change) = chunks_changed.as_ref() {
         let ecs = world_manager.get_ecs();
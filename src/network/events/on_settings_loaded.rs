@@ -1,7 +1,9 @@
 use crate::clients::client::Client;
+use crate::entities::entity::{Position, Rotation};
 use crate::network::server::NetworkEventListener;
+use crate::server_config::ServerConfig;
 use crate::worlds::commands::SpawnPlayer;
-use crate::worlds::worlds_manager::SharedWorldsManager;
+use crate::worlds::worlds_manager::{SharedWorldsManager, WorldsManager};
 use bevy::prelude::{Commands, Res};
 use bevy_ecs::message::Message;
 use common::utils::events::EventReader;
@@ -21,15 +23,23 @@ pub fn on_settings_loaded(
     mut commands: Commands,
     events: Res<NetworkEventListener<PlayerSettingsLoadedEvent>>,
     worlds_manager: Res<SharedWorldsManager>,
+    server_config: Res<ServerConfig>,
 ) {
     let _s = crate::span!("events.on_settings_loaded");
     for event in events.0.iter_events() {
-        let default_world = "default".to_string();
-        if !worlds_manager.read().has_world_with_slug(&default_world) {
+        let worlds_manager = worlds_manager.read();
+
+        let (world_slug, location) =
+            get_spawn_location(&event.client, &worlds_manager, server_config.get_default_world());
+        if !worlds_manager.has_world_with_slug(&world_slug) {
             panic!("default world is not found");
         };
 
-        commands.queue(SpawnPlayer::create(default_world, event.client.clone()));
+        let spawn = match location {
+            Some((position, rotation)) => SpawnPlayer::create_at(world_slug, event.client.clone(), position, rotation),
+            None => SpawnPlayer::create(world_slug, event.client.clone()),
+        };
+        commands.queue(spawn);
 
         // let skin = EntitySkinComponent::create(NetworkEntitySkin::Generic);
         // commands.queue(_UpdatePlayerComponent::_create(
@@ -38,3 +48,89 @@ pub fn on_settings_loaded(
         // ));
     }
 }
+
+/// Returns the player to the place where he left;
+/// the spawn of the default world is used if the stored world doesn't exist anymore
+fn get_spawn_location(
+    client: &Client,
+    worlds_manager: &WorldsManager,
+    default_world: &String,
+) -> (String, Option<(Position, Rotation)>) {
+    if let Some((world_slug, position, rotation)) = client.get_stored_location() {
+        if worlds_manager.has_world_with_slug(&world_slug) {
+            return (world_slug, Some((position, rotation)));
+        }
+        log::warn!(
+            target: "worlds",
+            "Stored world &e\"{}\"&r of the player is not found; spawning in the default world",
+            world_slug
+        );
+    }
+    (default_world.clone(), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::get_spawn_location;
+    use crate::{
+        clients::client::{Client, ClientInfo},
+        entities::entity::{Position, Rotation},
+        network::events::on_connection_info::PlayerConnectionInfoEvent,
+        worlds::worlds_manager::WorldsManager,
+    };
+    use common::{
+        server_storage::taits::{IServerStorage, PlayerLocation},
+        utils::srotage_settings::StorageSettings,
+        worlds_storage::taits::{IWorldStorage, WorldStorageData},
+        ServerStorageManager, WorldStorageManager,
+    };
+
+    fn store_location(client: &Client, world_slug: &str, position: Position) {
+        let location = PlayerLocation::create(
+            world_slug.to_string(),
+            position.to_network(),
+            Rotation::new(0.0, 0.0).to_network(),
+        );
+        client.with_player_data_mut(|player_data| player_data.set_location(Some(location)));
+    }
+
+    #[test]
+    fn test_spawn_location() {
+        let client = Client::test();
+        client.set_client_info(ClientInfo::new(&PlayerConnectionInfoEvent::new(
+            client.clone(),
+            "test_player".to_string(),
+            "test".to_string(),
+            "test".to_string(),
+            "test".to_string(),
+            None,
+        )));
+        let storage = ServerStorageManager::init(StorageSettings::in_memory()).expect("in-memory storage must init");
+        client
+            .read_player_data(&storage)
+            .expect("player data must load from storage");
+
+        let worlds_manager = WorldsManager::default();
+        let world_storage = WorldStorageManager::init(StorageSettings::in_memory(), "default").unwrap();
+        worlds_manager
+            .create_world("default".to_string(), world_storage, WorldStorageData::default())
+            .unwrap();
+        let default_world = "default".to_string();
+
+        let (world_slug, location) = get_spawn_location(&client, &worlds_manager, &default_world);
+        assert_eq!(world_slug, "default");
+        assert!(location.is_none());
+
+        let position = Position::new(10.0, 64.0, 20.0);
+        store_location(&client, "default", position);
+        let (world_slug, location) = get_spawn_location(&client, &worlds_manager, &default_world);
+        assert_eq!(world_slug, "default");
+        assert!(location.map(|(stored, _)| stored) == Some(position));
+
+        // The world was deleted since the last session
+        store_location(&client, "lobby", position);
+        let (world_slug, location) = get_spawn_location(&client, &worlds_manager, &default_world);
+        assert_eq!(world_slug, "default");
+        assert!(location.is_none());
+    }
+}
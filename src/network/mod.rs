@@ -1,5 +1,6 @@
 pub mod chunks_sender;
 pub mod events;
+pub mod rate_limit;
 pub mod server;
 pub mod sync_entities;
 pub mod sync_inventory;
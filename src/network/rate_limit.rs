@@ -0,0 +1,373 @@
+use bevy_ecs::resource::Resource;
+use network::messages::ClientMessages;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+/// Client messages sharing one token bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageCategory {
+    Movement = 0,
+    Console,
+    Inventory,
+    Block,
+    Script,
+
+    /// Handshake and flow control; never dropped, only counted toward violations
+    Protocol,
+}
+
+const CATEGORIES: [MessageCategory; 6] = [
+    MessageCategory::Movement,
+    MessageCategory::Console,
+    MessageCategory::Inventory,
+    MessageCategory::Block,
+    MessageCategory::Script,
+    MessageCategory::Protocol,
+];
+
+impl MessageCategory {
+    pub fn of(message: &ClientMessages) -> Self {
+        match message {
+            ClientMessages::PlayerMove { .. } => Self::Movement,
+            ClientMessages::ConsoleInput { .. } | ClientMessages::ConsoleCompleteRequest(..) => Self::Console,
+            ClientMessages::InventoryAction(..) => Self::Inventory,
+            ClientMessages::BlockAction(..) | ClientMessages::BlockUse { .. } => Self::Block,
+            ClientMessages::ClientScriptEvent { .. } => Self::Script,
+            // Dropping them would stall the login or the chunk sending
+            ClientMessages::ConnectionInfo { .. }
+            | ClientMessages::AuthResponse { .. }
+            | ClientMessages::ResourcesHasCache { .. }
+            | ClientMessages::ResourcesLoaded { .. }
+            | ClientMessages::SettingsLoaded
+            | ClientMessages::ChunkRecieved { .. } => Self::Protocol,
+        }
+    }
+
+    pub fn is_droppable(&self) -> bool {
+        *self != Self::Protocol
+    }
+}
+
+impl Display for MessageCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Movement => write!(f, "movement"),
+            Self::Console => write!(f, "console"),
+            Self::Inventory => write!(f, "inventory"),
+            Self::Block => write!(f, "block"),
+            Self::Script => write!(f, "script"),
+            Self::Protocol => write!(f, "protocol"),
+        }
+    }
+}
+
+/// Name of the message for logs
+pub fn get_message_type(message: &ClientMessages) -> &'static str {
+    match message {
+        ClientMessages::ConnectionInfo { .. } => "ConnectionInfo",
+        ClientMessages::AuthResponse { .. } => "AuthResponse",
+        ClientMessages::ConsoleInput { .. } => "ConsoleInput",
+        ClientMessages::ConsoleCompleteRequest(..) => "ConsoleCompleteRequest",
+        ClientMessages::PlayerMove { .. } => "PlayerMove",
+        ClientMessages::ChunkRecieved { .. } => "ChunkRecieved",
+        ClientMessages::ClientScriptEvent { .. } => "ClientScriptEvent",
+        ClientMessages::ResourcesHasCache { .. } => "ResourcesHasCache",
+        ClientMessages::ResourcesLoaded { .. } => "ResourcesLoaded",
+        ClientMessages::SettingsLoaded => "SettingsLoaded",
+        ClientMessages::InventoryAction(..) => "InventoryAction",
+        ClientMessages::BlockAction(..) => "BlockAction",
+        ClientMessages::BlockUse { .. } => "BlockUse",
+    }
+}
+
+/// Messages per second and how many can come at once; `rate: 0` disables the limit
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimit {
+    pub rate: f64,
+    pub burst: f64,
+}
+
+impl RateLimit {
+    const fn new(rate: f64, burst: f64) -> Self {
+        Self { rate, burst }
+    }
+}
+
+/// `rate_limits` section of `server.yml`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitsConfig {
+    movement: RateLimit,
+    console: RateLimit,
+    inventory: RateLimit,
+    block: RateLimit,
+    script: RateLimit,
+    protocol: RateLimit,
+
+    /// Messages over the limit inside the window after which the client is kicked; 0 disables kicking
+    kick_violations: u32,
+
+    /// Seconds
+    violations_window: f64,
+}
+
+impl Default for RateLimitsConfig {
+    fn default() -> Self {
+        Self {
+            movement: RateLimit::new(60.0, 120.0),
+            console: RateLimit::new(2.0, 10.0),
+            inventory: RateLimit::new(20.0, 40.0),
+            block: RateLimit::new(20.0, 40.0),
+            script: RateLimit::new(20.0, 40.0),
+            protocol: RateLimit::new(200.0, 1000.0),
+            kick_violations: 100,
+            violations_window: 10.0,
+        }
+    }
+}
+
+impl RateLimitsConfig {
+    fn get_limit(&self, category: MessageCategory) -> &RateLimit {
+        match category {
+            MessageCategory::Movement => &self.movement,
+            MessageCategory::Console => &self.console,
+            MessageCategory::Inventory => &self.inventory,
+            MessageCategory::Block => &self.block,
+            MessageCategory::Script => &self.script,
+            MessageCategory::Protocol => &self.protocol,
+        }
+    }
+
+    /// Returns all invalid values of the section
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors: Vec<String> = Default::default();
+        for category in CATEGORIES {
+            let limit = self.get_limit(category);
+            if !(limit.rate >= 0.0 && limit.rate.is_finite()) {
+                errors.push(format!(
+                    "rate_limits.{}.rate: must be zero or positive number of messages per second; got {}",
+                    category, limit.rate
+                ));
+            }
+            if !(limit.burst >= 0.0 && limit.burst.is_finite()) {
+                errors.push(format!(
+                    "rate_limits.{}.burst: must be zero or positive number of messages; got {}",
+                    category, limit.burst
+                ));
+            } else if limit.rate > 0.0 && limit.burst < 1.0 {
+                // Bucket would never have a whole token
+                errors.push(format!(
+                    "rate_limits.{}.burst: must be at least 1 while rate is set; got {}",
+                    category, limit.burst
+                ));
+            }
+        }
+        if !(self.violations_window >= 0.0 && self.violations_window.is_finite()) {
+            errors.push(format!(
+                "rate_limits.violations_window: must be zero or positive number of seconds; got {}",
+                self.violations_window
+            ));
+        }
+        errors
+    }
+}
+
+struct TokenBucket {
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: &RateLimit, now: Instant) -> Self {
+        Self {
+            tokens: limit.burst,
+            last: now,
+        }
+    }
+
+    fn try_take(&mut self, limit: &RateLimit, now: Instant) -> bool {
+        if limit.rate <= 0.0 {
+            return true;
+        }
+        let elapsed = now.duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.rate).min(limit.burst);
+        self.last = now;
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+/// Result of the rate limit check of one message
+#[derive(Debug, PartialEq, Eq)]
+pub enum RateLimitResult {
+    /// Messages over the limit which can't be dropped are allowed too
+    Allowed,
+    Dropped,
+
+    /// Client exceeded `kick_violations`
+    Kick,
+}
+
+struct ClientRateLimits {
+    buckets: Vec<TokenBucket>,
+    dropped: [u64; CATEGORIES.len()],
+    violations: u32,
+    violations_start: Instant,
+}
+
+impl ClientRateLimits {
+    fn new(config: &RateLimitsConfig, now: Instant) -> Self {
+        Self {
+            buckets: CATEGORIES
+                .iter()
+                .map(|category| TokenBucket::new(config.get_limit(*category), now))
+                .collect(),
+            dropped: Default::default(),
+            violations: 0,
+            violations_start: now,
+        }
+    }
+}
+
+/// Token buckets and dropped messages of connected clients
+#[derive(Resource, Default)]
+pub struct NetworkRateLimits {
+    clients: HashMap<u64, ClientRateLimits>,
+}
+
+impl NetworkRateLimits {
+    pub fn check(
+        &mut self,
+        config: &RateLimitsConfig,
+        client_id: u64,
+        category: MessageCategory,
+        now: Instant,
+    ) -> RateLimitResult {
+        let limits = self
+            .clients
+            .entry(client_id)
+            .or_insert_with(|| ClientRateLimits::new(config, now));
+        if limits.buckets[category as usize].try_take(config.get_limit(category), now) {
+            return RateLimitResult::Allowed;
+        }
+
+        if category.is_droppable() {
+            limits.dropped[category as usize] += 1;
+        }
+        if now.duration_since(limits.violations_start) > Duration::from_secs_f64(config.violations_window.max(0.0)) {
+            limits.violations = 0;
+            limits.violations_start = now;
+        }
+        limits.violations += 1;
+        if config.kick_violations > 0 && limits.violations >= config.kick_violations {
+            return RateLimitResult::Kick;
+        }
+        match category.is_droppable() {
+            true => RateLimitResult::Dropped,
+            false => RateLimitResult::Allowed,
+        }
+    }
+
+    /// Total of dropped messages of the client
+    pub fn get_dropped(&self, client_id: u64) -> u64 {
+        match self.clients.get(&client_id) {
+            Some(limits) => limits.dropped.iter().sum(),
+            None => 0,
+        }
+    }
+
+    pub fn remove(&mut self, client_id: u64) {
+        self.clients.remove(&client_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MessageCategory, NetworkRateLimits, RateLimit, RateLimitResult, RateLimitsConfig};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_token_bucket() {
+        let config = RateLimitsConfig {
+            console: RateLimit::new(2.0, 3.0),
+            kick_violations: 0,
+            ..Default::default()
+        };
+        let mut limits = NetworkRateLimits::default();
+        let now = Instant::now();
+        let mut check = |now: Instant| limits.check(&config, 1, MessageCategory::Console, now);
+
+        for _ in 0..3 {
+            assert_eq!(check(now), RateLimitResult::Allowed);
+        }
+        assert_eq!(check(now), RateLimitResult::Dropped);
+
+        // Two tokens per second
+        let later = now + Duration::from_millis(500);
+        assert_eq!(check(later), RateLimitResult::Allowed);
+        assert_eq!(check(later), RateLimitResult::Dropped);
+
+        assert_eq!(limits.get_dropped(1), 2);
+        assert_eq!(limits.get_dropped(2), 0);
+    }
+
+    #[test]
+    fn test_kick() {
+        let config = RateLimitsConfig {
+            script: RateLimit::new(1.0, 1.0),
+            kick_violations: 3,
+            ..Default::default()
+        };
+        let mut limits = NetworkRateLimits::default();
+        let now = Instant::now();
+
+        assert_eq!(
+            limits.check(&config, 1, MessageCategory::Script, now),
+            RateLimitResult::Allowed
+        );
+        assert_eq!(
+            limits.check(&config, 1, MessageCategory::Script, now),
+            RateLimitResult::Dropped
+        );
+        assert_eq!(
+            limits.check(&config, 1, MessageCategory::Script, now),
+            RateLimitResult::Dropped
+        );
+        assert_eq!(
+            limits.check(&config, 1, MessageCategory::Script, now),
+            RateLimitResult::Kick
+        );
+
+        // Other categories have their own buckets
+        assert_eq!(
+            limits.check(&config, 1, MessageCategory::Movement, now),
+            RateLimitResult::Allowed
+        );
+    }
+
+    #[test]
+    fn test_protocol_is_not_dropped() {
+        let config = RateLimitsConfig {
+            protocol: RateLimit::new(1.0, 1.0),
+            kick_violations: 3,
+            ..Default::default()
+        };
+        let mut limits = NetworkRateLimits::default();
+        let now = Instant::now();
+        let mut check = || limits.check(&config, 1, MessageCategory::Protocol, now);
+
+        assert_eq!(check(), RateLimitResult::Allowed);
+        assert_eq!(check(), RateLimitResult::Allowed);
+        assert_eq!(check(), RateLimitResult::Allowed);
+        assert_eq!(check(), RateLimitResult::Kick);
+        assert_eq!(limits.get_dropped(1), 0);
+    }
+}
@@ -2,7 +2,10 @@ use bevy::time::common_conditions::on_timer;
 use bevy_app::{App, Startup, Update};
 use bevy_ecs::resource::Resource;
 use bevy_ecs::schedule::IntoScheduleConfigs;
-use bevy_ecs::{system::Res, world::World};
+use bevy_ecs::{
+    system::{Res, ResMut},
+    world::World,
+};
 use common::plugin_api::events::client_script_event::ClientScriptEvent;
 use common::timed_lock;
 use common::utils::debug::SmartRwLock;
@@ -15,6 +18,9 @@ use network::server::{ConnectionMessages, IServerConnection, IServerNetwork};
 use network::NetworkServer;
 
 use super::events::{
+    on_auth_response::{on_auth_response, PlayerAuthResponseEvent},
+    on_block_action::{on_block_action, BlockActionEvent},
+    on_block_use::{on_block_use, BlockUseEvent},
     on_client_script_event::on_client_script_event,
     on_connection::{on_connection, PlayerConnectionEvent},
     on_connection_info::{on_connection_info, PlayerConnectionInfoEvent},
@@ -31,10 +37,11 @@ use crate::clients::clients_container::{ClientsContainer, SharedClientsContainer
 use crate::entities::entity::{IntoServerPosition, IntoServerRotation};
 use crate::entities::events::on_player_spawn::on_player_spawn;
 use crate::network::chunks_sender::{flush_compressed_chunks, send_chunks, ChunkCompressQueue};
+use crate::network::rate_limit::{get_message_type, MessageCategory, NetworkRateLimits, RateLimitResult};
 use crate::network::sync_players::PlayerSpawnEvent;
 use crate::plugins::server_plugin::host_functions::set_clients_container_bridge;
-use crate::{console::commands_executer::CommandsHandler, LaunchSettings};
-use std::sync::Arc;
+use crate::{console::commands_executer::CommandsHandler, server_config::ServerConfig};
+use std::{sync::Arc, time::Instant};
 
 const SEND_CHUNKS_DELAY: std::time::Duration = std::time::Duration::from_millis(10);
 
@@ -116,12 +123,13 @@ impl NetworkContainer {
 
 impl NetworkPlugin {
     pub fn build(app: &mut App) {
-        let server_settings = app.world().get_resource::<LaunchSettings>().unwrap();
-        let ip_port = format!("{}:{}", server_settings.get_args().ip, server_settings.get_args().port);
+        let server_config = app.world().get_resource::<ServerConfig>().unwrap();
+        let ip_port = server_config.get_ip_port();
 
         log::info!(target: "network", "Starting server on &6{}", ip_port);
 
         app.insert_resource(NetworkContainer::new(ip_port));
+        app.insert_resource(NetworkRateLimits::default());
         app.insert_resource(SharedClientsContainer::new(Arc::new(timed_lock!(
             ClientsContainer::default(),
             "clients_container"
@@ -133,12 +141,15 @@ impl NetworkPlugin {
         register_network_event::<ResourcesHasCacheEvent>(app);
         register_network_event::<PlayerConnectionEvent>(app);
         register_network_event::<PlayerConnectionInfoEvent>(app);
+        register_network_event::<PlayerAuthResponseEvent>(app);
         register_network_event::<PlayerDisconnectEvent>(app);
         register_network_event::<PlayerMoveEvent>(app);
         register_network_event::<PlayerMediaLoadedEvent>(app);
         register_network_event::<PlayerSettingsLoadedEvent>(app);
         register_network_event::<ClientScriptEvent>(app);
         register_network_event::<InventoryActionEvent>(app);
+        register_network_event::<BlockActionEvent>(app);
+        register_network_event::<BlockUseEvent>(app);
         register_network_event::<ConsoleCompleteRequestEvent>(app);
 
         // Core drain system (replaces receive_message_system + handle_events_system)
@@ -159,10 +170,13 @@ impl NetworkPlugin {
         app.add_systems(Update, on_resources_has_cache.after(drain_network_system));
         app.add_systems(Update, on_connection.after(drain_network_system));
         app.add_systems(Update, on_connection_info.after(drain_network_system));
+        app.add_systems(Update, on_auth_response.after(drain_network_system));
         app.add_systems(Update, on_disconnect.after(drain_network_system));
         app.add_systems(Update, on_player_move.after(drain_network_system));
         app.add_systems(Update, on_media_loaded.after(drain_network_system));
         app.add_systems(Update, on_inventory_action.after(drain_network_system));
+        app.add_systems(Update, on_block_action.after(drain_network_system));
+        app.add_systems(Update, on_block_use.after(drain_network_system));
         app.add_systems(Update, on_settings_loaded.after(drain_network_system));
         app.add_systems(Update, on_client_script_event.after(drain_network_system));
 
@@ -186,6 +200,7 @@ fn register_clients_container_bridge(clients: Res<SharedClientsContainer>) {
 fn span_name_for_client_message(msg: &ClientMessages) -> &'static str {
     match msg {
         ClientMessages::ConnectionInfo { .. } => "server.drain_network_system::ConnectionInfo",
+        ClientMessages::AuthResponse { .. } => "server.drain_network_system::AuthResponse",
         ClientMessages::ConsoleInput { .. } => "server.drain_network_system::ConsoleInput",
         ClientMessages::ConsoleCompleteRequest(..) => "server.drain_network_system::ConsoleCompleteRequest",
         ClientMessages::PlayerMove { .. } => "server.drain_network_system::PlayerMove",
@@ -195,6 +210,8 @@ fn span_name_for_client_message(msg: &ClientMessages) -> &'static str {
         ClientMessages::ResourcesLoaded { .. } => "server.drain_network_system::ResourcesLoaded",
         ClientMessages::SettingsLoaded => "server.drain_network_system::SettingsLoaded",
         ClientMessages::InventoryAction(..) => "server.drain_network_system::InventoryAction",
+        ClientMessages::BlockAction(..) => "server.drain_network_system::BlockAction",
+        ClientMessages::BlockUse { .. } => "server.drain_network_system::BlockUse",
     }
 }
 
@@ -205,12 +222,17 @@ fn drain_network_system(
     disconnect_channel: Res<NetworkEventChannel<PlayerDisconnectEvent>>,
     resources_has_cache_channel: Res<NetworkEventChannel<ResourcesHasCacheEvent>>,
     connection_info_channel: Res<NetworkEventChannel<PlayerConnectionInfoEvent>>,
+    auth_response_channel: Res<NetworkEventChannel<PlayerAuthResponseEvent>>,
     player_move_channel: Res<NetworkEventChannel<PlayerMoveEvent>>,
     player_media_loaded_channel: Res<NetworkEventChannel<PlayerMediaLoadedEvent>>,
     settings_loaded_channel: Res<NetworkEventChannel<PlayerSettingsLoadedEvent>>,
     client_script_channel: Res<NetworkEventChannel<ClientScriptEvent>>,
     inventory_action_channel: Res<NetworkEventChannel<InventoryActionEvent>>,
+    block_action_channel: Res<NetworkEventChannel<BlockActionEvent>>,
+    block_use_channel: Res<NetworkEventChannel<BlockUseEvent>>,
     complete_request_channel: Res<NetworkEventChannel<ConsoleCompleteRequestEvent>>,
+    server_config: Res<ServerConfig>,
+    mut rate_limits: ResMut<NetworkRateLimits>,
 ) {
     #[cfg(feature = "trace")]
     let _span = bevy_utils::tracing::info_span!("server.drain_network_system").entered();
@@ -238,6 +260,7 @@ fn drain_network_system(
                         .emit_event(PlayerConnectionEvent::new(client.clone()));
                 }
                 ConnectionMessages::Disconnect { client_id, reason } => {
+                    rate_limits.remove(client_id);
                     let clients_guard = clients.read();
 
                     // Happends while shot down server
@@ -254,11 +277,30 @@ fn drain_network_system(
     }
 
     // --- Drain client messages ---
+    let now = Instant::now();
     let clients_guard = clients.read();
     for (client_id, client) in clients_guard.iter() {
         for decoded in client.get_connection().drain_client_messages() {
             #[cfg(debug_assertions)]
             let _s = crate::span!(span_name_for_client_message(&decoded));
+
+            let category = MessageCategory::of(&decoded);
+            match rate_limits.check(server_config.get_rate_limits(), *client_id, category, now) {
+                RateLimitResult::Allowed => (),
+                RateLimitResult::Dropped => continue,
+                RateLimitResult::Kick => {
+                    log::warn!(
+                        target: "network",
+                        "&c{}&r is kicked for flooding &4{}&r messages &8category: &7{} &8dropped: &7{}",
+                        client,
+                        get_message_type(&decoded),
+                        category,
+                        rate_limits.get_dropped(*client_id)
+                    );
+                    client.disconnect(Some("Kicked for sending too many messages".to_string()));
+                    break;
+                }
+            }
             match decoded {
                 ClientMessages::ResourcesHasCache { exists } => {
                     let event = ResourcesHasCacheEvent::new(client.clone(), exists);
@@ -301,11 +343,23 @@ fn drain_network_system(
                     version,
                     architecture,
                     rendering_device,
+                    view_distance,
                 } => {
-                    let info =
-                        PlayerConnectionInfoEvent::new(client.clone(), login, version, architecture, rendering_device);
+                    let info = PlayerConnectionInfoEvent::new(
+                        client.clone(),
+                        login,
+                        version,
+                        architecture,
+                        rendering_device,
+                        view_distance,
+                    );
                     connection_info_channel.0.emit_event(info);
                 }
+                ClientMessages::AuthResponse { proof } => {
+                    auth_response_channel
+                        .0
+                        .emit_event(PlayerAuthResponseEvent::new(client.clone(), proof));
+                }
                 ClientMessages::ClientScriptEvent {
                     script_slug,
                     slug,
@@ -329,6 +383,16 @@ fn drain_network_system(
                         .0
                         .emit_event(InventoryActionEvent::new(client.clone(), inventory_action));
                 }
+                ClientMessages::BlockAction(block_action) => {
+                    block_action_channel
+                        .0
+                        .emit_event(BlockActionEvent::new(client.clone(), block_action));
+                }
+                ClientMessages::BlockUse { position } => {
+                    block_use_channel
+                        .0
+                        .emit_event(BlockUseEvent::new(client.clone(), position));
+                }
             }
         }
     }
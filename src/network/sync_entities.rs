@@ -8,8 +8,10 @@ use strum::IntoEnumIterator;
 
 use crate::{
     entities::{
+        dropped_item::DroppedItemComponent,
         entity::{Position, Rotation},
         entity_tag::EntityTagComponent,
+        health::Health,
         skin::EntitySkinComponent,
         traits::IEntityNetworkComponent,
         EntityComponent,
@@ -19,12 +21,18 @@ use crate::{
 
 use crate::clients::client::Client;
 
+/// Entities which are visible for clients: players with skins and dropped items
+pub(crate) fn is_streamed_entity(entity_ref: &EntityRef) -> bool {
+    entity_ref.get::<EntitySkinComponent>().is_some() || entity_ref.get::<DroppedItemComponent>().is_some()
+}
+
 pub(crate) fn send_start_streaming_entity(target_client: &Client, entity_ref: EntityRef, world_slug: String) {
     let position = entity_ref.get::<Position>().unwrap();
     let rotation = entity_ref.get::<Rotation>().unwrap();
-    let skin = entity_ref
-        .get::<EntitySkinComponent>()
-        .expect("skin is required for send_start_streaming_entity");
+    assert!(
+        is_streamed_entity(&entity_ref),
+        "skin or dropped item is required for send_start_streaming_entity"
+    );
 
     let mut components: Vec<EntityNetworkComponent> = Default::default();
 
@@ -36,10 +44,20 @@ pub(crate) fn send_start_streaming_entity(target_client: &Client, entity_ref: En
                 }
             }
             EntityComponent::Skin(_) => {
-                components.push(skin.to_network());
+                if let Some(skin) = entity_ref.get::<EntitySkinComponent>() {
+                    components.push(skin.to_network());
+                }
+            }
+            EntityComponent::Health(_) => {
+                if let Some(health) = entity_ref.get::<Health>() {
+                    components.push(health.to_network());
+                }
             }
         }
     }
+    if let Some(dropped_item) = entity_ref.get::<DroppedItemComponent>() {
+        components.push(dropped_item.to_network());
+    }
 
     let msg = ServerMessages::StartStreamingEntity {
         id: entity_ref.id().index(),
@@ -56,7 +74,7 @@ pub(crate) fn send_start_streaming_entity(target_client: &Client, entity_ref: En
 ///
 /// Обязательно проверять, чтобы информация о игроке не отправилась ему же самому!
 ///
-/// Only for entities with EntitySkin
+/// Only for entities with EntitySkin or dropped items
 pub(crate) fn sync_entity_spawn(world_manager: &WorldManager, entity: Entity) {
     let ecs = world_manager.get_ecs();
     let entity_ref = ecs.get_entity(entity).unwrap();
@@ -88,7 +106,7 @@ pub(crate) fn sync_entity_spawn(world_manager: &WorldManager, entity: Entity) {
 ///   • перешел из видимого чанка в невидимый - отправлять StopStreamingEntity
 ///   • перешел из невидимого чанка в видимый - отправлять StartStreamingEntity
 ///
-/// Only for entities with EntitySkin
+/// Only for entities with EntitySkin or dropped items
 pub(crate) fn sync_entity_move(
     world_manager: &WorldManager,
     target_entity: Entity,
@@ -179,7 +197,7 @@ pub(crate) fn sync_entity_move(
 
 /// Отправка всем наблюдателям чанка StopStreamingEntity
 ///
-/// Only for entities with EntitySkin
+/// Only for entities with EntitySkin or dropped items
 pub fn sync_entity_despawn(world_manager: &WorldManager, entity: Entity) {
     let ecs = world_manager.get_ecs();
     let entity_ref = ecs.get_entity(entity).unwrap();
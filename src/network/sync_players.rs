@@ -9,7 +9,7 @@ use crate::{
     worlds::world_manager::{ChunkChanged, WorldManager},
 };
 
-use super::sync_entities::{send_start_streaming_entity, sync_entity_move};
+use super::sync_entities::{is_streamed_entity, send_start_streaming_entity, sync_entity_move};
 
 #[derive(Message)]
 pub struct PlayerSpawnEvent {
@@ -41,7 +41,7 @@ pub fn send_entities_for_player(world_manager: &WorldManager, target_entity: Ent
                         continue;
                     }
 
-                    if target_ref.get::<EntitySkinComponent>().is_some() {
+                    if is_streamed_entity(&target_ref) {
                         send_start_streaming_entity(&*client, target_ref, world_manager.get_slug().clone());
                     }
                 }
@@ -73,34 +73,7 @@ pub fn sync_player_move(
     let entity_ref = ecs.get_entity(target_entity).unwrap();
 
     if let Some(change) = chunks_changed {
-        let client = entity_ref.get::<Client>().unwrap();
-
-        // Stop streaming entities from unseen chunks
-        let mut ids: Vec<u32> = Default::default();
-        for chunk in change.abandoned_chunks.iter() {
-            for entity_ref in world_manager.get_ecs().get_chunk_entities(&chunk).unwrap() {
-                ids.push(entity_ref.id().index());
-            }
-        }
-        if ids.len() > 0 {
-            let msg = ServerMessages::StopStreamingEntities {
-                world_slug: world_manager.get_slug().clone(),
-                ids,
-            };
-            client.send_message(NetworkMessageType::ReliableOrdered, &msg);
-        }
-
-        // Start streaming entities from new chunks
-        for chunk in change.new_chunks.iter() {
-            for target_ref in world_manager.get_ecs().get_chunk_entities(&chunk).unwrap() {
-                if target_ref.id() == target_entity {
-                    continue;
-                }
-                if target_ref.get::<EntitySkinComponent>().is_some() {
-                    send_start_streaming_entity(&*client, target_ref, world_manager.get_slug().clone());
-                }
-            }
-        }
+        sync_player_chunks_changed(world_manager, target_entity, change);
     }
 
     // Sync his entity if exists
@@ -114,3 +87,38 @@ pub fn sync_player_move(
         );
     }
 }
+
+/// Streams entities of the player's abandoned and new chunks:
+/// StopStreamingEntities for the old ones and StartStreamingEntity for the new ones
+pub(crate) fn sync_player_chunks_changed(world_manager: &WorldManager, target_entity: Entity, change: &ChunkChanged) {
+    let ecs = world_manager.get_ecs();
+    let entity_ref = ecs.get_entity(target_entity).unwrap();
+    let client = entity_ref.get::<Client>().unwrap();
+
+    // Stop streaming entities from unseen chunks
+    let mut ids: Vec<u32> = Default::default();
+    for chunk in change.abandoned_chunks.iter() {
+        for entity_ref in world_manager.get_ecs().get_chunk_entities(&chunk).unwrap() {
+            ids.push(entity_ref.id().index());
+        }
+    }
+    if ids.len() > 0 {
+        let msg = ServerMessages::StopStreamingEntities {
+            world_slug: world_manager.get_slug().clone(),
+            ids,
+        };
+        client.send_message(NetworkMessageType::ReliableOrdered, &msg);
+    }
+
+    // Start streaming entities from new chunks
+    for chunk in change.new_chunks.iter() {
+        for target_ref in world_manager.get_ecs().get_chunk_entities(&chunk).unwrap() {
+            if target_ref.id() == target_entity {
+                continue;
+            }
+            if is_streamed_entity(&target_ref) {
+                send_start_streaming_entity(&*client, target_ref, world_manager.get_slug().clone());
+            }
+        }
+    }
+}
@@ -11,6 +11,7 @@ use std::{
 };
 
 use super::server_plugin::plugin_instance::WASMPluginManager;
+use crate::items_manager::{loot_tables::LootTableManifest, recipes::RecipeManifest};
 
 const ALLOWED_FILES_EXT: &'static [&'static str] = &[".png", ".glb"];
 
@@ -24,6 +25,12 @@ pub struct ResourceManifest {
     pub media: Option<Vec<String>>,
 
     pub blocks: Option<Vec<BlockTypeManifest>>,
+    pub recipes: Option<Vec<RecipeManifest>>,
+
+    // Slugs of blocks which open the crafting inventory
+    pub crafting_blocks: Option<Vec<String>>,
+
+    pub loot_tables: Option<Vec<LootTableManifest>>,
 }
 
 pub struct PluginContainer {
@@ -35,6 +42,9 @@ pub struct PluginContainer {
     pub(crate) media: BTreeMap<String, Vec<u8>>,
 
     blocks: Vec<BlockType>,
+    recipes: Vec<RecipeManifest>,
+    crafting_blocks: Vec<String>,
+    loot_tables: Vec<LootTableManifest>,
 
     plugin: Option<Arc<WASMPluginManager>>,
 }
@@ -85,7 +95,7 @@ impl PluginContainer {
         Ok(wasm_file)
     }
 
-    pub fn from_manifest(resource_path: PathBuf) -> Result<Self, String> {
+    pub fn from_manifest(resource_path: PathBuf, pool_size: usize) -> Result<Self, String> {
         let mut manifest_path = resource_path.clone();
         manifest_path.push("manifest.yml");
 
@@ -119,11 +129,13 @@ impl PluginContainer {
             scripts: Default::default(),
             media: Default::default(),
             blocks: Default::default(),
+            recipes: manifest.recipes.clone().unwrap_or_default(),
+            crafting_blocks: manifest.crafting_blocks.clone().unwrap_or_default(),
+            loot_tables: manifest.loot_tables.clone().unwrap_or_default(),
             plugin: Default::default(),
         };
 
         if let Some(wasm_path) = Self::find_plugin_wasm(&resource_path)? {
-            let pool_size = rayon::current_num_threads() + 1;
             let wasm_plugin_manager =
                 match WASMPluginManager::new(&wasm_path, &resource_path, &manifest.slug, pool_size) {
                     Ok(w) => w,
@@ -232,6 +244,18 @@ impl PluginContainer {
         blocks
     }
 
+    pub(crate) fn get_recipes(&self) -> &Vec<RecipeManifest> {
+        &self.recipes
+    }
+
+    pub(crate) fn get_crafting_blocks(&self) -> &Vec<String> {
+        &self.crafting_blocks
+    }
+
+    pub(crate) fn get_loot_tables(&self) -> &Vec<LootTableManifest> {
+        &self.loot_tables
+    }
+
     pub fn local_to_global_path(&self, path: &String) -> String {
         format!("{}://{}", self.get_slug(), path)
     }
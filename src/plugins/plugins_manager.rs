@@ -5,7 +5,9 @@ use bevy_ecs::{
 use common::{
     blocks::block_type::{BlockContent, BlockType},
     default_resources::DEFAULT_RESOURCES,
-    plugin_api::events::{client_script_event::ClientScriptEvent, player_spawn::PlayerSpawnEvent},
+    plugin_api::events::{
+        block_interact::BlockInteractEvent, client_script_event::ClientScriptEvent, player_spawn::PlayerSpawnEvent,
+    },
     utils::{calculate_hash, split_resource_path},
 };
 use network::messages::ResurceScheme;
@@ -15,7 +17,7 @@ use super::{
     plugin_container::PluginContainer, resources_archive::ResourcesArchive,
     server_plugin::plugin_instance::WASMPluginManager, server_settings::ServerSettings,
 };
-use crate::{launch_settings::LaunchSettings, runtime_plugin::RuntimePlugin};
+use crate::{launch_settings::LaunchSettings, runtime_plugin::RuntimePlugin, server_config::ServerConfig};
 
 #[derive(Resource, Default)]
 pub struct PluginsManager {
@@ -31,7 +33,12 @@ impl PluginsManager {
             .expect("GET_RESOURCES_ARCHIVE: resources_archive is not set")
     }
 
-    pub fn rescan_plugins(&mut self, path: PathBuf, server_settings: &mut ServerSettings) -> Result<(), String> {
+    pub fn rescan_plugins(
+        &mut self,
+        path: PathBuf,
+        server_settings: &mut ServerSettings,
+        wasm_pool_size: usize,
+    ) -> Result<(), String> {
         self.unload_all_plugins();
 
         let mut resources_archive = ResourcesArchive::default();
@@ -55,7 +62,7 @@ impl PluginsManager {
                 continue;
             }
 
-            let plugin = match PluginContainer::from_manifest(resource_path.clone()) {
+            let plugin = match PluginContainer::from_manifest(resource_path.clone(), wasm_pool_size) {
                 Ok(i) => i,
                 Err(e) => {
                     return Err(format!(
@@ -83,7 +90,7 @@ impl PluginsManager {
             let elapsed = now.elapsed();
             log::info!(
                 target: "resources",
-                " □ Plugin &2\"{}\"&r loaded {:.1?};&8 Title:&7\"{}\" &8v:&7\"{}\" &8Author:&7\"{}\" &8Scripts:&7{} &8Media:&7{} &8Blocks:&7{}",
+                " □ Plugin &2\"{}\"&r loaded {:.1?};&8 Title:&7\"{}\" &8v:&7\"{}\" &8Author:&7\"{}\" &8Scripts:&7{} &8Media:&7{} &8Blocks:&7{} &8Recipes:&7{}",
                 plugin.get_slug(),
                 elapsed,
                 plugin.get_title(),
@@ -92,6 +99,7 @@ impl PluginsManager {
                 plugin.get_scripts_count(),
                 plugin.get_media_count(),
                 blocks.len(),
+                plugin.get_recipes().len(),
             );
 
             let mut scheme = ResurceScheme {
@@ -220,6 +228,10 @@ impl PluginsManager {
         self.plugins.insert(slug, plugin);
     }
 
+    pub(crate) fn iter_plugins(&self) -> impl Iterator<Item = &PluginContainer> {
+        self.plugins.values()
+    }
+
     pub fn has_world_generator(&self, method: &String) -> bool {
         for (_plugin_slug, plugin) in self.plugins.iter() {
             if plugin.has_world_generator(method) {
@@ -261,6 +273,28 @@ impl PluginsManager {
         }
     }
 
+    /// Plugins receive the event one by one until any of them cancels it
+    ///
+    /// Returns true if the interaction was cancelled; then the block inventory is not opened
+    pub fn dispatch_block_interact_event(&self, event: &BlockInteractEvent) -> bool {
+        for (_plugin_slug, plugin) in self.plugins.iter() {
+            let Some(wasm_plugin) = plugin.get_wasm_plugin().as_ref() else {
+                continue;
+            };
+
+            if !wasm_plugin.has_event_handler::<BlockInteractEvent>() {
+                continue;
+            }
+
+            match wasm_plugin.call_event_with_result::<BlockInteractEvent, bool>(event) {
+                Ok(true) => return true,
+                Ok(false) => (),
+                Err(e) => log::warn!(target: "scripts", "WASM block interact event error: {}", e),
+            }
+        }
+        false
+    }
+
     pub fn get_world_generator(&self, method: &String) -> Option<Arc<WASMPluginManager>> {
         for (_plugin_slug, plugin) in self.plugins.iter() {
             if plugin.has_world_generator(method) {
@@ -293,8 +327,14 @@ pub(crate) fn rescan_plugins(
     mut plugins_manager: ResMut<PluginsManager>,
     launch_settings: Res<LaunchSettings>,
     mut server_settings: ResMut<ServerSettings>,
+    server_config: Res<ServerConfig>,
 ) {
-    if let Err(e) = plugins_manager.rescan_plugins(launch_settings.get_plugins_path(), &mut *server_settings) {
+    let result = plugins_manager.rescan_plugins(
+        launch_settings.get_plugins_path(),
+        &mut *server_settings,
+        server_config.get_wasm_pool_size(),
+    );
+    if let Err(e) = result {
         log::error!(target: "resources", "&cPlugins loading error:");
         log::error!(target: "resources", "{}", e);
         RuntimePlugin::stop();
@@ -1,17 +1,32 @@
 use crate::{
-    clients::{client::WorldEntity, clients_container::ClientsContainer},
+    clients::{
+        client::{Client, WorldEntity},
+        client_version::{ClientCompatibility, MAX_CLIENT_VERSION, MIN_CLIENT_VERSION},
+        clients_container::ClientsContainer,
+    },
+    entities::{
+        health::{damage_player, heal_player, DamageSource, Health},
+        stats::EntityStats,
+    },
     inventory::{
+        audit_log::{snapshot, AuditActor, AuditInventory},
         commands::{close_inventory, get_or_create_inventory, open_inventory},
         inventory_manager::InventoryManager,
     },
-    items_manager::{ItemDisplay as ServerItemDisplay, ItemInfo as ServerItemInfo, ItemType as ServerItemType},
+    items_manager::{
+        recipes::{RecipeKind, RecipeManifest},
+        ItemDisplay as ServerItemDisplay, ItemInfo as ServerItemInfo, ItemType as ServerItemType,
+    },
     network::sync_world_change::sync_world_block_change,
     storage::storage_manager::StorageManager,
-    worlds::worlds_manager::WorldsManager,
+    worlds::{chunks::chunks_load_state::ChunkTicket, world_manager::WorldManager, worlds_manager::WorldsManager},
 };
 use common::{
-    chunks::{block_position::BlockPosition, chunk_data::BlockDataInfo},
-    inventory::item::{Item, ItemKind},
+    chunks::{block_position::BlockPosition, chunk_data::BlockDataInfo, chunk_position::ChunkPosition},
+    inventory::{
+        inventory::{Inventory, InventoryType},
+        item::{Item, ItemKind, ItemMetadata},
+    },
     plugin_api::inventory::OpenInventoryRequest,
     plugin_api::items_manager::{
         ItemDisplay as ApiItemDisplay, ItemInfo as ApiItemInfo, ItemType as ApiItemType,
@@ -20,12 +35,13 @@ use common::{
     utils::debug::SmartRwLock,
 };
 use extism::*;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json;
 use std::{
     fs,
     path::{Path, PathBuf},
     sync::{Arc, OnceLock},
+    time::Duration,
 };
 
 pub type SharedHostContext = Arc<parking_lot::Mutex<HostContext>>;
@@ -325,7 +341,7 @@ pub fn add_inventory_item_raw(
     plugin: &mut CurrentPlugin,
     inputs: &[Val],
     outputs: &mut [Val],
-    _user_data: UserData<SharedHostContext>,
+    user_data: UserData<SharedHostContext>,
 ) -> Result<(), Error> {
     let inventory_id: u64 = plugin.memory_get_val(&inputs[0])?;
     let item_json: String = plugin.memory_get_val(&inputs[1])?;
@@ -350,38 +366,52 @@ pub fn add_inventory_item_raw(
     let items_manager =
         get_items_manager_bridge().ok_or_else(|| Error::msg("ItemsManager bridge is not initialized"))?;
 
-    let clients_guard = clients.read();
-    for (_client_id, client) in clients_guard.iter() {
-        let is_target_inventory = client
-            .get_player_data()
-            .map(|player_data| player_data.get_inventory().get_id() == inventory_id)
-            .unwrap_or(false);
-        if !is_target_inventory {
-            continue;
-        }
+    let actor = get_plugin_actor(&user_data)?;
 
+    let target_client = clients
+        .read()
+        .iter()
+        .find(|(_client_id, client)| {
+            client
+                .get_player_data()
+                .map(|player_data| player_data.get_inventory().get_id() == inventory_id)
+                .unwrap_or(false)
+        })
+        .map(|(_client_id, client)| client.clone());
+    if let Some(client) = target_client {
         let result = client.with_player_data_mut(|player_data| {
             let max_stack_size = items_manager.read().get_max_stack_size(&item);
-            player_data
-                .get_inventory_mut()
-                .add_item(item.clone(), max_stack_size, |slot, updated_item| {
-                    emit_inventory_change_to_client(
-                        client,
-                        common::inventory::inventory::InventoryType::PlayerPersonal,
-                        slot,
-                        updated_item,
-                        &items_manager,
-                    );
-                })
+            let inventory = player_data.get_inventory_mut();
+            let before = snapshot(inventory);
+            let result = inventory.add_item(item.clone(), max_stack_size, |slot, updated_item| {
+                emit_inventory_change_to_client(
+                    &client,
+                    common::inventory::inventory::InventoryType::PlayerPersonal,
+                    slot,
+                    updated_item,
+                    &items_manager,
+                );
+            });
+            (result, before, snapshot(inventory))
         });
-        let Some(Ok(())) = result else {
+        let Some((result, before, after)) = result else {
             plugin.memory_set_val(&mut outputs[0], "full")?;
             return Ok(());
         };
-        plugin.memory_set_val(&mut outputs[0], "ok")?;
+
+        // A part of the stack may be added even if the inventory is full
+        if let Some(login) = client.get_client_info().map(|info| info.get_login().clone()) {
+            inventory_manager.write().get_audit_log_mut().record_diff(
+                &actor,
+                &AuditInventory::Player(login),
+                &before,
+                &after,
+            );
+        }
+        let status = if result.is_ok() { "ok" } else { "full" };
+        plugin.memory_set_val(&mut outputs[0], status)?;
         return Ok(());
     }
-    drop(clients_guard);
 
     let Some(location) = inventory_manager
         .read()
@@ -412,7 +442,8 @@ pub fn add_inventory_item_raw(
     };
 
     let max_stack_size = items_manager.read().get_max_stack_size(&item);
-    let Ok(()) = block_inventory
+    let before = snapshot(block_inventory.get_inventory());
+    let result = block_inventory
         .get_inventory_mut()
         .add_item(item.clone(), max_stack_size, |slot, updated_item| {
             emit_inventory_change_to_watchers(
@@ -423,11 +454,215 @@ pub fn add_inventory_item_raw(
                 updated_item,
                 &items_manager,
             );
-        })
+        });
+    let after = snapshot(block_inventory.get_inventory());
+    chunk_column.mark_dirty();
+    drop(chunk_column);
+
+    inventory_manager.write().get_audit_log_mut().record_diff(
+        &actor,
+        &AuditInventory::World(inventory_id),
+        &before,
+        &after,
+    );
+    let status = if result.is_ok() { "ok" } else { "full" };
+    plugin.memory_set_val(&mut outputs[0], status)?;
+    Ok(())
+}
+
+fn get_plugin_slug(user_data: &UserData<SharedHostContext>) -> Result<String, Error> {
+    let inner = user_data.get()?;
+    let inner = inner.lock().unwrap();
+    let ctx = inner.lock();
+    Ok(ctx.get_plugin_slug().clone())
+}
+
+fn get_plugin_actor(user_data: &UserData<SharedHostContext>) -> Result<AuditActor, Error> {
+    Ok(AuditActor::Plugin(get_plugin_slug(user_data)?))
+}
+
+/// Inventory found by `with_inventory_by_id`
+enum InventoryOwner {
+    Player(crate::clients::client::Client),
+    World,
+}
+
+/// Runs `f` on the player or the world inventory with the id;
+/// the chunk of the world inventory is marked for saving after `f` only if `modify` is set
+///
+/// Returns None if the inventory is not loaded
+fn with_inventory_by_id<R>(
+    inventory_id: u64,
+    modify: bool,
+    f: impl FnOnce(&mut Inventory) -> R,
+) -> Result<Option<(R, InventoryOwner)>, Error> {
+    let clients =
+        get_clients_container_bridge().ok_or_else(|| Error::msg("ClientsContainer bridge is not initialized"))?;
+    let inventory_manager =
+        get_inventory_manager_bridge().ok_or_else(|| Error::msg("InventoryManager bridge is not initialized"))?;
+    let worlds_manager =
+        get_worlds_manager_bridge().ok_or_else(|| Error::msg("WorldsManager bridge is not initialized"))?;
+
+    let clients_guard = clients.read();
+    for (_client_id, client) in clients_guard.iter() {
+        let is_target_inventory = client
+            .get_player_data()
+            .map(|player_data| player_data.get_inventory().get_id() == inventory_id)
+            .unwrap_or(false);
+        if !is_target_inventory {
+            continue;
+        }
+
+        let result = client.with_player_data_mut(|player_data| f(player_data.get_inventory_mut()));
+        return Ok(result.map(|result| (result, InventoryOwner::Player(client.clone()))));
+    }
+    drop(clients_guard);
+
+    let Some(location) = inventory_manager
+        .read()
+        .state()
+        .get_inventory_location(&inventory_id)
+        .cloned()
+    else {
+        return Ok(None);
+    };
+
+    let worlds_guard = worlds_manager.read();
+    let Some(world_manager) = worlds_guard.get_world_manager(location.get_world_slug()) else {
+        return Ok(None);
+    };
+    let Some(chunk_column_arc) = world_manager
+        .get_chunks_map()
+        .get_chunk_column_arc(location.get_chunk_position())
     else {
-        plugin.memory_set_val(&mut outputs[0], "full")?;
+        return Ok(None);
+    };
+    let mut chunk_column = chunk_column_arc.write();
+    let Some(block_inventory) = chunk_column.get_chunk_storage_mut().get_inventory_mut(inventory_id) else {
+        return Ok(None);
+    };
+    let result = f(block_inventory.get_inventory_mut());
+    if modify {
+        chunk_column.mark_dirty();
+    }
+    Ok(Some((result, InventoryOwner::World)))
+}
+
+pub fn get_inventory_item_raw(
+    plugin: &mut CurrentPlugin,
+    inputs: &[Val],
+    outputs: &mut [Val],
+    _user_data: UserData<SharedHostContext>,
+) -> Result<(), Error> {
+    let inventory_id: u64 = plugin.memory_get_val(&inputs[0])?;
+    let slot: u64 = plugin.memory_get_val(&inputs[1])?;
+
+    let item = with_inventory_by_id(inventory_id, false, |inventory| {
+        inventory.get_slot(slot as usize).cloned()
+    })?
+    .and_then(|(item, _owner)| item);
+    let item_json =
+        serde_json::to_string(&item).map_err(|e| Error::msg(format!("Serialize inventory item failed: {}", e)))?;
+
+    plugin.memory_set_val(&mut outputs[0], item_json)?;
+    Ok(())
+}
+
+pub fn set_inventory_item_metadata_raw(
+    plugin: &mut CurrentPlugin,
+    inputs: &[Val],
+    outputs: &mut [Val],
+    user_data: UserData<SharedHostContext>,
+) -> Result<(), Error> {
+    let inventory_id: u64 = plugin.memory_get_val(&inputs[0])?;
+    let slot: u64 = plugin.memory_get_val(&inputs[1])?;
+    let metadata_json: String = plugin.memory_get_val(&inputs[2])?;
+    let metadata: ItemMetadata =
+        serde_json::from_str(&metadata_json).map_err(|e| Error::msg(format!("Invalid item metadata json: {}", e)))?;
+
+    if let (Some(durability), Some(max_durability)) = (metadata.get_durability(), metadata.get_max_durability()) {
+        if durability > max_durability {
+            return Err(Error::msg(format!(
+                "Item durability {} is greater than max durability {}",
+                durability, max_durability
+            )));
+        }
+    }
+
+    let slot = slot as usize;
+    let updated = with_inventory_by_id(inventory_id, true, |inventory| {
+        let before = inventory.get_slot(slot)?.clone();
+
+        // Durability belongs to one item; the whole stack would share it
+        let has_durability = metadata.get_durability().is_some() || metadata.get_max_durability().is_some();
+        if has_durability && before.get_amount() != 1 {
+            return Some(Err(format!(
+                "Item durability can be set only for a single item; slot {} has {}",
+                slot,
+                before.get_amount()
+            )));
+        }
+
+        let mut item = before.clone();
+        item.set_metadata(metadata);
+        inventory.set_slot_option(slot, Some(item.clone()));
+        Some(Ok((before, item)))
+    })?;
+    let Some((updated, owner)) = updated else {
+        plugin.memory_set_val(&mut outputs[0], "not_found")?;
+        return Ok(());
+    };
+    let Some(updated) = updated else {
+        plugin.memory_set_val(&mut outputs[0], "empty")?;
         return Ok(());
     };
+    let (before, item) = updated.map_err(Error::msg)?;
+
+    let audit_inventory = match &owner {
+        InventoryOwner::Player(client) => client
+            .get_client_info()
+            .map(|info| AuditInventory::Player(info.get_login().clone())),
+        InventoryOwner::World => Some(AuditInventory::World(inventory_id)),
+    };
+    if let Some(audit_inventory) = audit_inventory {
+        let inventory_manager =
+            get_inventory_manager_bridge().ok_or_else(|| Error::msg("InventoryManager bridge is not initialized"))?;
+        inventory_manager.write().get_audit_log_mut().record(
+            &get_plugin_actor(&user_data)?,
+            &audit_inventory,
+            slot,
+            Some(before),
+            Some(item.clone()),
+        );
+    }
+
+    let items_manager =
+        get_items_manager_bridge().ok_or_else(|| Error::msg("ItemsManager bridge is not initialized"))?;
+    match owner {
+        InventoryOwner::Player(client) => {
+            emit_inventory_change_to_client(
+                &client,
+                InventoryType::PlayerPersonal,
+                slot,
+                Some(&item),
+                &items_manager,
+            );
+        }
+        InventoryOwner::World => {
+            let clients = get_clients_container_bridge()
+                .ok_or_else(|| Error::msg("ClientsContainer bridge is not initialized"))?;
+            let inventory_manager = get_inventory_manager_bridge()
+                .ok_or_else(|| Error::msg("InventoryManager bridge is not initialized"))?;
+            emit_inventory_change_to_watchers(
+                &clients,
+                &inventory_manager.read(),
+                inventory_id,
+                slot,
+                Some(&item),
+                &items_manager,
+            );
+        }
+    }
 
     plugin.memory_set_val(&mut outputs[0], "ok")?;
     Ok(())
@@ -475,6 +710,153 @@ pub fn get_player_world_slug_raw(
     Ok(())
 }
 
+#[derive(Serialize)]
+struct ClientVersionInfo {
+    version: String,
+    compatibility: ClientCompatibility,
+    min_version: String,
+    max_version: String,
+}
+
+/// Client version of the player and the range supported by the server; `null` before the handshake
+pub fn get_player_client_version_raw(
+    plugin: &mut CurrentPlugin,
+    inputs: &[Val],
+    outputs: &mut [Val],
+    _user_data: UserData<SharedHostContext>,
+) -> Result<(), Error> {
+    let client_id: u64 = plugin.memory_get_val(&inputs[0])?;
+    let clients =
+        get_clients_container_bridge().ok_or_else(|| Error::msg("ClientsContainer bridge is not initialized"))?;
+    let info = clients
+        .read()
+        .get(&client_id)
+        .and_then(|client| client.get_client_info())
+        .map(|client_info| ClientVersionInfo {
+            version: client_info.get_version().clone(),
+            compatibility: client_info.get_compatibility(),
+            min_version: MIN_CLIENT_VERSION.to_string(),
+            max_version: MAX_CLIENT_VERSION.to_string(),
+        });
+    let json =
+        serde_json::to_string(&info).map_err(|e| Error::msg(format!("Serialize client version failed: {}", e)))?;
+    plugin.memory_set_val(&mut outputs[0], json)?;
+    Ok(())
+}
+
+/// Effective stats of the player entity; `null` if the player isn't spawned
+pub fn get_player_stats_raw(
+    plugin: &mut CurrentPlugin,
+    inputs: &[Val],
+    outputs: &mut [Val],
+    _user_data: UserData<SharedHostContext>,
+) -> Result<(), Error> {
+    let client_id: u64 = plugin.memory_get_val(&inputs[0])?;
+    let clients =
+        get_clients_container_bridge().ok_or_else(|| Error::msg("ClientsContainer bridge is not initialized"))?;
+    let worlds_manager =
+        get_worlds_manager_bridge().ok_or_else(|| Error::msg("WorldsManager bridge is not initialized"))?;
+
+    let world_entity = clients
+        .read()
+        .get(&client_id)
+        .and_then(|client| client.get_world_entity());
+    let stats: Option<EntityStats> = world_entity.and_then(|world_entity| {
+        let worlds_manager = worlds_manager.read();
+        let world_manager = worlds_manager.get_world_manager(world_entity.get_world_slug())?;
+        let entity_ref = world_manager.get_ecs().get_entity(world_entity.get_entity())?;
+        entity_ref.get::<EntityStats>().cloned()
+    });
+
+    let json =
+        serde_json::to_string(&stats).map_err(|e| Error::msg(format!("Serialize player stats failed: {}", e)))?;
+    plugin.memory_set_val(&mut outputs[0], json)?;
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct DamageRequest {
+    amount: f32,
+    source: DamageSource,
+}
+
+#[derive(Serialize)]
+struct HealthInfo {
+    health: f32,
+    max_health: f32,
+}
+
+/// Changes the health of the spawned player and returns it as json; `null` if the player isn't spawned
+fn change_player_health(
+    client_id: u64,
+    f: impl FnOnce(&mut WorldManager, &Client) -> Option<Health>,
+) -> Result<String, Error> {
+    let clients =
+        get_clients_container_bridge().ok_or_else(|| Error::msg("ClientsContainer bridge is not initialized"))?;
+    let worlds_manager =
+        get_worlds_manager_bridge().ok_or_else(|| Error::msg("WorldsManager bridge is not initialized"))?;
+
+    let Some(client) = clients.read().get(&client_id).cloned() else {
+        return Ok("null".to_string());
+    };
+    let health = client.get_world_entity().and_then(|world_entity| {
+        let worlds_manager = worlds_manager.read();
+        let mut world_manager = worlds_manager.get_world_manager_mut(world_entity.get_world_slug())?;
+        f(&mut *world_manager, &client)
+    });
+    let health_info = health.map(|health| HealthInfo {
+        health: health.get_health(),
+        max_health: health.get_max_health(),
+    });
+    serde_json::to_string(&health_info).map_err(|e| Error::msg(format!("Serialize player health failed: {}", e)))
+}
+
+/// Damage source `plugin` always gets the slug of the calling plugin
+pub fn damage_player_raw(
+    plugin: &mut CurrentPlugin,
+    inputs: &[Val],
+    outputs: &mut [Val],
+    user_data: UserData<SharedHostContext>,
+) -> Result<(), Error> {
+    let client_id: u64 = plugin.memory_get_val(&inputs[0])?;
+    let request_json: String = plugin.memory_get_val(&inputs[1])?;
+    let mut request: DamageRequest =
+        serde_json::from_str(&request_json).map_err(|e| Error::msg(format!("Invalid damage json: {}", e)))?;
+    if request.amount.is_nan() || request.amount < 0.0 {
+        return Err(Error::msg("Damage amount must be zero or positive"));
+    }
+    if let DamageSource::Plugin(slug) = &mut request.source {
+        *slug = get_plugin_slug(&user_data)?;
+    }
+
+    let health_json = change_player_health(client_id, |world_manager, client| {
+        damage_player(world_manager, client, request.amount, request.source)
+    })?;
+    plugin.memory_set_val(&mut outputs[0], health_json)?;
+    Ok(())
+}
+
+pub fn heal_player_raw(
+    plugin: &mut CurrentPlugin,
+    inputs: &[Val],
+    outputs: &mut [Val],
+    _user_data: UserData<SharedHostContext>,
+) -> Result<(), Error> {
+    let client_id: u64 = plugin.memory_get_val(&inputs[0])?;
+    let amount_json: String = plugin.memory_get_val(&inputs[1])?;
+    let amount: f32 =
+        serde_json::from_str(&amount_json).map_err(|e| Error::msg(format!("Invalid heal amount: {}", e)))?;
+    if amount.is_nan() || amount < 0.0 {
+        return Err(Error::msg("Heal amount must be zero or positive"));
+    }
+
+    let health_json = change_player_health(client_id, |world_manager, client| {
+        heal_player(world_manager, client, amount)
+    })?;
+    plugin.memory_set_val(&mut outputs[0], health_json)?;
+    Ok(())
+}
+
 pub fn get_player_inventory_raw(
     plugin: &mut CurrentPlugin,
     inputs: &[Val],
@@ -521,7 +903,8 @@ pub fn add_item_raw(
         item.get_title().clone(),
         item.get_description().clone(),
         item.get_max_stack_size(),
-    );
+    )
+    .stats(item.get_stats().clone());
 
     let items_manager =
         get_items_manager_bridge().ok_or_else(|| Error::msg("ItemsManager bridge is not initialized"))?;
@@ -536,6 +919,65 @@ pub fn add_item_raw(
     Ok(())
 }
 
+pub fn add_recipe_raw(
+    plugin: &mut CurrentPlugin,
+    inputs: &[Val],
+    outputs: &mut [Val],
+    _user_data: UserData<SharedHostContext>,
+) -> Result<(), Error> {
+    let recipe_json: String = plugin.memory_get_val(&inputs[0])?;
+    let recipe: RecipeManifest =
+        serde_json::from_str(&recipe_json).map_err(|e| Error::msg(format!("Invalid recipe json: {}", e)))?;
+
+    let items_manager =
+        get_items_manager_bridge().ok_or_else(|| Error::msg("ItemsManager bridge is not initialized"))?;
+    items_manager
+        .write()
+        .get_recipes_mut()
+        .add_recipe(recipe)
+        .map_err(Error::msg)?;
+
+    plugin.memory_set_val(&mut outputs[0], "")?;
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct SmeltingRecipeInfo {
+    result: Item,
+
+    // Seconds
+    time: f32,
+}
+
+pub fn find_smelting_recipe_raw(
+    plugin: &mut CurrentPlugin,
+    inputs: &[Val],
+    outputs: &mut [Val],
+    _user_data: UserData<SharedHostContext>,
+) -> Result<(), Error> {
+    let item_json: String = plugin.memory_get_val(&inputs[0])?;
+    let item: Item = serde_json::from_str(&item_json).map_err(|e| Error::msg(format!("Invalid item json: {}", e)))?;
+
+    let items_manager =
+        get_items_manager_bridge().ok_or_else(|| Error::msg("ItemsManager bridge is not initialized"))?;
+    let items_manager = items_manager.read();
+    let recipe_info = items_manager
+        .get_recipes()
+        .find_smelting_recipe(&item)
+        .and_then(|recipe| match recipe.get_kind() {
+            RecipeKind::Smelting { time, .. } => Some(SmeltingRecipeInfo {
+                result: recipe.get_result().clone(),
+                time: time.as_secs_f32(),
+            }),
+            _ => None,
+        });
+    let recipe_json = serde_json::to_string(&recipe_info)
+        .map_err(|e| Error::msg(format!("Serialize smelting recipe failed: {}", e)))?;
+
+    plugin.memory_set_val(&mut outputs[0], recipe_json)?;
+    Ok(())
+}
+
 pub fn get_or_create_player_data_raw(
     plugin: &mut CurrentPlugin,
     inputs: &[Val],
@@ -624,6 +1066,65 @@ pub fn edit_world_block_raw(
     Ok(())
 }
 
+/// Keeps the chunk loaded for `ttl_ms`; the plugin must renew the ticket to keep it longer
+pub fn add_chunk_ticket_raw(
+    plugin: &mut CurrentPlugin,
+    inputs: &[Val],
+    outputs: &mut [Val],
+    user_data: UserData<SharedHostContext>,
+) -> Result<(), Error> {
+    let world_slug: String = plugin.memory_get_val(&inputs[0])?;
+    let position_json: String = plugin.memory_get_val(&inputs[1])?;
+    let ttl_ms: u64 = plugin.memory_get_val(&inputs[2])?;
+    if ttl_ms == 0 {
+        return Err(Error::msg("Chunk ticket ttl must be greater than 0"));
+    }
+    let chunk_position: ChunkPosition = serde_json::from_str(&position_json)
+        .map_err(|e| Error::msg(format!("Invalid chunk position json: {}", e)))?;
+    let ticket = get_plugin_ticket(&user_data)?;
+
+    let worlds_manager =
+        get_worlds_manager_bridge().ok_or_else(|| Error::msg("WorldsManager bridge is not initialized"))?;
+    let worlds_manager = worlds_manager.read();
+    let Some(mut world_manager) = worlds_manager.get_world_manager_mut(&world_slug) else {
+        return Err(Error::msg(format!("World \"{}\" not found", world_slug)));
+    };
+    world_manager
+        .get_chunks_map_mut()
+        .add_ticket(chunk_position, ticket, Some(Duration::from_millis(ttl_ms)));
+    plugin.memory_set_val(&mut outputs[0], "")?;
+    Ok(())
+}
+
+pub fn remove_chunk_ticket_raw(
+    plugin: &mut CurrentPlugin,
+    inputs: &[Val],
+    outputs: &mut [Val],
+    user_data: UserData<SharedHostContext>,
+) -> Result<(), Error> {
+    let world_slug: String = plugin.memory_get_val(&inputs[0])?;
+    let position_json: String = plugin.memory_get_val(&inputs[1])?;
+    let chunk_position: ChunkPosition = serde_json::from_str(&position_json)
+        .map_err(|e| Error::msg(format!("Invalid chunk position json: {}", e)))?;
+    let ticket = get_plugin_ticket(&user_data)?;
+
+    let worlds_manager =
+        get_worlds_manager_bridge().ok_or_else(|| Error::msg("WorldsManager bridge is not initialized"))?;
+    let worlds_manager = worlds_manager.read();
+    let Some(mut world_manager) = worlds_manager.get_world_manager_mut(&world_slug) else {
+        return Err(Error::msg(format!("World \"{}\" not found", world_slug)));
+    };
+    let removed = world_manager
+        .get_chunks_map_mut()
+        .release_ticket(&chunk_position, &ticket);
+    plugin.memory_set_val(&mut outputs[0], if removed { "true" } else { "false" })?;
+    Ok(())
+}
+
+fn get_plugin_ticket(user_data: &UserData<SharedHostContext>) -> Result<ChunkTicket, Error> {
+    Ok(ChunkTicket::Plugin(get_plugin_slug(user_data)?))
+}
+
 pub fn get_or_create_inventory_raw(
     plugin: &mut CurrentPlugin,
     inputs: &[Val],
@@ -765,6 +1266,20 @@ pub fn register_all<'a>(builder: PluginBuilder<'a>, ctx: &SharedHostContext) ->
             UserData::new(Arc::clone(ctx)),
             add_inventory_item_raw,
         )
+        .with_function(
+            "get_inventory_item_raw",
+            [PTR, PTR],
+            [PTR],
+            UserData::new(Arc::clone(ctx)),
+            get_inventory_item_raw,
+        )
+        .with_function(
+            "set_inventory_item_metadata_raw",
+            [PTR, PTR, PTR],
+            [PTR],
+            UserData::new(Arc::clone(ctx)),
+            set_inventory_item_metadata_raw,
+        )
         .with_function(
             "get_player_world_slug_raw",
             [PTR],
@@ -772,6 +1287,34 @@ pub fn register_all<'a>(builder: PluginBuilder<'a>, ctx: &SharedHostContext) ->
             UserData::new(Arc::clone(ctx)),
             get_player_world_slug_raw,
         )
+        .with_function(
+            "get_player_client_version_raw",
+            [PTR],
+            [PTR],
+            UserData::new(Arc::clone(ctx)),
+            get_player_client_version_raw,
+        )
+        .with_function(
+            "get_player_stats_raw",
+            [PTR],
+            [PTR],
+            UserData::new(Arc::clone(ctx)),
+            get_player_stats_raw,
+        )
+        .with_function(
+            "damage_player_raw",
+            [PTR, PTR],
+            [PTR],
+            UserData::new(Arc::clone(ctx)),
+            damage_player_raw,
+        )
+        .with_function(
+            "heal_player_raw",
+            [PTR, PTR],
+            [PTR],
+            UserData::new(Arc::clone(ctx)),
+            heal_player_raw,
+        )
         .with_function(
             "get_player_inventory_raw",
             [PTR],
@@ -786,6 +1329,20 @@ pub fn register_all<'a>(builder: PluginBuilder<'a>, ctx: &SharedHostContext) ->
             UserData::new(Arc::clone(ctx)),
             add_item_raw,
         )
+        .with_function(
+            "add_recipe_raw",
+            [PTR],
+            [PTR],
+            UserData::new(Arc::clone(ctx)),
+            add_recipe_raw,
+        )
+        .with_function(
+            "find_smelting_recipe_raw",
+            [PTR],
+            [PTR],
+            UserData::new(Arc::clone(ctx)),
+            find_smelting_recipe_raw,
+        )
         .with_function(
             "get_or_create_player_data_raw",
             [PTR],
@@ -828,4 +1385,18 @@ pub fn register_all<'a>(builder: PluginBuilder<'a>, ctx: &SharedHostContext) ->
             UserData::new(Arc::clone(ctx)),
             close_inventory_raw,
         )
+        .with_function(
+            "add_chunk_ticket_raw",
+            [PTR, PTR, PTR],
+            [PTR],
+            UserData::new(Arc::clone(ctx)),
+            add_chunk_ticket_raw,
+        )
+        .with_function(
+            "remove_chunk_ticket_raw",
+            [PTR, PTR],
+            [PTR],
+            UserData::new(Arc::clone(ctx)),
+            remove_chunk_ticket_raw,
+        )
 }
@@ -37,12 +37,12 @@ impl ServerSettings {
         Ok(())
     }
 
-    pub fn get_network_settings(&self) -> ServerMessages {
+    pub fn get_network_settings(&self, chunks_distance: u16) -> ServerMessages {
         assert!(self.loaded, "server settings is not loaded");
         ServerMessages::Settings {
             block_types: self.blocks.clone(),
             block_id_map: self.block_id_map.as_ref().unwrap().clone(),
-            chunks_distance: crate::CHUNKS_DISTANCE,
+            chunks_distance,
         }
     }
 
@@ -104,6 +104,14 @@ impl ServerSettings {
         self.blocks.iter().find(|block_type| block_type.get_slug() == slug)
     }
 
+    pub fn get_block_id_by_slug(&self, slug: &str) -> Option<BlockIndexType> {
+        let block_id_map = self.block_id_map.as_ref()?;
+        block_id_map
+            .iter()
+            .find(|(_id, block_slug)| *block_slug == slug)
+            .map(|(id, _slug)| *id)
+    }
+
     pub fn add_block(&mut self, block_type: BlockType) {
         self.blocks.push(block_type);
     }
@@ -7,10 +7,12 @@ use lazy_static::lazy_static;
 use std::sync::{Arc, RwLock};
 
 use crate::console::console_handler::ConsoleHandler;
+use crate::inventory::SharedInventoryManager;
 use crate::plugins::plugins_manager::PluginsManager;
 use crate::worlds::worlds_manager::SharedWorldsManager;
 
-use crate::clients::clients_container::SharedClientsContainer;
+use crate::clients::{autosave::save_players, clients_container::SharedClientsContainer};
+use crate::storage::storage_manager::SharedStorageManager;
 
 lazy_static! {
     static ref SERVER_STATE: Arc<RwLock<ServerState>> = Arc::new(RwLock::new(ServerState::STARTED));
@@ -81,13 +83,21 @@ fn update_runtime(
     mut console_handler: ResMut<ConsoleHandler>,
     mut plugins_manager: ResMut<PluginsManager>,
     worlds_manager: Res<SharedWorldsManager>,
+    storage: Option<Res<SharedStorageManager>>,
+    inventory_manager: Res<SharedInventoryManager>,
 ) {
     let _s = crate::span!("runtime.update_runtime");
     if RuntimePlugin::is_stopping() {
         log::info!(target: "main", "Server shutdown...");
+        if let Some(storage) = storage {
+            save_players(&clients.read(), &worlds_manager.read(), &storage.read());
+        }
         clients.write().disconnect_all(Some("Server shutting down".to_string()));
         plugins_manager.unload_all_plugins();
         worlds_manager.read().save_all().unwrap();
+        if let Err(e) = inventory_manager.write().get_audit_log_mut().flush() {
+            log::error!(target: "inventory", "{}", e);
+        }
         console_handler.handle_stop_server();
         app_exit_events.write(AppExit::Success);
         RuntimePlugin::set_stoped();
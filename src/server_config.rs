@@ -0,0 +1,444 @@
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs::File, path::PathBuf, time::Duration};
+
+use crate::{
+    clients::auth::AuthMode,
+    launch_settings::LaunchSettings,
+    network::rate_limit::RateLimitsConfig,
+    worlds::chunks::{chunk_failure::ChunkFailurePolicy, chunks_map::ChunksUpdateSettings},
+};
+
+pub const SERVER_CONFIG_FILE: &str = "server.yml";
+
+/// Server configuration from the `server.yml` inside the server data folder.
+///
+/// Missing keys take default values; the file is created with defaults if it doesn't exist.
+/// Command line arguments override values from the file.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    ip: String,
+    port: u16,
+
+    /// Server ticks per second
+    target_tps: f64,
+
+    /// World for new players and for players from unloaded worlds
+    default_world: String,
+
+    /// Generator of the default world if it's created at the first start
+    default_world_generator: String,
+
+    /// Radius in chunks around the player which are loaded and sent;
+    /// used for clients which didn't request their own view distance
+    chunks_distance: u16,
+
+    /// Upper limit of the view distance requested by clients
+    max_chunks_distance: u16,
+
+    /// Radius in chunks around the world spawn which is kept loaded; 0 disables it
+    spawn_chunks_radius: u16,
+
+    /// Per-world overrides by world slug
+    worlds: BTreeMap<String, WorldConfig>,
+
+    /// Seconds before the chunk nobody is watching is despawned
+    chunks_despawn_timer: f64,
+
+    /// Distance in blocks from the player eyes to blocks which can be broken or placed
+    block_reach_distance: f32,
+
+    /// Seconds before the dropped item nobody picked up disappears
+    dropped_item_lifetime: f64,
+
+    /// Dead players drop all items of their inventory where they died
+    drop_inventory_on_death: bool,
+
+    /// What to do with chunks which can't be read or generated: retry, quarantine or void
+    chunk_failure_policy: ChunkFailurePolicy,
+
+    /// Attempts of the `retry` policy before the chunk is quarantined
+    chunk_load_retries: u32,
+
+    /// Chunks loading or generating at once in each world; by default the number of rayon threads
+    max_chunk_loads: Option<usize>,
+
+    /// Seconds between saves of changed chunks; 0 disables the autosave
+    autosave_interval: u64,
+
+    /// Chunks sent to the client and waiting for the confirmation
+    send_chunk_queue_limit: usize,
+
+    /// WASM instances per plugin; by default the number of rayon threads + 1
+    wasm_pool_size: Option<usize>,
+
+    /// How players prove their login: offline trusts the client, password uses `auth.yml`
+    auth_mode: AuthMode,
+
+    /// Token buckets of client messages by category and the kick threshold
+    rate_limits: RateLimitsConfig,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorldConfig {
+    /// Lowers the server `max_chunks_distance` inside this world
+    max_chunks_distance: Option<u16>,
+
+    /// Replaces the server `spawn_chunks_radius`
+    spawn_chunks_radius: Option<u16>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            ip: "0.0.0.0".to_string(),
+            port: 19132,
+            target_tps: common::TARGET_TPS,
+            default_world: "default".to_string(),
+            default_world_generator: "default".to_string(),
+            chunks_distance: 12,
+            max_chunks_distance: 16,
+            spawn_chunks_radius: 2,
+            worlds: Default::default(),
+            chunks_despawn_timer: 5.0,
+            block_reach_distance: 6.0,
+            dropped_item_lifetime: 300.0,
+            drop_inventory_on_death: true,
+            chunk_failure_policy: Default::default(),
+            chunk_load_retries: 3,
+            max_chunk_loads: None,
+            autosave_interval: 300,
+            send_chunk_queue_limit: 64,
+            wasm_pool_size: None,
+            auth_mode: Default::default(),
+            rate_limits: Default::default(),
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Reads the config and applies command line overrides
+    pub fn load(launch_settings: &LaunchSettings) -> Result<Self, String> {
+        let path = launch_settings.get_server_data_path().join(SERVER_CONFIG_FILE);
+        let mut config = Self::read_file(&path)?;
+
+        let args = launch_settings.get_args();
+        if let Some(ip) = args.ip.as_ref() {
+            config.ip = ip.clone();
+        }
+        if let Some(port) = args.port {
+            config.port = port;
+        }
+
+        if let Err(errors) = config.validate() {
+            let mut message = format!("&cfile &4{}&c has invalid values:", path.display());
+            for error in errors {
+                message.push_str(&format!("\n &4- &c{}", error));
+            }
+            return Err(message);
+        }
+        Ok(config)
+    }
+
+    fn read_file(path: &PathBuf) -> Result<Self, String> {
+        if !path.exists() {
+            if let Some(parent) = path.parent() {
+                if let Err(e) = std::fs::create_dir_all(parent) {
+                    return Err(format!("&cCannot create server data directory: {}", e));
+                }
+            }
+            let file = match File::create(path) {
+                Ok(f) => f,
+                Err(e) => return Err(format!("&cfile &4{}&c create error: {}", path.display(), e)),
+            };
+            if let Err(e) = serde_yaml::to_writer(file, &Self::default()) {
+                return Err(format!("&cfile &4{}&c write error: {}", path.display(), e));
+            }
+            log::info!(target: "settings", "Config file &e{}&r is not exists; Default file was created", path.display());
+        }
+
+        let data = match std::fs::read_to_string(path) {
+            Ok(d) => d,
+            Err(e) => return Err(format!("&cfile &4{}&c read error: {}", path.display(), e)),
+        };
+        Self::parse(&data).map_err(|e| format!("&cfile &4{}&c yaml parse error: &c{}", path.display(), e))
+    }
+
+    fn parse(data: &str) -> Result<Self, serde_yaml::Error> {
+        // An empty file is a valid config with default values
+        if data.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        serde_yaml::from_str(data)
+    }
+
+    /// Returns all invalid values at once
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors: Vec<String> = Default::default();
+
+        if self.ip.parse::<std::net::IpAddr>().is_err() {
+            errors.push(format!("ip: \"{}\" is not a valid ip address", self.ip));
+        }
+        if self.port == 0 {
+            errors.push("port: must be greater than 0".to_string());
+        }
+        if !(self.target_tps > 0.0 && self.target_tps <= 1000.0) {
+            errors.push(format!(
+                "target_tps: must be between 0 and 1000; got {}",
+                self.target_tps
+            ));
+        }
+        if !is_valid_slug(&self.default_world) {
+            errors.push(format!(
+                "default_world: \"{}\" must be non-empty and contain only a-z, 0-9, _ and -",
+                self.default_world
+            ));
+        }
+        if self.default_world_generator.is_empty() {
+            errors.push("default_world_generator: must not be empty".to_string());
+        }
+        if self.chunks_distance < 1 || self.chunks_distance > 64 {
+            errors.push(format!(
+                "chunks_distance: must be between 1 and 64; got {}",
+                self.chunks_distance
+            ));
+        } else if self.chunks_distance > self.max_chunks_distance {
+            errors.push(format!(
+                "chunks_distance: must not be greater than max_chunks_distance {}; got {}",
+                self.max_chunks_distance, self.chunks_distance
+            ));
+        }
+        if self.max_chunks_distance < 1 || self.max_chunks_distance > 64 {
+            errors.push(format!(
+                "max_chunks_distance: must be between 1 and 64; got {}",
+                self.max_chunks_distance
+            ));
+        }
+        if self.spawn_chunks_radius > 32 {
+            errors.push(format!(
+                "spawn_chunks_radius: must be between 0 and 32; got {}",
+                self.spawn_chunks_radius
+            ));
+        }
+        for (slug, world_config) in self.worlds.iter() {
+            if world_config.max_chunks_distance == Some(0) {
+                errors.push(format!("worlds.{}.max_chunks_distance: must be greater than 0", slug));
+            }
+            if world_config.spawn_chunks_radius.is_some_and(|r| r > 32) {
+                errors.push(format!("worlds.{}.spawn_chunks_radius: must be between 0 and 32", slug));
+            }
+        }
+        if !(self.chunks_despawn_timer >= 0.0 && self.chunks_despawn_timer.is_finite()) {
+            errors.push(format!(
+                "chunks_despawn_timer: must be zero or positive number of seconds; got {}",
+                self.chunks_despawn_timer
+            ));
+        }
+        if !(self.block_reach_distance > 0.0 && self.block_reach_distance.is_finite()) {
+            errors.push(format!(
+                "block_reach_distance: must be positive number of blocks; got {}",
+                self.block_reach_distance
+            ));
+        }
+        if !(self.dropped_item_lifetime > 0.0 && self.dropped_item_lifetime.is_finite()) {
+            errors.push(format!(
+                "dropped_item_lifetime: must be positive number of seconds; got {}",
+                self.dropped_item_lifetime
+            ));
+        }
+        if self.max_chunk_loads == Some(0) {
+            errors.push("max_chunk_loads: must be greater than 0".to_string());
+        }
+        if self.send_chunk_queue_limit == 0 {
+            errors.push("send_chunk_queue_limit: must be greater than 0".to_string());
+        }
+        if self.wasm_pool_size == Some(0) {
+            errors.push("wasm_pool_size: must be greater than 0".to_string());
+        }
+        errors.extend(self.rate_limits.validate());
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+
+    pub fn get_ip_port(&self) -> String {
+        format!("{}:{}", self.ip, self.port)
+    }
+
+    pub fn get_target_tps(&self) -> f64 {
+        self.target_tps
+    }
+
+    pub fn get_tick_duration(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.target_tps)
+    }
+
+    pub fn get_default_world(&self) -> &String {
+        &self.default_world
+    }
+
+    pub fn get_default_world_generator(&self) -> &String {
+        &self.default_world_generator
+    }
+
+    pub fn get_chunks_distance(&self) -> u16 {
+        self.chunks_distance
+    }
+
+    /// The largest view distance allowed inside the world
+    pub fn get_max_chunks_distance(&self, world_slug: &String) -> u16 {
+        let world_max = self.worlds.get(world_slug).and_then(|w| w.max_chunks_distance);
+        match world_max {
+            Some(world_max) => world_max.min(self.max_chunks_distance),
+            None => self.max_chunks_distance,
+        }
+    }
+
+    /// View distance of the client inside the world: the requested one
+    /// (or the default `chunks_distance`) capped by the server and world maximum
+    pub fn get_view_distance(&self, world_slug: &String, requested: Option<u16>) -> u16 {
+        let distance = requested.unwrap_or(self.chunks_distance);
+        distance.clamp(1, self.get_max_chunks_distance(world_slug))
+    }
+
+    pub fn get_spawn_chunks_radius(&self, world_slug: &String) -> u16 {
+        match self.worlds.get(world_slug).and_then(|w| w.spawn_chunks_radius) {
+            Some(radius) => radius,
+            None => self.spawn_chunks_radius,
+        }
+    }
+
+    pub fn get_chunks_despawn_timer(&self) -> Duration {
+        Duration::from_secs_f64(self.chunks_despawn_timer)
+    }
+
+    pub fn get_block_reach_distance(&self) -> f32 {
+        self.block_reach_distance
+    }
+
+    pub fn get_dropped_item_lifetime(&self) -> Duration {
+        Duration::from_secs_f64(self.dropped_item_lifetime)
+    }
+
+    pub fn get_drop_inventory_on_death(&self) -> bool {
+        self.drop_inventory_on_death
+    }
+
+    pub fn get_chunks_update_settings(&self) -> ChunksUpdateSettings {
+        ChunksUpdateSettings {
+            despawn_timer: self.get_chunks_despawn_timer(),
+            failure_policy: self.chunk_failure_policy,
+            max_retries: self.chunk_load_retries,
+            max_loads: match self.max_chunk_loads {
+                Some(max) => max,
+                None => rayon::current_num_threads(),
+            },
+        }
+    }
+
+    pub fn get_autosave_interval(&self) -> Option<Duration> {
+        match self.autosave_interval {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
+
+    pub fn get_auth_mode(&self) -> AuthMode {
+        self.auth_mode
+    }
+
+    pub fn get_rate_limits(&self) -> &RateLimitsConfig {
+        &self.rate_limits
+    }
+
+    pub fn get_send_chunk_queue_limit(&self) -> usize {
+        self.send_chunk_queue_limit
+    }
+
+    pub fn get_wasm_pool_size(&self) -> usize {
+        match self.wasm_pool_size {
+            Some(size) => size,
+            None => rayon::current_num_threads() + 1,
+        }
+    }
+}
+
+fn is_valid_slug(slug: &String) -> bool {
+    !slug.is_empty()
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ServerConfig;
+
+    #[test]
+    fn test_parse_partial_config() {
+        let config = ServerConfig::parse("port: 20000\nchunks_distance: 8\n").unwrap();
+        assert_eq!(config.port, 20000);
+        assert_eq!(config.get_chunks_distance(), 8);
+        assert_eq!(config.get_default_world(), "default");
+        assert!(config.validate().is_ok());
+
+        assert_eq!(ServerConfig::parse("").unwrap(), ServerConfig::default());
+    }
+
+    #[test]
+    fn test_parse_unknown_key() {
+        assert!(ServerConfig::parse("chunk_distance: 8\n").is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let config = ServerConfig::parse(
+            "ip: localhost\nport: 0\ntarget_tps: 0\ndefault_world: Main World\nchunks_distance: 100\nsend_chunk_queue_limit: 0\nwasm_pool_size: 0\n",
+        )
+        .unwrap();
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors.len(), 7);
+        assert!(errors[0].starts_with("ip:"));
+        assert!(errors[4].starts_with("chunks_distance:"));
+    }
+
+    #[test]
+    fn test_validate_rate_limits() {
+        let config = ServerConfig::parse(
+            "rate_limits:\n  console:\n    rate: 2\n    burst: 0.5\n  block:\n    rate: -1\n    burst: 0\n  violations_window: .inf\n",
+        )
+        .unwrap();
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(errors[0].starts_with("rate_limits.console.burst:"));
+        assert!(errors[1].starts_with("rate_limits.block.rate:"));
+        assert!(errors[2].starts_with("rate_limits.violations_window:"));
+
+        // Disabled limit doesn't need the burst
+        let config = ServerConfig::parse("rate_limits:\n  console:\n    rate: 0\n    burst: 0\n").unwrap();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_view_distance() {
+        let config = ServerConfig::parse(
+            "chunks_distance: 8\nmax_chunks_distance: 16\nworlds:\n  nether:\n    max_chunks_distance: 6\n",
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+
+        let default_world = "default".to_string();
+        assert_eq!(config.get_view_distance(&default_world, None), 8);
+        assert_eq!(config.get_view_distance(&default_world, Some(4)), 4);
+        assert_eq!(config.get_view_distance(&default_world, Some(32)), 16);
+        assert_eq!(config.get_view_distance(&default_world, Some(0)), 1);
+
+        let nether = "nether".to_string();
+        assert_eq!(config.get_view_distance(&nether, None), 6);
+        assert_eq!(config.get_view_distance(&nether, Some(4)), 4);
+    }
+}
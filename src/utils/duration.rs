@@ -0,0 +1,34 @@
+/// `30s`, `30m`, `12h`, `7d` in milliseconds; `None` if the input isn't a positive duration
+pub fn parse_duration(input: &str) -> Option<i64> {
+    let unit = input.chars().last()?;
+    let multiplier: i64 = match unit {
+        's' => 1_000,
+        'm' => 60_000,
+        'h' => 3_600_000,
+        'd' => 86_400_000,
+        _ => return None,
+    };
+    let value: i64 = input[..input.len() - 1].parse().ok()?;
+    if value <= 0 {
+        return None;
+    }
+    Some(value.saturating_mul(multiplier))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_duration;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30s"), Some(30_000));
+        assert_eq!(parse_duration("15m"), Some(900_000));
+        assert_eq!(parse_duration("2h"), Some(7_200_000));
+        assert_eq!(parse_duration("7d"), Some(604_800_000));
+        assert_eq!(parse_duration("0d"), None);
+        assert_eq!(parse_duration("d"), None);
+        assert_eq!(parse_duration("2x"), None);
+        assert_eq!(parse_duration("5é"), None);
+        assert_eq!(parse_duration(""), None);
+    }
+}
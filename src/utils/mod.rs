@@ -1,3 +1,6 @@
+pub mod duration;
+pub mod yaml_file;
+
 use bevy_ecs::resource::Resource;
 use common::utils::debug::SmartRwLock;
 use std::sync::Arc;
@@ -0,0 +1,111 @@
+use bevy_ecs::{
+    resource::Resource,
+    system::{Res, ResMut},
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fs::File,
+    ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+};
+
+use crate::{launch_settings::LaunchSettings, runtime_plugin::RuntimePlugin};
+
+/// Data stored in the YAML file inside the server data folder
+///
+/// The file and its folder are created on the first save.
+#[derive(Default)]
+pub struct YamlFile<T> {
+    path: PathBuf,
+    data: T,
+}
+
+impl<T: Serialize + DeserializeOwned + Default> YamlFile<T> {
+    /// Missing or empty file gives the default data
+    pub fn load(server_data_path: &Path, file_name: &str) -> Result<Self, String> {
+        let path = server_data_path.join(file_name);
+        match Self::read(&path)? {
+            Some(file) => Ok(file),
+            None => Ok(Self::new(path, Default::default())),
+        }
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> YamlFile<T> {
+    pub fn new(path: PathBuf, data: T) -> Self {
+        Self { path, data }
+    }
+
+    /// Returns None for the missing or empty file
+    pub fn read(path: &Path) -> Result<Option<Self>, String> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let data = match std::fs::read_to_string(path) {
+            Ok(d) => d,
+            Err(e) => return Err(format!("&cfile &4{}&c read error: {}", path.display(), e)),
+        };
+        if data.trim().is_empty() {
+            return Ok(None);
+        }
+        let data = serde_yaml::from_str(&data)
+            .map_err(|e| format!("&cfile &4{}&c yaml parse error: {}", path.display(), e))?;
+        Ok(Some(Self::new(path.to_path_buf(), data)))
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            if let Err(e) = std::fs::create_dir_all(parent) {
+                return Err(format!("&cCannot create directory &4{}&c: {}", parent.display(), e));
+            }
+        }
+        let file = match File::create(&self.path) {
+            Ok(f) => f,
+            Err(e) => return Err(format!("&cfile &4{}&c create error: {}", self.path.display(), e)),
+        };
+        serde_yaml::to_writer(file, &self.data)
+            .map_err(|e| format!("&cfile &4{}&c write error: {}", self.path.display(), e))
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl<T> Deref for YamlFile<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.data
+    }
+}
+
+impl<T> DerefMut for YamlFile<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.data
+    }
+}
+
+/// Resource which is loaded from the server data folder on startup
+pub trait YamlFileResource: Resource + Sized {
+    const SPAN: &'static str;
+    const LOG_TARGET: &'static str;
+
+    fn load(server_data_path: &Path) -> Result<Self, String>;
+}
+
+/// Stops the server if the file can't be loaded
+pub(crate) fn load_yaml_file_resource<R: YamlFileResource>(
+    mut resource: ResMut<R>,
+    launch_settings: Res<LaunchSettings>,
+) {
+    let _s = crate::span!(R::SPAN);
+    match R::load(&launch_settings.get_server_data_path()) {
+        Ok(r) => *resource = r,
+        Err(e) => {
+            log::error!(target: R::LOG_TARGET, "{}", e);
+            RuntimePlugin::stop();
+        }
+    }
+}
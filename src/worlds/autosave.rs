@@ -0,0 +1,39 @@
+use bevy_ecs::system::Res;
+
+use crate::runtime_plugin::RuntimePlugin;
+
+use super::worlds_manager::SharedWorldsManager;
+
+/// Starts saving chunks of all worlds changed since the last save
+///
+/// Chunks are written on the rayon thread pool; results are logged by `flush_autosave_worlds`.
+pub(crate) fn autosave_worlds(worlds_manager: Res<SharedWorldsManager>) {
+    let _s = crate::span!("worlds.autosave_worlds");
+    if RuntimePlugin::is_stopped() {
+        return;
+    }
+
+    for world in worlds_manager.read().iter_worlds() {
+        if !world.start_autosave() {
+            log::warn!(target: "worlds", "World &e\"{}\"&r autosave skipped; the previous one is still running", world.get_slug());
+        }
+    }
+}
+
+/// Logs results of finished autosaves
+pub(crate) fn flush_autosave_worlds(worlds_manager: Res<SharedWorldsManager>) {
+    let _s = crate::span!("worlds.flush_autosave_worlds");
+    for world in worlds_manager.read().iter_worlds() {
+        match world.poll_autosave() {
+            Some((Ok(count), elapsed)) => {
+                if count > 0 {
+                    log::info!(target: "worlds", "World &a\"{}\"&r autosaved; &8chunks:&7{} &8(executed:{:.2?})", world.get_slug(), count, elapsed);
+                }
+            }
+            Some((Err(e), _)) => {
+                log::error!(target: "worlds", "&cWorld \"{}\" autosave error: {}", world.get_slug(), e);
+            }
+            None => (),
+        }
+    }
+}
@@ -7,6 +7,7 @@ use core::fmt;
 use network::messages::ServerMessages;
 use parking_lot::RwLock;
 use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::{sync::Arc, time::Duration};
 
 pub struct ChunkColumn {
@@ -18,6 +19,15 @@ pub struct ChunkColumn {
 
     despawn_timer: Arc<RwLock<Duration>>,
     loaded: bool,
+
+    // Changed since the last save
+    dirty: AtomicBool,
+
+    // Replaces the chunk which failed to load; never saved
+    placeholder: bool,
+
+    // Nobody needs the chunk anymore; its loading result must be dropped
+    cancelled: AtomicBool,
 }
 
 impl Display for ChunkColumn {
@@ -40,6 +50,9 @@ impl ChunkColumn {
             chunk_position,
             world_slug,
             loaded: false,
+            dirty: AtomicBool::new(false),
+            placeholder: false,
+            cancelled: AtomicBool::new(false),
         }
     }
 
@@ -53,6 +66,16 @@ impl ChunkColumn {
         // log::info!(target: "set_sections", "chunk {} loaded", self.chunk_position);
     }
 
+    /// Loads the chunk with data which must not replace the stored one
+    pub(crate) fn set_placeholder(&mut self, chunk_storage: ChunkStorage) {
+        self.set_chunk_data(chunk_storage);
+        self.placeholder = true;
+    }
+
+    pub(crate) fn is_placeholder(&self) -> bool {
+        self.placeholder
+    }
+
     pub fn get_sections(&self) -> &ChunkData {
         &self.chunk_storage.as_ref().unwrap().get_chunk_data()
     }
@@ -82,6 +105,28 @@ impl ChunkColumn {
     ) {
         let chunk_data = self.chunk_storage.as_mut().unwrap().get_chunk_data_mut();
         chunk_data.change_block(section, &chunk_block, new_block_info);
+        self.mark_dirty();
+    }
+
+    /// Chunk must be written by the next save
+    pub fn mark_dirty(&self) {
+        if self.placeholder {
+            return;
+        }
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Clears the flag; returns true if the chunk was changed since the last save
+    pub(crate) fn take_dirty(&self) -> bool {
+        self.dirty.swap(false, Ordering::Relaxed)
+    }
+
+    pub(crate) fn cancel_loading(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub(crate) fn is_loading_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
     }
 
     pub(crate) fn is_for_despawn(&self, duration: Duration) -> bool {
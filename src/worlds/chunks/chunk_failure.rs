@@ -0,0 +1,125 @@
+use common::chunks::chunk_position::ChunkPosition;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+/// Delay before the first retry; doubled by every next attempt
+const RETRY_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(60);
+
+/// What to do with the chunk which can't be read or generated
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkFailurePolicy {
+    /// Load the chunk again with a growing delay; quarantine it after all attempts
+    #[default]
+    Retry,
+
+    /// Keep the chunk unloaded and never send it to players
+    Quarantine,
+
+    /// Replace the chunk with an empty one which is never saved
+    Void,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChunkFailureKind {
+    /// Storage can't be read
+    Read,
+
+    /// Stored data can't be decoded; it was moved aside
+    Corrupt,
+
+    /// World generator returned an error
+    Generate,
+}
+
+impl Display for ChunkFailureKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ChunkFailureKind::Read => "read",
+            ChunkFailureKind::Corrupt => "corrupt",
+            ChunkFailureKind::Generate => "generate",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChunkFailureState {
+    /// Waiting for the next attempt
+    Backoff(Instant),
+
+    /// Loading again
+    Retrying,
+
+    /// Not loaded until the quarantine is cleared
+    Quarantined,
+
+    /// Empty chunk is sent instead
+    Placeholder,
+}
+
+/// Failed chunk load; sent by the loading task and tracked by the ChunkMap
+pub struct ChunkFailure {
+    chunk_position: ChunkPosition,
+    kind: ChunkFailureKind,
+    error: String,
+    attempts: u32,
+    state: ChunkFailureState,
+}
+
+impl ChunkFailure {
+    pub fn new(chunk_position: ChunkPosition, kind: ChunkFailureKind, error: String) -> Self {
+        Self {
+            chunk_position,
+            kind,
+            error,
+            attempts: 1,
+            state: ChunkFailureState::Retrying,
+        }
+    }
+
+    pub fn get_chunk_position(&self) -> &ChunkPosition {
+        &self.chunk_position
+    }
+
+    pub fn get_kind(&self) -> ChunkFailureKind {
+        self.kind
+    }
+
+    pub fn get_error(&self) -> &String {
+        &self.error
+    }
+
+    pub fn get_attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn get_state(&self) -> ChunkFailureState {
+        self.state
+    }
+
+    pub(crate) fn set_state(&mut self, state: ChunkFailureState) {
+        self.state = state;
+    }
+
+    /// Takes the error of the new attempt and counts it
+    pub(crate) fn repeat(&mut self, failure: ChunkFailure) {
+        self.kind = failure.kind;
+        self.error = failure.error;
+        self.attempts += 1;
+    }
+
+    pub fn is_quarantined(&self) -> bool {
+        self.state == ChunkFailureState::Quarantined
+    }
+
+    /// Delay before the next attempt
+    pub(crate) fn get_backoff(&self) -> Duration {
+        let multiplier = 2_u32.saturating_pow(self.attempts.saturating_sub(1));
+        RETRY_BACKOFF.saturating_mul(multiplier).min(MAX_RETRY_BACKOFF)
+    }
+}
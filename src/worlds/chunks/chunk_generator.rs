@@ -1,69 +1,193 @@
 use parking_lot::RwLock;
-use std::sync::Arc;
+use serde::Deserialize;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
 
 use common::{
-    chunks::{chunk_data::ChunkData, chunk_position::ChunkPosition, chunk_storage::ChunkStorage},
+    chunks::{
+        block_position::{BlockPosition, BlockPositionTrait},
+        chunk_data::{ChunkData, ChunkSectionData},
+        chunk_position::ChunkPosition,
+        chunk_storage::ChunkStorage,
+    },
     plugin_api::events::generage_chunk::ChunkGenerateEvent,
     world_generator::traits::WorldGeneratorSettings,
     worlds_storage::taits::IWorldStorage,
+    VERTICAL_SECTIONS,
 };
 
-use super::{chunk_column::ChunkColumn, chunks_map::StorageLock};
+use super::{
+    chunk_column::ChunkColumn,
+    chunk_failure::{ChunkFailure, ChunkFailureKind},
+    chunks_map::StorageLock,
+};
 use crate::{plugins::server_plugin::plugin_instance::WASMPluginManager, runtime_plugin::RuntimePlugin};
 
+/// Decreases the number of loading chunks when the task is finished
+struct LoadingGuard(Arc<AtomicUsize>);
+
+impl Drop for LoadingGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
 pub(crate) fn load_chunk(
     plugin: Arc<WASMPluginManager>,
     world_generator_settings: WorldGeneratorSettings,
     storage: StorageLock,
-    chunk_position: ChunkPosition,
     chunk_column: Arc<RwLock<ChunkColumn>>,
     loaded_chunks: flume::Sender<ChunkPosition>,
+    failed_chunks: flume::Sender<ChunkFailure>,
+    loading: Arc<AtomicUsize>,
 ) {
+    loading.fetch_add(1, Ordering::Relaxed);
+    let guard = LoadingGuard(loading);
     rayon::spawn(move || {
+        let _guard = guard;
         if RuntimePlugin::is_stopped() {
             return;
         }
 
-        // Load from storage
-        let index = match storage.read().has_chunk_data(&chunk_position) {
-            Ok(i) => i,
-            Err(e) => {
-                log::error!(target: "worlds", "&cChunk load error!");
-                log::error!(target: "worlds", "Error: {}", e);
-                RuntimePlugin::stop();
+        let chunk_position = *chunk_column.read().get_chunk_position();
+        let result = read_or_generate_chunk(&plugin, world_generator_settings, &storage, &chunk_column);
+        let (chunk_storage, generated) = match result {
+            Ok(Some(c)) => c,
+            Ok(None) => return,
+            Err(failure) => {
+                // The world could be unloaded while the chunk was loading
+                let _ = failed_chunks.send(failure);
                 return;
             }
         };
 
-        let chunk_storage = if let Some(index) = index {
-            match storage.read().read_chunk_data(index) {
-                Ok(c) => c,
-                Err(e) => {
-                    log::error!(target: "worlds", "&cChunk load error!");
-                    log::error!(target: "worlds", "Error: {}", e);
-                    RuntimePlugin::stop();
-                    return;
-                }
-            }
-        }
-        // Or generate new
-        else {
-            let event = ChunkGenerateEvent::create(chunk_position, world_generator_settings);
-            let chunk_data: ChunkData = match plugin.call_event_with_result(&event) {
-                Ok(sections) => sections,
-                Err(e) => {
-                    log::error!(target: "worlds", "&4Chunk generation error: &c{}", e);
-                    RuntimePlugin::stop();
-                    return;
-                }
-            };
-            ChunkStorage::create(chunk_data)
-        };
         let mut chunk_column = chunk_column.write();
+        if chunk_column.is_loading_cancelled() {
+            return;
+        }
         chunk_column.set_chunk_data(chunk_storage);
+        if generated {
+            // New chunks are not in the storage yet
+            chunk_column.mark_dirty();
+        }
 
         if !cfg!(test) {
-            loaded_chunks.send(chunk_position).expect("channel poisoned");
+            // The world could be unloaded while the chunk was loading
+            let _ = loaded_chunks.send(chunk_position);
         }
     })
 }
+
+/// Returns the chunk and true if it was generated;
+/// None if the chunk is not needed anymore
+fn read_or_generate_chunk(
+    plugin: &WASMPluginManager,
+    world_generator_settings: WorldGeneratorSettings,
+    storage: &StorageLock,
+    chunk_column: &RwLock<ChunkColumn>,
+) -> Result<Option<(ChunkStorage, bool)>, ChunkFailure> {
+    let chunk_position = *chunk_column.read().get_chunk_position();
+    if chunk_column.read().is_loading_cancelled() {
+        return Ok(None);
+    }
+
+    // Load from storage
+    let index = match storage.read().has_chunk_data(&chunk_position) {
+        Ok(i) => i,
+        Err(e) => return Err(ChunkFailure::new(chunk_position, ChunkFailureKind::Read, e)),
+    };
+
+    if let Some(index) = index {
+        return match storage.read().read_chunk_data(index) {
+            Ok(c) => Ok(Some((c, false))),
+            Err(e) => {
+                // Keep the broken data for recovery; the next attempt will generate the chunk again
+                match storage.read().move_chunk_data_aside(&chunk_position) {
+                    Ok(()) => Err(ChunkFailure::new(chunk_position, ChunkFailureKind::Corrupt, e)),
+                    Err(move_error) => Err(ChunkFailure::new(
+                        chunk_position,
+                        ChunkFailureKind::Read,
+                        format!("{}; data can't be moved aside: {}", e, move_error),
+                    )),
+                }
+            }
+        };
+    }
+
+    // Or generate new if it's still needed
+    if chunk_column.read().is_loading_cancelled() {
+        return Ok(None);
+    }
+    match generate_chunk(plugin, world_generator_settings, chunk_position) {
+        Ok(c) => Ok(Some((c, true))),
+        Err(e) => Err(ChunkFailure::new(chunk_position, ChunkFailureKind::Generate, e)),
+    }
+}
+
+/// Empty chunk for the `void` chunk failure policy
+pub(crate) fn void_chunk() -> ChunkStorage {
+    let mut chunk_data = ChunkData::default();
+    for _ in 0..VERTICAL_SECTIONS {
+        chunk_data.push_section(ChunkSectionData::default());
+    }
+    ChunkStorage::create(chunk_data)
+}
+
+/// Block inventory created by the world generator; filled from the loot table when it's opened
+#[derive(Deserialize)]
+struct GeneratedInventory {
+    position: BlockPosition,
+    slots: usize,
+    loot_table: String,
+}
+
+/// World generator result; generators without inventories may return only the `ChunkData`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum GeneratedChunk {
+    WithInventories {
+        chunk_data: ChunkData,
+        inventories: Vec<GeneratedInventory>,
+    },
+    Data(ChunkData),
+}
+
+/// Calls the world generator of the plugin for the chunk
+pub(crate) fn generate_chunk(
+    plugin: &WASMPluginManager,
+    world_generator_settings: WorldGeneratorSettings,
+    chunk_position: ChunkPosition,
+) -> Result<ChunkStorage, String> {
+    let event = ChunkGenerateEvent::create(chunk_position, world_generator_settings);
+    let (chunk_data, inventories) = match plugin.call_event_with_result(&event)? {
+        GeneratedChunk::WithInventories {
+            chunk_data,
+            inventories,
+        } => (chunk_data, inventories),
+        GeneratedChunk::Data(chunk_data) => (chunk_data, Default::default()),
+    };
+
+    let mut chunk_storage = ChunkStorage::create(chunk_data);
+    for inventory in inventories {
+        if inventory.position.get_chunk_position() != chunk_position {
+            return Err(format!(
+                "inventory at {:?} is outside of the generated chunk",
+                inventory.position
+            ));
+        }
+        if inventory.slots == 0 {
+            return Err(format!("inventory at {:?} has no slots", inventory.position));
+        }
+        let (section, block_position) = inventory.position.get_block_position();
+        let block_inventory = chunk_storage.get_or_create_inventory_by_position_mut(
+            section,
+            block_position,
+            inventory.slots,
+            rand::random::<u64>(),
+        );
+        block_inventory.set_loot_table(Some(inventory.loot_table));
+    }
+    Ok(chunk_storage)
+}
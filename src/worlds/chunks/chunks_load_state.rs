@@ -1,14 +1,45 @@
 use ahash::AHashMap;
 use bevy::prelude::Entity;
 use common::{chunks::chunk_position::ChunkPosition, utils::vec_remove_item};
+use std::time::Duration;
+
+/// Reason to keep the chunk loaded
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ChunkTicket {
+    /// Player is watching the chunk
+    Player(Entity),
+
+    /// Spawn area of the world
+    Spawn,
+
+    /// Loaded by the `forceload` command
+    Forced,
+
+    /// Held by the plugin with slug; expires if it's not renewed
+    Plugin(String),
+}
+
+impl ChunkTicket {
+    pub fn get_type_name(&self) -> &'static str {
+        match self {
+            ChunkTicket::Player(_) => "player",
+            ChunkTicket::Spawn => "spawn",
+            ChunkTicket::Forced => "forced",
+            ChunkTicket::Plugin(_) => "plugin",
+        }
+    }
+}
 
 /// Idia was taken from
 /// https://github.com/feather-rs/feather
 /// feather/common/src/chunk/loading.rs
 #[derive(Default)]
 pub struct ChunksLoadState {
-    pub(crate) by_chunk: AHashMap<ChunkPosition, Vec<Entity>>,
+    by_chunk: AHashMap<ChunkPosition, Vec<Entity>>,
     by_entity: AHashMap<Entity, Vec<ChunkPosition>>,
+
+    /// Non-player tickets with the time left for expiring ones
+    other_tickets: AHashMap<ChunkPosition, AHashMap<ChunkTicket, Option<Duration>>>,
 }
 
 impl ChunksLoadState {
@@ -34,12 +65,98 @@ impl ChunksLoadState {
         self.by_entity.remove(&entity);
     }
 
-    /// The number of players who are watching this chunk
+    /// Keeps the chunk loaded by the ticket of any type.
+    /// Adding an existing ticket renews its ttl.
+    pub fn add_ticket(&mut self, chunk: ChunkPosition, ticket: ChunkTicket, ttl: Option<Duration>) {
+        match ticket {
+            ChunkTicket::Player(entity) => {
+                if !self.by_chunk.get(&chunk).is_some_and(|v| v.contains(&entity)) {
+                    self.insert_ticket(chunk, entity);
+                }
+            }
+            _ => {
+                self.other_tickets.entry(chunk).or_default().insert(ticket, ttl);
+            }
+        }
+    }
+
+    /// Returns false if there was no such ticket
+    pub fn release_ticket(&mut self, chunk: &ChunkPosition, ticket: &ChunkTicket) -> bool {
+        match ticket {
+            ChunkTicket::Player(entity) => {
+                if !self.by_chunk.get(chunk).is_some_and(|v| v.contains(entity)) {
+                    return false;
+                }
+                self.remove_ticket(chunk, entity);
+                true
+            }
+            _ => {
+                let Some(tickets) = self.other_tickets.get_mut(chunk) else {
+                    return false;
+                };
+                let removed = tickets.remove(ticket).is_some();
+                if tickets.is_empty() {
+                    self.other_tickets.remove(chunk);
+                }
+                removed
+            }
+        }
+    }
+
+    /// Removes the non-player ticket from all chunks
+    pub fn release_all_tickets(&mut self, ticket: &ChunkTicket) {
+        self.other_tickets.retain(|_chunk, tickets| {
+            tickets.remove(ticket);
+            !tickets.is_empty()
+        });
+    }
+
+    /// Decreases ttl of expiring tickets and removes expired ones
+    pub fn update_tickets_ttl(&mut self, delta: Duration) {
+        self.other_tickets.retain(|_chunk, tickets| {
+            tickets.retain(|_ticket, ttl| match ttl {
+                Some(left) => {
+                    *left = left.saturating_sub(delta);
+                    !left.is_zero()
+                }
+                None => true,
+            });
+            !tickets.is_empty()
+        });
+    }
+
+    /// Non-player tickets of all chunks
+    pub fn iter_other_tickets(&self) -> impl Iterator<Item = (&ChunkPosition, &ChunkTicket, &Option<Duration>)> {
+        self.other_tickets
+            .iter()
+            .flat_map(|(chunk, tickets)| tickets.iter().map(move |(ticket, ttl)| (chunk, ticket, ttl)))
+    }
+
+    /// Chunks which has at least one ticket of any type
+    pub fn iter_requested_chunks(&self) -> impl Iterator<Item = &ChunkPosition> {
+        let by_players = self
+            .by_chunk
+            .iter()
+            .filter(|(_chunk, entities)| entities.len() > 0)
+            .map(|(chunk, _entities)| chunk);
+        let by_others = self
+            .other_tickets
+            .keys()
+            .filter(|chunk| self.by_chunk.get(chunk).map_or(true, |entities| entities.is_empty()));
+        by_players.chain(by_others)
+    }
+
+    /// The number of tickets of all types for this chunk
     pub fn num_tickets(&self, chunk: &ChunkPosition) -> usize {
-        match self.by_chunk.get(chunk) {
+        let players = match self.by_chunk.get(chunk) {
             Some(vec) => vec.len(),
             None => 0,
-        }
+        };
+        let others = match self.other_tickets.get(chunk) {
+            Some(tickets) => tickets.len(),
+            None => 0,
+        };
+        players + others
     }
 
     /// Returns all entities that wathing the chunk
@@ -58,3 +175,42 @@ impl ChunksLoadState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ChunkTicket, ChunksLoadState};
+    use bevy::prelude::Entity;
+    use common::chunks::chunk_position::ChunkPosition;
+    use std::time::Duration;
+
+    #[test]
+    fn test_other_tickets() {
+        let mut state = ChunksLoadState::default();
+        let chunk = ChunkPosition::new(2, 3);
+        let entity = Entity::from_raw_u32(0).unwrap();
+        let plugin = ChunkTicket::Plugin("machines".to_string());
+
+        state.add_ticket(chunk, ChunkTicket::Player(entity), None);
+        state.add_ticket(chunk, ChunkTicket::Forced, None);
+        state.add_ticket(chunk, plugin.clone(), Some(Duration::from_secs(2)));
+        assert_eq!(state.num_tickets(&chunk), 3);
+        assert_eq!(state.iter_requested_chunks().count(), 1);
+
+        // Renew and expire
+        state.update_tickets_ttl(Duration::from_secs(1));
+        state.add_ticket(chunk, plugin.clone(), Some(Duration::from_secs(2)));
+        state.update_tickets_ttl(Duration::from_secs(1));
+        assert_eq!(state.num_tickets(&chunk), 3);
+        state.update_tickets_ttl(Duration::from_secs(1));
+        assert_eq!(state.num_tickets(&chunk), 2);
+
+        state.remove_all_entity_tickets(&entity);
+        assert_eq!(state.num_tickets(&chunk), 1);
+        assert_eq!(state.iter_requested_chunks().collect::<Vec<_>>(), vec![&chunk]);
+
+        assert!(state.release_ticket(&chunk, &ChunkTicket::Forced));
+        assert!(!state.release_ticket(&chunk, &ChunkTicket::Forced));
+        assert_eq!(state.num_tickets(&chunk), 0);
+        assert_eq!(state.iter_requested_chunks().count(), 0);
+    }
+}
@@ -1,7 +1,6 @@
 use crate::{
     inventory::inventory_manager::InventoryManager, plugins::server_plugin::plugin_instance::WASMPluginManager,
     plugins::server_settings::ServerSettings, runtime_plugin::RuntimePlugin, worlds::world_manager::ChunkChanged,
-    CHUNKS_DESPAWN_TIMER,
 };
 use ahash::AHashMap;
 use bevy::prelude::Entity;
@@ -10,16 +9,16 @@ use common::{
         block_position::{BlockPosition, BlockPositionTrait},
         chunk_data::BlockDataInfo,
         chunk_position::ChunkPosition,
-        position::Vector3,
+        chunk_storage::{ChunkStorage, DroppedItemData},
     },
-    utils::{block_raycast::RayBlockIter, spiral_iterator::SpiralIterator, vec_remove_item},
+    utils::{spiral_iterator::SpiralIterator, vec_remove_item},
     world_generator::traits::WorldGeneratorSettings,
-    worlds_storage::taits::IWorldStorage,
+    worlds_storage::taits::{IWorldStorage, WorldStorageData},
     WorldStorageManager, VERTICAL_SECTIONS,
 };
-use parking_lot::RwLock;
 #[cfg(test)]
 use parking_lot::RwLockReadGuard;
+use parking_lot::{Mutex, RwLock};
 use rayon::prelude::*;
 use std::{
     sync::atomic::{AtomicUsize, Ordering},
@@ -27,10 +26,25 @@ use std::{
     time::{Duration, Instant},
 };
 
-use super::{chunk_column::ChunkColumn, chunks_load_state::ChunksLoadState};
+use super::{
+    chunk_column::ChunkColumn,
+    chunk_failure::{ChunkFailure, ChunkFailurePolicy, ChunkFailureState},
+    chunk_generator::void_chunk,
+    chunks_load_state::{ChunkTicket, ChunksLoadState},
+};
 
 const MAX_DESPAWN_DURATION: Duration = Duration::from_millis(2);
 
+/// Free blocks required above the ground for a player to stand
+const SPAWN_HEADROOM: usize = 2;
+
+enum SpawnBlockKind {
+    Unloaded,
+    Empty,
+    Sensor,
+    Solid,
+}
+
 pub type MapChunksType = AHashMap<ChunkPosition, Arc<RwLock<ChunkColumn>>>;
 
 #[cfg(test)]
@@ -38,6 +52,35 @@ pub type ChunkSectionType<'a> = RwLockReadGuard<'a, ChunkColumn>;
 
 pub type StorageLock = Arc<RwLock<WorldStorageManager>>;
 
+/// Despawned chunks which failed to save
+type UnsavedChunks = Arc<Mutex<AHashMap<ChunkPosition, ChunkStorage>>>;
+
+/// Save of changed chunks running on the rayon thread pool
+struct BackgroundSave {
+    started: Instant,
+    receiver: flume::Receiver<Result<usize, String>>,
+}
+
+impl BackgroundSave {
+    fn wait(self) -> Result<usize, String> {
+        match self.receiver.recv() {
+            Ok(result) => result,
+            Err(_) => Err("chunks save task stopped".to_string()),
+        }
+    }
+}
+
+/// Server settings of chunks loading and despawn
+pub struct ChunksUpdateSettings {
+    /// Time before the chunk nobody is watching is despawned
+    pub despawn_timer: Duration,
+    pub failure_policy: ChunkFailurePolicy,
+    pub max_retries: u32,
+
+    /// Chunks loading or generating at once
+    pub max_loads: usize,
+}
+
 /// Container of 2d ChunkColumn's.
 /// This container manages vision of the chunks
 /// and responsible for load/unload chunks
@@ -50,9 +93,26 @@ pub struct ChunkMap {
     // A channel for tracking successfully uploaded chunks.
     loaded_chunks: (flume::Sender<ChunkPosition>, flume::Receiver<ChunkPosition>),
 
+    // A channel for chunks which failed to load
+    failed_chunks: (flume::Sender<ChunkFailure>, flume::Receiver<ChunkFailure>),
+
+    // Chunks retrying, quarantined or replaced by placeholders
+    failures: AHashMap<ChunkPosition, ChunkFailure>,
+
+    // Chunks loading or generating right now
+    loading: Arc<AtomicUsize>,
+
+    // Center chunk of each player for the loading order
+    watchers_centers: AHashMap<Entity, ChunkPosition>,
+
     world_generator_settings: WorldGeneratorSettings,
 
     storage: StorageLock,
+
+    background_save: Mutex<Option<BackgroundSave>>,
+
+    // Written again with the next save; the chunk is restored from here instead of loading
+    unsaved_chunks: UnsavedChunks,
 }
 
 #[cfg(test)]
@@ -72,8 +132,14 @@ impl Default for ChunkMap {
             chunks: Default::default(),
             chunks_load_state: Default::default(),
             loaded_chunks: (tx, rx),
+            failed_chunks: flume::unbounded(),
+            failures: Default::default(),
+            loading: Default::default(),
+            watchers_centers: Default::default(),
             world_generator_settings: Default::default(),
             storage: Arc::new(RwLock::new(storage)),
+            background_save: Default::default(),
+            unsaved_chunks: Default::default(),
         }
     }
 }
@@ -84,8 +150,14 @@ impl ChunkMap {
             chunks: Default::default(),
             chunks_load_state: Default::default(),
             loaded_chunks: flume::unbounded(),
+            failed_chunks: flume::unbounded(),
+            failures: Default::default(),
+            loading: Default::default(),
+            watchers_centers: Default::default(),
             world_generator_settings,
             storage: Arc::new(RwLock::new(world_storage)),
+            background_save: Default::default(),
+            unsaved_chunks: Default::default(),
         }
     }
 
@@ -93,6 +165,10 @@ impl ChunkMap {
         &self.world_generator_settings
     }
 
+    pub(crate) fn get_storage(&self) -> StorageLock {
+        self.storage.clone()
+    }
+
     pub fn drain_loaded_chunks(&self) -> flume::Drain<'_, ChunkPosition> {
         self.loaded_chunks.1.drain()
     }
@@ -120,10 +196,35 @@ impl ChunkMap {
         }
     }
 
+    #[cfg(test)]
+    pub fn insert_loaded_chunk(
+        &mut self,
+        world_slug: &String,
+        chunk_position: ChunkPosition,
+        chunk_storage: common::chunks::chunk_storage::ChunkStorage,
+    ) {
+        let mut chunk_column = ChunkColumn::new(chunk_position, world_slug.clone());
+        chunk_column.set_chunk_data(chunk_storage);
+        self.chunks.insert(chunk_position, Arc::new(RwLock::new(chunk_column)));
+    }
+
     pub fn get_chunk_column_arc(&self, chunk_position: &ChunkPosition) -> Option<Arc<RwLock<ChunkColumn>>> {
         self.chunks.get(chunk_position).cloned()
     }
 
+    pub fn iter_failures(&self) -> impl Iterator<Item = &ChunkFailure> {
+        self.failures.values()
+    }
+
+    /// Lets quarantined chunks load again
+    ///
+    /// Returns the number of released chunks
+    pub fn clear_quarantine(&mut self) -> usize {
+        let count = self.failures.len();
+        self.failures.retain(|_chunk, failure| !failure.is_quarantined());
+        count - self.failures.len()
+    }
+
     /// Get all chunks watching by the player
     pub fn get_watching_chunks(&self, entity: &Entity) -> Option<&Vec<ChunkPosition>> {
         self.chunks_load_state.get_watching_chunks(entity)
@@ -138,15 +239,45 @@ impl ChunkMap {
 
     /// Create player in the world
     pub fn start_chunks_render(&mut self, entity: Entity, to: &ChunkPosition, chunks_distance: u16) {
+        self.watchers_centers.insert(entity, *to);
         let iter = SpiralIterator::new(to.x as i64, to.z as i64, chunks_distance as i64);
         for (x, z) in iter {
-            let chunk_pos = ChunkPosition::new(x, z);
-            self.chunks_load_state.insert_ticket(chunk_pos, entity.clone());
+            self.add_ticket(ChunkPosition::new(x, z), ChunkTicket::Player(entity), None);
+        }
+    }
 
-            // Update despawn timer
-            if let Some(chunk_column) = self.chunks.get_mut(&chunk_pos) {
-                chunk_column.read().set_despawn_timer(Duration::ZERO);
-            }
+    /// Keeps the chunk loaded until the ticket is released or its ttl is expired
+    pub fn add_ticket(&mut self, chunk_position: ChunkPosition, ticket: ChunkTicket, ttl: Option<Duration>) {
+        self.chunks_load_state.add_ticket(chunk_position, ticket, ttl);
+
+        // Update despawn timer
+        if let Some(chunk_column) = self.chunks.get_mut(&chunk_position) {
+            chunk_column.read().set_despawn_timer(Duration::ZERO);
+        }
+    }
+
+    /// Returns false if there was no such ticket
+    pub fn release_ticket(&mut self, chunk_position: &ChunkPosition, ticket: &ChunkTicket) -> bool {
+        self.chunks_load_state.release_ticket(chunk_position, ticket)
+    }
+
+    pub fn release_all_tickets(&mut self, ticket: &ChunkTicket) {
+        self.chunks_load_state.release_all_tickets(ticket);
+    }
+
+    /// Spawn, forced and plugin tickets with the time left for expiring ones
+    pub fn iter_other_tickets(&self) -> impl Iterator<Item = (&ChunkPosition, &ChunkTicket, &Option<Duration>)> {
+        self.chunks_load_state.iter_other_tickets()
+    }
+
+    /// Keeps chunks around the world spawn loaded; zero radius releases them
+    pub fn set_spawn_area(&mut self, center: &ChunkPosition, radius: u16) {
+        self.release_all_tickets(&ChunkTicket::Spawn);
+        if radius == 0 {
+            return;
+        }
+        for (x, z) in SpiralIterator::new(center.x, center.z, radius as i64) {
+            self.add_ticket(ChunkPosition::new(x, z), ChunkTicket::Spawn, None);
         }
     }
 
@@ -190,6 +321,44 @@ impl ChunkMap {
             panic!("update_chunks_render from and to must be different chunks positions");
         }
 
+        let (old, new) = self.retarget_chunks_render(entity, to, chunks_distance);
+        ChunkChanged {
+            old_chunk: from.clone(),
+            new_chunk: to.clone(),
+            abandoned_chunks: old,
+            new_chunks: new,
+        }
+    }
+
+    /// Triggered when the player view distance is changed
+    /// without moving to another chunk
+    ///
+    /// Returns unwatched and new chunks
+    pub fn resize_chunks_render(
+        &mut self,
+        entity: Entity,
+        center: &ChunkPosition,
+        chunks_distance: u16,
+    ) -> ChunkChanged {
+        let (old, new) = self.retarget_chunks_render(entity, center, chunks_distance);
+        ChunkChanged {
+            old_chunk: center.clone(),
+            new_chunk: center.clone(),
+            abandoned_chunks: old,
+            new_chunks: new,
+        }
+    }
+
+    /// Moves the entity tickets to the square around the center
+    ///
+    /// Returns (abandoned, new) chunks
+    fn retarget_chunks_render(
+        &mut self,
+        entity: Entity,
+        to: &ChunkPosition,
+        chunks_distance: u16,
+    ) -> (Vec<ChunkPosition>, Vec<ChunkPosition>) {
+        self.watchers_centers.insert(entity, *to);
         let mut old = self.chunks_load_state.get_watching_chunks(&entity).unwrap().clone();
         let mut new: Vec<ChunkPosition> = Default::default();
 
@@ -219,27 +388,31 @@ impl ChunkMap {
             self.chunks_load_state.remove_ticket(&chunk, &entity);
         }
 
-        ChunkChanged {
-            old_chunk: from.clone(),
-            new_chunk: to.clone(),
-            abandoned_chunks: old,
-            new_chunks: new,
-        }
+        (old, new)
     }
 
     /// Player stop watch the world (despawn or move to another world)
     pub fn stop_chunks_render(&mut self, entity: Entity) {
+        self.watchers_centers.remove(&entity);
         self.chunks_load_state.remove_all_entity_tickets(&entity);
     }
 
     /// Update chunks: load or despawn
+    ///
+    /// `take_dropped_items` removes dropped items of the despawned chunk from the world;
+    /// they are saved together with the chunk
     pub fn update_chunks_state(
         &mut self,
         delta: Duration,
         world_slug: &String,
         wasm_plugin_manager: Arc<WASMPluginManager>,
         inventory_manager: &mut InventoryManager,
+        settings: &ChunksUpdateSettings,
+        mut take_dropped_items: impl FnMut(&ChunkPosition) -> Vec<DroppedItemData>,
     ) {
+        self.chunks_load_state.update_tickets_ttl(delta);
+        self.update_failures(world_slug, settings.failure_policy, settings.max_retries);
+
         // Update chunks despawn timer
         // Increase ONLY of noone looking at the chunk
         for (&chunk, chunk_column) in self.chunks.iter_mut() {
@@ -257,24 +430,45 @@ impl ChunkMap {
 
             let chunk_column = chunk_column.read();
 
-            // Skip loading chunk
+            // Loading chunk is dropped as soon as nobody needs it
             if !chunk_column.is_loaded() {
-                return true;
+                if self.chunks_load_state.num_tickets(&chunk) > 0 {
+                    return true;
+                }
+                log::trace!(target: "chunks", "Chunk {} loading cancelled", chunk);
+                chunk_column.cancel_loading();
+                return false;
             }
 
-            if !chunk_column.is_for_despawn(CHUNKS_DESPAWN_TIMER) {
+            if !chunk_column.is_for_despawn(settings.despawn_timer) {
                 return true;
             }
 
             log::trace!(target: "chunks", "Chunk {} despawned", chunk);
 
-            let chunk_storage = chunk_column.get_chunk_storage().clone();
-            let chunk_position = *chunk_column.get_chunk_position();
-            let storage = self.storage.clone();
+            // Placeholder has nothing to save; the chunk will be loaded again next time
+            if chunk_column.is_placeholder() {
+                return false;
+            }
+
+            let dropped_items = take_dropped_items(&chunk);
 
             inventory_manager
                 .state_mut()
-                .unregister_chunk_inventories(&chunk_storage);
+                .unregister_chunk_inventories(chunk_column.get_chunk_storage());
+
+            // Nothing changed since the last save
+            let has_dropped_items =
+                !dropped_items.is_empty() || !chunk_column.get_chunk_storage().get_dropped_items().is_empty();
+            if !chunk_column.take_dirty() && !has_dropped_items {
+                return false;
+            }
+
+            let mut chunk_storage = chunk_column.get_chunk_storage().clone();
+            chunk_storage.set_dropped_items(dropped_items);
+            let chunk_position = *chunk_column.get_chunk_position();
+            let storage = self.storage.clone();
+            let unsaved_chunks = self.unsaved_chunks.clone();
 
             rayon::spawn(move || {
                 if RuntimePlugin::is_stopped() {
@@ -282,42 +476,161 @@ impl ChunkMap {
                 }
 
                 if let Err(e) = storage.read().save_chunk_data(&chunk_position, &chunk_storage) {
-                    log::error!(target: "worlds", "&cChunk save error!");
-                    log::error!(target: "worlds", "&cError: {}", e);
-                    RuntimePlugin::stop();
+                    log::error!(target: "worlds", "&cChunk &4{}&c save error: {}", chunk_position, e);
+                    unsaved_chunks.lock().insert(chunk_position, chunk_storage);
                 }
             });
 
             false
         });
+        self.failures.retain(|chunk, failure| {
+            failure.get_state() != ChunkFailureState::Placeholder || self.chunks.contains_key(chunk)
+        });
+
+        // Retries share the loads budget and go before new chunks
+        let free_loads = settings.max_loads.saturating_sub(self.loading.load(Ordering::Relaxed));
+        let free_loads = free_loads - self.retry_failed_chunks(&wasm_plugin_manager, free_loads);
 
-        // Send to load new chunks
-        for (chunk_position, players) in self.chunks_load_state.by_chunk.iter() {
-            if players.len() == 0 {
+        // Send to load new chunks, the nearest to players first
+        if free_loads == 0 {
+            return;
+        }
+        let mut queue: Vec<((bool, i64), ChunkPosition)> = self
+            .chunks_load_state
+            .iter_requested_chunks()
+            .filter(|chunk| !self.chunks.contains_key(*chunk))
+            .filter(|chunk| !self.failures.get(*chunk).is_some_and(|f| f.is_quarantined()))
+            .map(|chunk| (self.get_load_priority(chunk), *chunk))
+            .collect();
+        queue.sort_unstable_by_key(|(priority, _chunk)| *priority);
+        queue.truncate(free_loads);
+
+        for (_priority, chunk_position) in queue {
+            let chunk_column = Arc::new(RwLock::new(ChunkColumn::new(
+                chunk_position.clone(),
+                world_slug.clone(),
+            )));
+
+            // Storage has older data of the chunk which failed to save
+            let unsaved = self.unsaved_chunks.lock().remove(&chunk_position);
+            if let Some(chunk_storage) = unsaved {
+                log::trace!(target: "chunks", "Chunk {} restored from unsaved", chunk_position);
+                chunk_column.write().set_chunk_data(chunk_storage);
+                chunk_column.read().mark_dirty();
+                let _ = self.loaded_chunks.0.send(chunk_position);
+                self.chunks.insert(chunk_position, chunk_column);
                 continue;
             }
 
-            if !self.chunks.contains_key(&chunk_position) {
-                let chunk_column = Arc::new(RwLock::new(ChunkColumn::new(
-                    chunk_position.clone(),
-                    world_slug.clone(),
-                )));
-
-                log::trace!(target: "chunks", "Send chunk {} to load", chunk_position);
-                if !cfg!(test) {
-                    use crate::worlds::chunks::chunk_generator::load_chunk;
-                    load_chunk(
-                        wasm_plugin_manager.clone(),
-                        self.world_generator_settings.clone(),
-                        self.storage.clone(),
-                        chunk_position.clone(),
-                        chunk_column.clone(),
-                        self.loaded_chunks.0.clone(),
-                    );
+            log::trace!(target: "chunks", "Send chunk {} to load", chunk_position);
+            self.spawn_load_chunk(&wasm_plugin_manager, chunk_column.clone());
+            self.chunks.insert(chunk_position, chunk_column);
+        }
+    }
+
+    /// Chunks watched by players go first, closer to any of them is earlier;
+    /// chunks kept by other tickets go after them
+    fn get_load_priority(&self, chunk: &ChunkPosition) -> (bool, i64) {
+        let nearest = self
+            .chunks_load_state
+            .get_chunk_watchers(chunk)
+            .into_iter()
+            .flatten()
+            .filter_map(|entity| self.watchers_centers.get(entity))
+            .map(|center| (center.x - chunk.x).pow(2) + (center.z - chunk.z).pow(2))
+            .min();
+        match nearest {
+            Some(distance) => (false, distance),
+            None => (true, 0),
+        }
+    }
+
+    fn spawn_load_chunk(&self, wasm_plugin_manager: &Arc<WASMPluginManager>, chunk_column: Arc<RwLock<ChunkColumn>>) {
+        if cfg!(test) {
+            return;
+        }
+        use crate::worlds::chunks::chunk_generator::load_chunk;
+        load_chunk(
+            wasm_plugin_manager.clone(),
+            self.world_generator_settings.clone(),
+            self.storage.clone(),
+            chunk_column,
+            self.loaded_chunks.0.clone(),
+            self.failed_chunks.0.clone(),
+            self.loading.clone(),
+        );
+    }
+
+    /// Applies the failure policy to chunks which failed to load
+    fn update_failures(&mut self, world_slug: &String, failure_policy: ChunkFailurePolicy, max_retries: u32) {
+        for failure in self.failed_chunks.1.drain() {
+            let chunk_position = *failure.get_chunk_position();
+            let mut failure = match self.failures.remove(&chunk_position) {
+                Some(mut previous) => {
+                    previous.repeat(failure);
+                    previous
+                }
+                None => failure,
+            };
+            log::error!(
+                target: "chunks",
+                "&cChunk &4{}&c of the world &4\"{}\"&c {} error &8(attempt:{})&c: {}",
+                chunk_position, world_slug, failure.get_kind(), failure.get_attempts(), failure.get_error()
+            );
+
+            match failure_policy {
+                ChunkFailurePolicy::Void => {
+                    if let Some(chunk_column) = self.chunks.get(&chunk_position) {
+                        chunk_column.write().set_placeholder(void_chunk());
+                        let _ = self.loaded_chunks.0.send(chunk_position);
+                    }
+                    failure.set_state(ChunkFailureState::Placeholder);
+                }
+                ChunkFailurePolicy::Retry if failure.get_attempts() <= max_retries => {
+                    failure.set_state(ChunkFailureState::Backoff(Instant::now() + failure.get_backoff()));
+                }
+                _ => {
+                    log::warn!(target: "chunks", "Chunk &e{}&r of the world &e\"{}\"&r is quarantined", chunk_position, world_slug);
+                    self.chunks.remove(&chunk_position);
+                    failure.set_state(ChunkFailureState::Quarantined);
+                }
+            }
+            self.failures.insert(chunk_position, failure);
+        }
+    }
+
+    /// Starts the next attempts of failed chunks, no more than `max_loads`
+    ///
+    /// Returns the number of started attempts
+    fn retry_failed_chunks(&mut self, wasm_plugin_manager: &Arc<WASMPluginManager>, max_loads: usize) -> usize {
+        let now = Instant::now();
+        let mut retry: Vec<Arc<RwLock<ChunkColumn>>> = Default::default();
+        self.failures.retain(|chunk_position, failure| {
+            let Some(chunk_column) = self.chunks.get(chunk_position) else {
+                return failure.is_quarantined();
+            };
+            match failure.get_state() {
+                ChunkFailureState::Retrying => {
+                    if chunk_column.read().is_loaded() {
+                        log::info!(target: "chunks", "Chunk &a{}&r loaded after &e{}&r failed attempts", chunk_position, failure.get_attempts());
+                        return false;
+                    }
+                }
+                ChunkFailureState::Backoff(retry_at) => {
+                    if retry_at <= now && retry.len() < max_loads {
+                        failure.set_state(ChunkFailureState::Retrying);
+                        retry.push(chunk_column.clone());
+                    }
                 }
-                self.chunks.insert(chunk_position.clone(), chunk_column);
+                ChunkFailureState::Quarantined | ChunkFailureState::Placeholder => (),
             }
+            true
+        });
+        let count = retry.len();
+        for chunk_column in retry {
+            self.spawn_load_chunk(wasm_plugin_manager, chunk_column);
         }
+        count
     }
 
     pub fn edit_block(&self, position: BlockPosition, new_block_info: Option<BlockDataInfo>) -> Result<(), String> {
@@ -329,8 +642,11 @@ impl ChunkMap {
         };
 
         let (section, block_position) = position.get_block_position();
-        if section > VERTICAL_SECTIONS as u32 {
-            return Err(format!("edit_block section {} is more than", VERTICAL_SECTIONS));
+        if section >= VERTICAL_SECTIONS as u32 {
+            return Err(format!(
+                "edit_block section {} is out of {} sections",
+                section, VERTICAL_SECTIONS
+            ));
         }
         chunk_column
             .write()
@@ -338,73 +654,307 @@ impl ChunkMap {
         return Ok(());
     }
 
+    /// Writes chunks changed since the last save on the rayon pool
+    ///
+    /// Returns the number of written chunks
     pub fn save(&self) -> Result<usize, String> {
-        let storage = Arc::clone(&self.storage);
-        let saved_chunks = AtomicUsize::new(0);
+        // Chunks of the running background save are already taken; failed ones must be marked again first
+        let mut results: Vec<_> = self
+            .background_save
+            .lock()
+            .take()
+            .map(|s| s.wait())
+            .into_iter()
+            .collect();
+        results.push(save_chunks(
+            &self.storage,
+            &self.take_dirty_chunks(),
+            &self.unsaved_chunks,
+        ));
+        results.push(save_unsaved_chunks(
+            &self.storage,
+            self.take_unsaved_chunks(),
+            &self.unsaved_chunks,
+        ));
+        merge_save_results(results)
+    }
 
-        self.chunks.iter().par_bridge().try_for_each(|(_, chunk_column)| {
-            let chunk_column = chunk_column.read();
-            storage
-                .read()
-                .save_chunk_data(chunk_column.get_chunk_position(), chunk_column.get_chunk_storage())?;
-            saved_chunks.fetch_add(1, Ordering::Relaxed);
-            Ok::<(), String>(())
-        })?;
+    /// Saves changed chunks on the rayon thread pool so the tick is not blocked
+    ///
+    /// Returns false if the previous background save is not finished yet
+    pub fn start_background_save(&self) -> bool {
+        let mut background_save = self.background_save.lock();
+        if background_save.is_some() {
+            return false;
+        }
 
-        Ok(saved_chunks.load(Ordering::Relaxed))
+        let chunks = self.take_dirty_chunks();
+        let unsaved = self.take_unsaved_chunks();
+        let storage = self.storage.clone();
+        let unsaved_chunks = self.unsaved_chunks.clone();
+        let (sender, receiver) = flume::bounded(1);
+        rayon::spawn(move || {
+            let result = merge_save_results([
+                save_chunks(&storage, &chunks, &unsaved_chunks),
+                save_unsaved_chunks(&storage, unsaved, &unsaved_chunks),
+            ]);
+            let _ = sender.send(result);
+        });
+        *background_save = Some(BackgroundSave {
+            started: Instant::now(),
+            receiver,
+        });
+        true
     }
 
-    pub fn first_solid_block(
-        &self,
-        origin: Vector3,
-        dir: Vector3,
-        max_dist: f32,
-        server_settings: &ServerSettings,
-    ) -> Option<BlockPosition> {
-        for step in RayBlockIter::new(origin, dir, max_dist) {
-            let chunk_position = step.pos.get_chunk_position();
-            let Some(chunk_column) = self.chunks.get(&chunk_position) else {
+    /// Result of the finished background save and its duration
+    pub fn poll_background_save(&self) -> Option<(Result<usize, String>, Duration)> {
+        let mut background_save = self.background_save.lock();
+        let result = match background_save.as_ref()?.receiver.try_recv() {
+            Ok(result) => result,
+            Err(flume::TryRecvError::Empty) => return None,
+            Err(flume::TryRecvError::Disconnected) => Err("chunks save task stopped".to_string()),
+        };
+        let started = background_save.take().unwrap().started;
+        Some((result, started.elapsed()))
+    }
+
+    fn take_dirty_chunks(&self) -> Vec<Arc<RwLock<ChunkColumn>>> {
+        self.chunks
+            .values()
+            .filter(|chunk_column| {
+                let chunk_column = chunk_column.read();
+                chunk_column.is_loaded() && chunk_column.take_dirty()
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Chunks loaded again keep newer data and are saved as dirty
+    fn take_unsaved_chunks(&self) -> Vec<(ChunkPosition, ChunkStorage)> {
+        let mut unsaved = self.unsaved_chunks.lock();
+        unsaved.retain(|chunk_position, _| {
+            !self
+                .chunks
+                .get(chunk_position)
+                .is_some_and(|chunk_column| chunk_column.read().is_loaded())
+        });
+        unsaved.drain().collect()
+    }
+
+    /// Writes dropped items of loaded chunks into their storage so they are saved with chunks
+    pub fn store_dropped_items(&self, mut dropped_items: AHashMap<ChunkPosition, Vec<DroppedItemData>>) {
+        for (chunk_position, chunk_column) in self.chunks.iter() {
+            let items = dropped_items.remove(chunk_position).unwrap_or_default();
+            let mut chunk_column = chunk_column.write();
+            if !chunk_column.is_loaded() || chunk_column.is_placeholder() {
                 continue;
-            };
+            }
+            if items.is_empty() && chunk_column.get_chunk_storage().get_dropped_items().is_empty() {
+                continue;
+            }
+            chunk_column.get_chunk_storage_mut().set_dropped_items(items);
+            chunk_column.mark_dirty();
+        }
+    }
 
+    /// Forgets inventories of all chunks before the map is dropped
+    pub fn unregister_inventories(&self, inventory_manager: &mut InventoryManager) {
+        for chunk_column in self.chunks.values() {
             let chunk_column = chunk_column.read();
             if !chunk_column.is_loaded() {
                 continue;
             }
+            inventory_manager
+                .state_mut()
+                .unregister_chunk_inventories(chunk_column.get_chunk_storage());
+        }
+    }
 
-            let Some(block_info) = chunk_column
-                .get_chunk_storage()
-                .get_chunk_data()
-                .get_block_info(&step.pos)
-            else {
-                continue;
-            };
+    pub fn get_storage_size(&self) -> Result<u64, String> {
+        self.storage.read().get_storage_size()
+    }
 
-            let Some(block_type) = server_settings.get_block_type_by_id(block_info.get_id()) else {
-                panic!(
-                    "first_solid_block: block id {} is not found in server settings",
-                    block_info.get_id()
-                );
-            };
+    pub fn save_world_data(&self, world_data: &WorldStorageData) -> Result<(), String> {
+        self.storage.read().update_world_data(world_data)
+    }
 
-            if !block_type.get_collider_type().is_sensor() {
-                return Some(step.pos);
+    /// Looks for a place where a player can stand: a solid block with enough free space above.
+    ///
+    /// Columns are checked in a spiral around the origin, each one from the origin height down.
+    /// Columns with sensor blocks (liquids) on the way down are skipped.
+    pub fn find_safe_spawn(
+        &self,
+        origin: &BlockPosition,
+        radius: i64,
+        server_settings: &ServerSettings,
+    ) -> Option<BlockPosition> {
+        for (x, z) in SpiralIterator::new(origin.x, origin.z, radius) {
+            if let Some(position) = self.find_column_ground(x, origin.y, z, server_settings) {
+                return Some(position);
             }
         }
         None
     }
+
+    fn find_column_ground(&self, x: i64, top: i64, z: i64, server_settings: &ServerSettings) -> Option<BlockPosition> {
+        // Blocks above the origin height count toward the headroom of the top block
+        let mut free_above = (1..=SPAWN_HEADROOM as i64)
+            .take_while(|dy| {
+                let position = BlockPosition::new(x, top + dy, z);
+                matches!(
+                    self.get_spawn_block_kind(&position, server_settings),
+                    SpawnBlockKind::Empty
+                )
+            })
+            .count();
+        for y in (0..=top).rev() {
+            let position = BlockPosition::new(x, y, z);
+            match self.get_spawn_block_kind(&position, server_settings) {
+                SpawnBlockKind::Unloaded | SpawnBlockKind::Sensor => return None,
+                SpawnBlockKind::Empty => free_above += 1,
+                SpawnBlockKind::Solid => {
+                    if free_above >= SPAWN_HEADROOM {
+                        return Some(BlockPosition::new(x, y + 1, z));
+                    }
+                    free_above = 0;
+                }
+            }
+        }
+        None
+    }
+
+    /// Solid blocks stop falling players; sensors and unloaded chunks don't
+    pub fn is_solid_block(&self, position: &BlockPosition, server_settings: &ServerSettings) -> bool {
+        matches!(
+            self.get_spawn_block_kind(position, server_settings),
+            SpawnBlockKind::Solid
+        )
+    }
+
+    fn get_spawn_block_kind(&self, position: &BlockPosition, server_settings: &ServerSettings) -> SpawnBlockKind {
+        let Some(chunk_column) = self.chunks.get(&position.get_chunk_position()) else {
+            return SpawnBlockKind::Unloaded;
+        };
+
+        let chunk_column = chunk_column.read();
+        if !chunk_column.is_loaded() {
+            return SpawnBlockKind::Unloaded;
+        }
+
+        let Some(block_info) = chunk_column
+            .get_chunk_storage()
+            .get_chunk_data()
+            .get_block_info(position)
+        else {
+            return SpawnBlockKind::Empty;
+        };
+
+        let Some(block_type) = server_settings.get_block_type_by_id(block_info.get_id()) else {
+            panic!(
+                "find_safe_spawn: block id {} is not found in server settings",
+                block_info.get_id()
+            );
+        };
+
+        match block_type.get_collider_type().is_sensor() {
+            true => SpawnBlockKind::Sensor,
+            false => SpawnBlockKind::Solid,
+        }
+    }
+}
+
+fn save_chunks(
+    storage: &StorageLock,
+    chunks: &[Arc<RwLock<ChunkColumn>>],
+    unsaved_chunks: &UnsavedChunks,
+) -> Result<usize, String> {
+    let results: Vec<_> = chunks
+        .par_iter()
+        .map(|chunk_column| {
+            let chunk_column = chunk_column.read();
+            let chunk_position = *chunk_column.get_chunk_position();
+            let result = storage
+                .read()
+                .save_chunk_data(&chunk_position, chunk_column.get_chunk_storage());
+            if result.is_err() {
+                // Keep it for the next attempt; the column may be despawned meanwhile
+                chunk_column.mark_dirty();
+                let chunk_storage = chunk_column.get_chunk_storage().clone();
+                drop(chunk_column);
+                unsaved_chunks.lock().insert(chunk_position, chunk_storage);
+            }
+            result.map(|_| 1)
+        })
+        .collect();
+    merge_save_results(results)
+}
+
+/// Failed chunks are returned to unsaved unless a newer despawn stored them there
+fn save_unsaved_chunks(
+    storage: &StorageLock,
+    chunks: Vec<(ChunkPosition, ChunkStorage)>,
+    unsaved_chunks: &UnsavedChunks,
+) -> Result<usize, String> {
+    let results: Vec<_> = chunks
+        .into_par_iter()
+        .map(|(chunk_position, chunk_storage)| {
+            let result = storage.read().save_chunk_data(&chunk_position, &chunk_storage);
+            if result.is_err() {
+                unsaved_chunks.lock().entry(chunk_position).or_insert(chunk_storage);
+            }
+            result.map(|_| 1)
+        })
+        .collect();
+    merge_save_results(results)
+}
+
+/// Sums written chunks; all errors are returned together
+pub(crate) fn merge_save_results(results: impl IntoIterator<Item = Result<usize, String>>) -> Result<usize, String> {
+    let mut count = 0;
+    let mut errors = Vec::new();
+    for result in results {
+        match result {
+            Ok(c) => count += c,
+            Err(e) => errors.push(e),
+        }
+    }
+    if errors.is_empty() {
+        return Ok(count);
+    }
+    errors.sort();
+    errors.dedup();
+    Err(errors.join("; "))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ChunkMap, ChunkPosition};
-    use crate::{plugins::server_plugin::plugin_instance::WASMPluginManager, CHUNKS_DESPAWN_TIMER};
+    use super::{ChunkMap, ChunkPosition, ChunksUpdateSettings};
+    use crate::plugins::server_plugin::plugin_instance::WASMPluginManager;
+    use crate::worlds::chunks::chunk_failure::{ChunkFailure, ChunkFailureKind, ChunkFailurePolicy, ChunkFailureState};
     use bevy::prelude::Entity;
-    use common::chunks::{
-        chunk_data::{ChunkData, ChunkSectionData},
-        chunk_storage::ChunkStorage,
+    use common::{
+        chunks::{
+            chunk_data::{ChunkData, ChunkSectionData},
+            chunk_storage::ChunkStorage,
+        },
+        worlds_storage::taits::IWorldStorage,
+    };
+    use std::{
+        sync::{atomic::Ordering, Arc},
+        time::{Duration, Instant},
     };
-    use std::{sync::Arc, time::Duration};
+
+    const CHUNKS_DESPAWN_TIMER: Duration = Duration::from_secs(5);
+
+    fn settings(failure_policy: ChunkFailurePolicy) -> ChunksUpdateSettings {
+        ChunksUpdateSettings {
+            despawn_timer: CHUNKS_DESPAWN_TIMER,
+            failure_policy,
+            max_retries: 3,
+            max_loads: 64,
+        }
+    }
 
     fn chunks_to_grid(chunks: &Vec<ChunkPosition>, center: &ChunkPosition, radius: i64) -> String {
         let mut lines = Vec::new();
@@ -481,6 +1031,32 @@ ___________
         assert_eq!(chunk_map.chunks_load_state.num_tickets(&new_pos), 0);
     }
 
+    #[test]
+    fn test_tickets_resize() {
+        let mut chunk_map = ChunkMap::default();
+        let entity = Entity::from_raw_u32(0).unwrap();
+        let pos = ChunkPosition::new(0, 0);
+        chunk_map.start_chunks_render(entity, &pos, 5);
+
+        // Shrink
+        let change = chunk_map.resize_chunks_render(entity, &pos, 3);
+        let watching = chunk_map.chunks_load_state.get_watching_chunks(&entity).unwrap().len();
+        assert_eq!(change.new_chunks.len(), 0);
+        assert_eq!(watching + change.abandoned_chunks.len(), 69);
+        for chunk in change.abandoned_chunks.iter() {
+            assert_eq!(chunk_map.chunks_load_state.num_tickets(chunk), 0);
+        }
+
+        // Grow back
+        let grow = chunk_map.resize_chunks_render(entity, &pos, 5);
+        assert_eq!(grow.abandoned_chunks.len(), 0);
+        assert_eq!(grow.new_chunks.len(), change.abandoned_chunks.len());
+        assert_eq!(
+            chunk_map.chunks_load_state.get_watching_chunks(&entity).unwrap().len(),
+            69
+        );
+    }
+
     #[test]
     fn test_update_chunks() {
         let wasm_plugin_manager: Arc<WASMPluginManager> = Default::default();
@@ -496,6 +1072,8 @@ ___________
             &world_slug,
             wasm_plugin_manager.clone(),
             &mut inventory_manager,
+            &settings(ChunkFailurePolicy::Retry),
+            |_| Vec::new(),
         );
         assert_eq!(chunk_map.chunks.len(), 1, "One chunk must be created");
 
@@ -519,6 +1097,8 @@ ___________
             &world_slug,
             wasm_plugin_manager,
             &mut inventory_manager,
+            &settings(ChunkFailurePolicy::Retry),
+            |_| Vec::new(),
         );
         assert_eq!(
             chunk_map.chunks.len(),
@@ -526,4 +1106,209 @@ ___________
             "Because despawn_timer is fill - chunk must be unloaded"
         );
     }
+
+    #[test]
+    fn test_chunk_failures() {
+        let wasm_plugin_manager: Arc<WASMPluginManager> = Default::default();
+        let mut chunk_map = ChunkMap::default();
+        let mut inventory_manager = crate::inventory::inventory_manager::InventoryManager::default();
+        let world_slug = "default".to_string();
+        let entity = Entity::from_raw_u32(0).unwrap();
+        let broken = ChunkPosition::new(0, 0);
+        let empty = ChunkPosition::new(1, 0);
+
+        chunk_map.chunks_load_state.insert_ticket(broken, entity);
+        chunk_map.chunks_load_state.insert_ticket(empty, entity);
+        let mut update = |chunk_map: &mut ChunkMap, policy: ChunkFailurePolicy| {
+            chunk_map.update_chunks_state(
+                Duration::from_secs(1),
+                &world_slug,
+                wasm_plugin_manager.clone(),
+                &mut inventory_manager,
+                &settings(policy),
+                |_| Vec::new(),
+            );
+        };
+        update(&mut chunk_map, ChunkFailurePolicy::Quarantine);
+        assert_eq!(chunk_map.chunks.len(), 2);
+
+        // Quarantined chunk is not loaded again
+        let failure = ChunkFailure::new(broken, ChunkFailureKind::Generate, "error".to_string());
+        chunk_map.failed_chunks.0.send(failure).unwrap();
+        update(&mut chunk_map, ChunkFailurePolicy::Quarantine);
+        update(&mut chunk_map, ChunkFailurePolicy::Quarantine);
+        assert!(chunk_map.get_chunk_column(&broken).is_none());
+        assert_eq!(chunk_map.iter_failures().count(), 1);
+
+        // Placeholder is never saved
+        let failure = ChunkFailure::new(empty, ChunkFailureKind::Corrupt, "error".to_string());
+        chunk_map.failed_chunks.0.send(failure).unwrap();
+        update(&mut chunk_map, ChunkFailurePolicy::Void);
+        let column = chunk_map.get_chunk_column(&empty).unwrap();
+        assert!(column.is_loaded());
+        column.mark_dirty();
+        assert!(!column.take_dirty());
+        drop(column);
+
+        assert_eq!(chunk_map.clear_quarantine(), 1);
+        update(&mut chunk_map, ChunkFailurePolicy::Quarantine);
+        assert!(chunk_map.get_chunk_column(&broken).is_some());
+    }
+
+    #[test]
+    fn test_load_queue() {
+        let wasm_plugin_manager: Arc<WASMPluginManager> = Default::default();
+        let mut chunk_map = ChunkMap::default();
+        let mut inventory_manager = crate::inventory::inventory_manager::InventoryManager::default();
+        let world_slug = "default".to_string();
+        let entity = Entity::from_raw_u32(0).unwrap();
+        let center = ChunkPosition::new(3, 3);
+        let mut settings = settings(ChunkFailurePolicy::Retry);
+        settings.max_loads = 1;
+
+        chunk_map.start_chunks_render(entity, &center, 2);
+        chunk_map.add_ticket(ChunkPosition::new(3, 4), super::ChunkTicket::Forced, None);
+        chunk_map.update_chunks_state(
+            Duration::from_secs(1),
+            &world_slug,
+            wasm_plugin_manager.clone(),
+            &mut inventory_manager,
+            &settings,
+            |_| Vec::new(),
+        );
+        assert_eq!(chunk_map.chunks.len(), 1);
+        assert!(chunk_map.chunks.contains_key(&center), "the nearest chunk goes first");
+
+        // Loading chunks are dropped without watchers
+        chunk_map.stop_chunks_render(entity);
+        chunk_map.update_chunks_state(
+            Duration::from_secs(1),
+            &world_slug,
+            wasm_plugin_manager,
+            &mut inventory_manager,
+            &settings,
+            |_| Vec::new(),
+        );
+        assert_eq!(chunk_map.chunks.len(), 1);
+        assert!(chunk_map.chunks.contains_key(&ChunkPosition::new(3, 4)));
+    }
+
+    #[test]
+    fn test_retries_budget() {
+        let wasm_plugin_manager: Arc<WASMPluginManager> = Default::default();
+        let mut chunk_map = ChunkMap::default();
+        let mut inventory_manager = crate::inventory::inventory_manager::InventoryManager::default();
+        let world_slug = "default".to_string();
+        let entity = Entity::from_raw_u32(0).unwrap();
+        let chunks = [ChunkPosition::new(0, 0), ChunkPosition::new(1, 0)];
+        let mut settings = settings(ChunkFailurePolicy::Retry);
+
+        for chunk in chunks.iter() {
+            chunk_map.chunks_load_state.insert_ticket(*chunk, entity);
+        }
+        let mut update = |chunk_map: &mut ChunkMap, settings: &ChunksUpdateSettings| {
+            chunk_map.update_chunks_state(
+                Duration::from_secs(1),
+                &world_slug,
+                wasm_plugin_manager.clone(),
+                &mut inventory_manager,
+                settings,
+                |_| Vec::new(),
+            );
+        };
+        update(&mut chunk_map, &settings);
+        for chunk in chunks.iter() {
+            let failure = ChunkFailure::new(*chunk, ChunkFailureKind::Generate, "error".to_string());
+            chunk_map.failed_chunks.0.send(failure).unwrap();
+        }
+        update(&mut chunk_map, &settings);
+
+        let retrying = |chunk_map: &ChunkMap| {
+            chunk_map
+                .iter_failures()
+                .filter(|f| f.get_state() == ChunkFailureState::Retrying)
+                .count()
+        };
+        let expire_backoff = |chunk_map: &mut ChunkMap| {
+            for failure in chunk_map.failures.values_mut() {
+                if let ChunkFailureState::Backoff(_) = failure.get_state() {
+                    failure.set_state(ChunkFailureState::Backoff(Instant::now()));
+                }
+            }
+        };
+        assert_eq!(retrying(&chunk_map), 0);
+
+        // Nothing is retried while the budget is used
+        settings.max_loads = 1;
+        chunk_map.loading.store(1, Ordering::Relaxed);
+        expire_backoff(&mut chunk_map);
+        update(&mut chunk_map, &settings);
+        assert_eq!(retrying(&chunk_map), 0);
+
+        chunk_map.loading.store(0, Ordering::Relaxed);
+        update(&mut chunk_map, &settings);
+        assert_eq!(retrying(&chunk_map), 1);
+
+        update(&mut chunk_map, &settings);
+        assert_eq!(retrying(&chunk_map), 2);
+    }
+
+    fn chunk_storage() -> ChunkStorage {
+        let mut chunk_data = ChunkData::default();
+        chunk_data.push_section(ChunkSectionData::default());
+        ChunkStorage::create(chunk_data)
+    }
+
+    #[test]
+    fn test_unsaved_chunk_restored() {
+        let wasm_plugin_manager: Arc<WASMPluginManager> = Default::default();
+        let mut chunk_map = ChunkMap::default();
+        let mut inventory_manager = crate::inventory::inventory_manager::InventoryManager::default();
+        let entity = Entity::from_raw_u32(0).unwrap();
+        let pos = ChunkPosition::new(0, 0);
+
+        // Despawn save of the chunk failed
+        chunk_map.unsaved_chunks.lock().insert(pos, chunk_storage());
+
+        chunk_map.chunks_load_state.insert_ticket(pos, entity);
+        chunk_map.update_chunks_state(
+            Duration::from_secs(1),
+            &"default".to_string(),
+            wasm_plugin_manager,
+            &mut inventory_manager,
+            &settings(ChunkFailurePolicy::Retry),
+            |_| Vec::new(),
+        );
+        let column = chunk_map.get_chunk_column(&pos).unwrap();
+        assert!(column.is_loaded(), "chunk is restored without loading");
+        assert!(column.take_dirty(), "restored chunk must be saved again");
+        drop(column);
+        assert!(chunk_map.unsaved_chunks.lock().is_empty());
+        assert_eq!(chunk_map.drain_loaded_chunks().collect::<Vec<_>>(), vec![pos]);
+    }
+
+    #[test]
+    fn test_save_unsaved_chunks() {
+        let mut chunk_map = ChunkMap::default();
+        let unsaved = ChunkPosition::new(0, 0);
+        let loaded = ChunkPosition::new(1, 0);
+        chunk_map.unsaved_chunks.lock().insert(unsaved, chunk_storage());
+
+        // Loaded column has newer data and is saved by itself
+        chunk_map.unsaved_chunks.lock().insert(loaded, chunk_storage());
+        chunk_map.insert_loaded_chunk(&"default".to_string(), loaded, chunk_storage());
+
+        assert_eq!(chunk_map.save(), Ok(1));
+        assert!(chunk_map.unsaved_chunks.lock().is_empty());
+        let storage = chunk_map.get_storage();
+        assert!(storage.read().has_chunk_data(&unsaved).unwrap().is_some());
+        assert!(storage.read().has_chunk_data(&loaded).unwrap().is_none());
+
+        // Background save writes them too
+        chunk_map.unsaved_chunks.lock().insert(loaded, chunk_storage());
+        chunk_map.chunks.clear();
+        assert!(chunk_map.start_background_save());
+        assert_eq!(chunk_map.save(), Ok(1));
+        assert!(storage.read().has_chunk_data(&loaded).unwrap().is_some());
+    }
 }
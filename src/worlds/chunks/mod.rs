@@ -1,4 +1,5 @@
 pub mod chunk_column;
+pub mod chunk_failure;
 pub mod chunk_generator;
 pub mod chunks_load_state;
 pub mod chunks_map;
@@ -1,29 +1,194 @@
 use bevy::prelude::World;
+use bevy::time::Time;
 use bevy_ecs::system::Command;
 use common::chunks::block_position::BlockPositionTrait;
+use network::entities::AnimationState;
 
-use super::worlds_manager::SharedWorldsManager;
+use super::worlds_manager::{SharedWorldsManager, WorldsManager};
 use crate::{
-    clients::client::Client,
-    entities::entity::{Position, Rotation},
+    clients::client::{Client, WorldEntity},
+    entities::{
+        entity::{Position, Rotation},
+        health::FallTracker,
+        skin::EntitySkinComponent,
+    },
+    inventory::{inventory_manager::InventoryManager, SharedInventoryManager},
     items_manager::items_manager::SharedItemsManager,
-    network::sync_players::PlayerSpawnEvent,
+    network::{
+        events::on_inventory_action::InventoryTarget,
+        events::on_player_move::move_player,
+        sync_entities::sync_entity_despawn,
+        sync_inventory::send_inventory_stop_to_client,
+        sync_players::{sync_player_chunks_changed, PlayerSpawnEvent},
+    },
+    server_config::ServerConfig,
 };
 
+/// Moves the player to the position inside any loaded world.
+///
+/// Inside the same world it's a regular move. Otherwise the entity is despawned from
+/// the current world and spawned in the target one; the chunks are streamed again from scratch.
+pub(crate) fn teleport_player(
+    world: &mut World,
+    client: &Client,
+    world_slug: &String,
+    position: Position,
+    rotation: Rotation,
+) -> Result<(), String> {
+    let Some(world_entity) = client.get_world_entity() else {
+        return Err("player is not in the world".to_string());
+    };
+
+    if world_entity.get_world_slug() == world_slug {
+        let server_time = world.resource::<Time>().elapsed().as_secs_f64();
+        let worlds_manager = world.resource::<SharedWorldsManager>().read();
+        let Some(mut world_manager) = worlds_manager.get_world_manager_mut(world_entity.get_world_slug()) else {
+            return Err(format!("world \"{}\" is not loaded", world_slug));
+        };
+        move_player(
+            &mut *world_manager,
+            &world_entity,
+            position,
+            rotation,
+            AnimationState::Idle,
+            server_time,
+            client.get_view_distance(),
+        );
+        world_manager
+            .get_ecs_mut()
+            .entity_mut(world_entity.get_entity())
+            .insert(FallTracker::default());
+        client.network_send_spawn(&position, &rotation, &Vec::new());
+        return Ok(());
+    }
+
+    if !world
+        .resource::<SharedWorldsManager>()
+        .read()
+        .has_world_with_slug(world_slug)
+    {
+        return Err(format!("world \"{}\" is not loaded", world_slug));
+    }
+
+    let watched_inventories = {
+        let worlds_manager = world.resource::<SharedWorldsManager>().read();
+        let mut inventory_manager = world.resource::<SharedInventoryManager>().write();
+        let watched_inventories = inventory_manager
+            .state()
+            .get_watched_inventories(&world_entity.get_entity());
+        despawn_player_from_world(&worlds_manager, &mut inventory_manager, client);
+        watched_inventories
+    };
+
+    SpawnPlayer::create_at(world_slug.clone(), client.clone(), position, rotation).apply(world);
+
+    // Inventories stay opened only if they are inside the new world
+    let Some(new_world_entity) = client.get_world_entity() else {
+        return Err(format!("player was not spawned in the world \"{}\"", world_slug));
+    };
+    let mut inventory_manager = world.resource::<SharedInventoryManager>().write();
+    for inventory_id in watched_inventories {
+        let inside_world = match inventory_manager.state().get_inventory_location(&inventory_id) {
+            Some(location) => location.get_world_slug() == world_slug,
+            None => false,
+        };
+        if inside_world {
+            inventory_manager.open_inventory(new_world_entity.get_entity(), inventory_id);
+        } else {
+            send_inventory_stop_to_client(client, &InventoryTarget::World(inventory_id));
+        }
+    }
+    Ok(())
+}
+
+/// Removes the player from his current world without disconnecting him.
+///
+/// The entity is despawned for other players and the world chunks are unloaded on the client.
+pub(crate) fn despawn_player_from_world(
+    worlds_manager: &WorldsManager,
+    inventory_manager: &mut InventoryManager,
+    client: &Client,
+) -> Option<WorldEntity> {
+    let world_entity = client.get_world_entity()?;
+
+    if let Some(mut world_manager) = worlds_manager.get_world_manager_mut(world_entity.get_world_slug()) {
+        let has_skin = match world_manager.get_ecs().get_entity(world_entity.get_entity()) {
+            Some(entity_ref) => entity_ref.get::<EntitySkinComponent>().is_some(),
+            None => false,
+        };
+        if has_skin {
+            sync_entity_despawn(&*world_manager, world_entity.get_entity());
+        }
+        world_manager.despawn_player(&world_entity, inventory_manager);
+    }
+
+    client.unload_all_chunks(world_entity.get_world_slug());
+    client.set_world_entity(None);
+    Some(world_entity)
+}
+
+/// Changes the view distance requested by the player and applies it at once:
+/// the chunk tickets are rebuilt, extra chunks and their entities are unloaded on the client.
+///
+/// Returns the effective view distance after the server and world limits
+pub(crate) fn change_view_distance(world: &World, client: &Client, requested: Option<u16>) -> u16 {
+    client.set_requested_view_distance(requested);
+
+    let server_config = world.resource::<ServerConfig>();
+    let Some(world_entity) = client.get_world_entity() else {
+        // Not spawned yet; SpawnPlayer applies it
+        return server_config.get_view_distance(server_config.get_default_world(), requested);
+    };
+
+    let view_distance = server_config.get_view_distance(world_entity.get_world_slug(), requested);
+    if !client.set_view_distance(view_distance) {
+        return view_distance;
+    }
+    client.network_send_view_distance();
+
+    let worlds_manager = world.resource::<SharedWorldsManager>().read();
+    let mut world_manager = worlds_manager
+        .get_world_manager_mut(world_entity.get_world_slug())
+        .unwrap();
+    let change = world_manager.player_resize_render(&world_entity, view_distance);
+    client.send_chunks_to_unload(world_entity.get_world_slug(), change.abandoned_chunks.clone());
+    sync_player_chunks_changed(&*world_manager, world_entity.get_entity(), &change);
+    view_distance
+}
+
 pub struct SpawnPlayer {
     world_slug: String,
     client: Client,
+
+    // Exact place to spawn; world spawn is used if none
+    location: Option<(Position, Rotation)>,
 }
 
 impl SpawnPlayer {
+    /// Spawn player at the spawn point of the world
     pub fn create(world_slug: String, client: Client) -> Self {
-        Self { world_slug, client }
+        Self {
+            world_slug,
+            client,
+            location: None,
+        }
+    }
+
+    pub fn create_at(world_slug: String, client: Client, position: Position, rotation: Rotation) -> Self {
+        Self {
+            world_slug,
+            client,
+            location: Some((position, rotation)),
+        }
     }
 }
 
 impl Command for SpawnPlayer {
     fn apply(self, world: &mut World) {
-        let (world_entity, is_chunk_loaded) = {
+        let (world_entity, is_chunk_loaded, chunks_distance) = {
+            let chunks_distance = world
+                .resource::<ServerConfig>()
+                .get_view_distance(&self.world_slug, self.client.get_requested_view_distance());
             let items_manager = world.resource::<SharedItemsManager>();
             let worlds_manager = world.resource::<SharedWorldsManager>();
             let worlds_manager = worlds_manager.write();
@@ -32,19 +197,31 @@ impl Command for SpawnPlayer {
             };
 
             let components = self.client.get_player_spawn_components(&items_manager);
-            let position = Position::new(0.0, 100.0, 0.0);
-            let rotation = Rotation::new(0.0, 0.0);
+            let (position, rotation) = match self.location {
+                Some(location) => location,
+                None => (world_manager.get_spawn_position(), Rotation::new(0.0, 0.0)),
+            };
 
-            let bundle = (position.clone(), rotation, self.client.clone());
-            let world_entity = world_manager.spawn_player(position, bundle, components.clone());
+            let stats = self.client.get_player_stats(&items_manager).unwrap_or_default();
+            let bundle = (
+                position.clone(),
+                rotation,
+                self.client.clone(),
+                stats,
+                FallTracker::default(),
+            );
+            let world_entity = world_manager.spawn_player(position, bundle, components.clone(), chunks_distance);
             let is_chunk_loaded = world_manager
                 .get_chunks_map()
                 .is_chunk_loaded(&position.get_chunk_position());
 
-            (world_entity, is_chunk_loaded)
+            (world_entity, is_chunk_loaded, chunks_distance)
         };
 
         self.client.set_world_entity(Some(world_entity.clone()));
+        if self.client.set_view_distance(chunks_distance) {
+            self.client.network_send_view_distance();
+        }
 
         // Send world creation message
         self.client.network_send_spawn_pending();
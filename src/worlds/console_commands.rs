@@ -1,18 +1,351 @@
+use crate::clients::autosave::save_players;
 use crate::clients::client::Client;
+use crate::clients::clients_container::SharedClientsContainer;
 use crate::console::console_sender::ConsoleSenderType;
-use crate::entities::entity::{Position, Rotation};
-use crate::network::events::on_player_move::move_player;
-use bevy::time::Time;
+use crate::entities::entity::Position;
+use crate::inventory::SharedInventoryManager;
+use crate::launch_settings::LaunchSettings;
+use crate::plugins::{plugins_manager::PluginsManager, server_settings::ServerSettings};
+use crate::server_config::ServerConfig;
+use crate::storage::storage_manager::SharedStorageManager;
+use bevy_ecs::system::Command as _;
 use bevy_ecs::world::World;
-use common::commands::command::{Arg, Command, CommandMatch};
-use network::entities::AnimationState;
+use common::chunks::block_position::BlockPositionTrait;
+use common::chunks::chunk_position::ChunkPosition;
+use common::commands::command::{Arg, ArgCompleterContext, Command, CommandMatch};
+use common::utils::srotage_settings::StorageSettings;
+use common::WorldStorageManager;
 
+use super::chunks::chunk_failure::ChunkFailureState;
+use super::chunks::chunks_load_state::ChunkTicket;
+use super::commands::{despawn_player_from_world, SpawnPlayer};
+use super::forceload::ForcedChunks;
+use super::load_worlds::{create_new_world, load_world, scan_world};
+use super::pregen::PregenManager;
 use super::worlds_manager::SharedWorldsManager;
 
+pub(crate) fn complete_worlds(context: &dyn ArgCompleterContext, input: &str) -> Vec<String> {
+    let Some(world) = context.world().downcast_ref::<World>() else {
+        panic!("ArgCompleterContext world is not bevy_ecs::world::World");
+    };
+    let Some(worlds_manager) = world.get_resource::<SharedWorldsManager>() else {
+        return Vec::new();
+    };
+
+    let mut slugs: Vec<String> = worlds_manager
+        .read()
+        .iter_worlds()
+        .map(|w| w.get_slug().clone())
+        .filter(|slug| slug.contains(input))
+        .collect();
+    slugs.sort();
+    slugs
+}
+
+fn complete_pregen(context: &dyn ArgCompleterContext, input: &str) -> Vec<String> {
+    let mut targets: Vec<String> = ["status", "cancel"]
+        .into_iter()
+        .filter(|s| s.contains(input))
+        .map(|s| s.to_string())
+        .collect();
+    targets.extend(complete_worlds(context, input));
+    targets
+}
+
 pub(crate) fn command_parser_world() -> Command {
     Command::new("world".to_string())
         .subcommand_required(true)
         .subcommand(Command::new("list".to_owned()))
+        .subcommand(
+            Command::new("create".to_owned())
+                .arg(Arg::new("slug".to_owned()).required(true))
+                .arg(Arg::new("generator".to_owned()).required(false))
+                .arg(Arg::new("seed".to_owned()).required(false)),
+        )
+        .subcommand(Command::new("load".to_owned()).arg(Arg::new("slug".to_owned()).required(true)))
+        .subcommand(
+            Command::new("unload".to_owned())
+                .arg(Arg::new("slug".to_owned()).required(true).completer(complete_worlds)),
+        )
+        .subcommand(
+            Command::new("delete".to_owned())
+                .arg(Arg::new("slug".to_owned()).required(true).completer(complete_worlds)),
+        )
+        .subcommand(
+            Command::new("info".to_owned()).arg(Arg::new("slug".to_owned()).required(true).completer(complete_worlds)),
+        )
+        .subcommand(
+            Command::new("pregen".to_owned())
+                .arg(Arg::new("slug".to_owned()).required(true).completer(complete_pregen))
+                .arg(Arg::new("radius".to_owned()).required(false))
+                .arg(Arg::new("center".to_owned()).required(false)),
+        )
+        .subcommand(
+            Command::new("setspawn".to_owned())
+                .arg(Arg::new("world".to_owned()).required(false).completer(complete_worlds))
+                .arg(Arg::new("x".to_owned()).required(false))
+                .arg(Arg::new("y".to_owned()).required(false))
+                .arg(Arg::new("z".to_owned()).required(false)),
+        )
+}
+
+pub(crate) fn command_parser_forceload() -> Command {
+    let area = |name: &str| {
+        Command::new(name.to_owned())
+            .arg(Arg::new("world".to_owned()).required(true).completer(complete_worlds))
+            .arg(Arg::new("x".to_owned()).required(true))
+            .arg(Arg::new("z".to_owned()).required(true))
+            .arg(Arg::new("to_x".to_owned()).required(false))
+            .arg(Arg::new("to_z".to_owned()).required(false))
+    };
+    Command::new("forceload".to_string())
+        .subcommand_required(true)
+        .subcommand(area("add"))
+        .subcommand(area("remove"))
+        .subcommand(
+            Command::new("list".to_owned())
+                .arg(Arg::new("world".to_owned()).required(false).completer(complete_worlds)),
+        )
+}
+
+/// Chunks of `forceload add|remove` at once
+const FORCELOAD_MAX_CHUNKS: usize = 256;
+
+/// `forceload add|remove <world> <x> <z> [to_x to_z]` keeps chunks of the block area loaded
+/// without players; `forceload list [world]` shows non-player tickets
+pub(crate) fn command_forceload(
+    world: &mut World,
+    sender: Box<dyn ConsoleSenderType>,
+    args: CommandMatch,
+) -> Result<(), String> {
+    let Some(subcommand) = args.subcommand() else {
+        return Ok(());
+    };
+
+    match subcommand.get_name().as_str() {
+        "add" | "remove" => {
+            let world_slug = subcommand.get_arg::<String, _>("world")?.clone();
+            if !world
+                .resource::<SharedWorldsManager>()
+                .read()
+                .has_world_with_slug(&world_slug)
+            {
+                sender.send_console_message(format!("&cWorld \"{}\" is not loaded", world_slug));
+                return Ok(());
+            }
+
+            let from = Position::new(
+                subcommand.get_arg::<f32, _>("x")?,
+                0.0,
+                subcommand.get_arg::<f32, _>("z")?,
+            )
+            .get_chunk_position();
+            let to = match (
+                subcommand.get_arg::<f32, _>("to_x"),
+                subcommand.get_arg::<f32, _>("to_z"),
+            ) {
+                (Ok(x), Ok(z)) => Position::new(x, 0.0, z).get_chunk_position(),
+                (Err(_), Err(_)) => from.clone(),
+                _ => {
+                    sender
+                        .send_console_message("&cUsage: forceload add|remove <world> <x> <z> [to_x to_z]".to_string());
+                    return Ok(());
+                }
+            };
+
+            let (width, depth) = ((from.x - to.x).unsigned_abs() + 1, (from.z - to.z).unsigned_abs() + 1);
+            if width.saturating_mul(depth) > FORCELOAD_MAX_CHUNKS as u64 {
+                sender.send_console_message(format!(
+                    "&cArea is too large: {} chunks; limit is {}",
+                    width.saturating_mul(depth),
+                    FORCELOAD_MAX_CHUNKS
+                ));
+                return Ok(());
+            }
+            let mut chunks: Vec<ChunkPosition> = Default::default();
+            for x in from.x.min(to.x)..=from.x.max(to.x) {
+                for z in from.z.min(to.z)..=from.z.max(to.z) {
+                    chunks.push(ChunkPosition::new(x, z));
+                }
+            }
+
+            let mut forced_chunks = world.resource_mut::<ForcedChunks>();
+            if subcommand.get_name() == "add" {
+                let added = forced_chunks.add(&world_slug, &chunks)?;
+                sender.send_console_message(format!(
+                    "Chunks &e{}&r of the world &a\"{}\"&r are forced to load",
+                    added, world_slug
+                ));
+            } else {
+                let removed = forced_chunks.remove(&world_slug, &chunks)?;
+                sender.send_console_message(format!(
+                    "Chunks &e{}&r of the world &a\"{}\"&r are no longer forced",
+                    removed, world_slug
+                ));
+            }
+        }
+        "list" => {
+            let world_slug = subcommand.get_arg::<String, _>("world").ok().cloned();
+            let worlds_manager = world.resource::<SharedWorldsManager>().read();
+            let mut worlds: Vec<_> = worlds_manager
+                .iter_worlds()
+                .filter(|w| world_slug.as_ref().is_none_or(|slug| slug == w.get_slug()))
+                .collect();
+            if worlds.is_empty() {
+                sender.send_console_message("&cNo worlds found".to_string());
+                return Ok(());
+            }
+            worlds.sort_by(|a, b| a.get_slug().cmp(b.get_slug()));
+            for world_manager in worlds {
+                let (mut spawn, mut plugin) = (0, 0);
+                let mut forced: Vec<String> = Default::default();
+                for (chunk_position, ticket, _ttl) in world_manager.get_chunks_map().iter_other_tickets() {
+                    match ticket {
+                        ChunkTicket::Spawn => spawn += 1,
+                        ChunkTicket::Forced => forced.push(chunk_position.to_string()),
+                        ChunkTicket::Plugin(_) => plugin += 1,
+                        ChunkTicket::Player(_) => (),
+                    }
+                }
+                forced.sort();
+                sender.send_console_message(format!(
+                    " - &a{}&r: &8spawn:&7{} &8forced:&7{} &8plugin:&7{}",
+                    world_manager.get_slug(),
+                    spawn,
+                    forced.len(),
+                    plugin
+                ));
+                if !forced.is_empty() {
+                    sender.send_console_message(format!("   &7forced: &8{}", forced.join(", ")));
+                }
+            }
+        }
+        _ => {
+            sender.send_console_message("Error".to_string());
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn command_parser_chunks() -> Command {
+    Command::new("chunks".to_string())
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("failed".to_owned())
+                .arg(Arg::new("world".to_owned()).required(false).completer(complete_worlds)),
+        )
+        .subcommand(
+            Command::new("retry".to_owned())
+                .arg(Arg::new("world".to_owned()).required(true).completer(complete_worlds)),
+        )
+}
+
+/// `chunks failed [world]` lists chunks which failed to load;
+/// `chunks retry <world>` lets quarantined chunks load again
+pub(crate) fn command_chunks(
+    world: &mut World,
+    sender: Box<dyn ConsoleSenderType>,
+    args: CommandMatch,
+) -> Result<(), String> {
+    let Some(subcommand) = args.subcommand() else {
+        return Ok(());
+    };
+
+    let worlds_manager = world.resource::<SharedWorldsManager>().read();
+    match subcommand.get_name().as_str() {
+        "failed" => {
+            let world_slug = subcommand.get_arg::<String, _>("world").ok().cloned();
+            let mut count = 0;
+            for world_manager in worlds_manager.iter_worlds() {
+                if world_slug.as_ref().is_some_and(|slug| slug != world_manager.get_slug()) {
+                    continue;
+                }
+                for failure in world_manager.get_chunks_map().iter_failures() {
+                    let state = match failure.get_state() {
+                        ChunkFailureState::Backoff(_) | ChunkFailureState::Retrying => "retrying",
+                        ChunkFailureState::Quarantined => "quarantined",
+                        ChunkFailureState::Placeholder => "void",
+                    };
+                    sender.send_console_message(format!(
+                        " - &a{}&r {} &e{}&r &8kind:&7{} &8attempts:&7{} &8error:&7{}",
+                        world_manager.get_slug(),
+                        failure.get_chunk_position(),
+                        state,
+                        failure.get_kind(),
+                        failure.get_attempts(),
+                        failure.get_error()
+                    ));
+                    count += 1;
+                }
+            }
+            if count == 0 {
+                sender.send_console_message("No failed chunks".to_string());
+            }
+        }
+        "retry" => {
+            let world_slug = subcommand.get_arg::<String, _>("world")?.clone();
+            let Some(mut world_manager) = worlds_manager.get_world_manager_mut(&world_slug) else {
+                sender.send_console_message(format!("&cWorld \"{}\" is not loaded", world_slug));
+                return Ok(());
+            };
+            let count = world_manager.get_chunks_map_mut().clear_quarantine();
+            sender.send_console_message(format!(
+                "Chunks &e{}&r of the world &a\"{}\"&r released from quarantine",
+                count, world_slug
+            ));
+        }
+        _ => {
+            sender.send_console_message("Error".to_string());
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn command_parser_save_all() -> Command {
+    Command::new("save-all".to_string())
+}
+
+/// Saves changed chunks of all worlds and data of all players
+pub(crate) fn command_save_all(
+    world: &mut World,
+    sender: Box<dyn ConsoleSenderType>,
+    _args: CommandMatch,
+) -> Result<(), String> {
+    let now = std::time::Instant::now();
+    let worlds_manager = world.resource::<SharedWorldsManager>().read();
+
+    let players = match world.get_resource::<SharedStorageManager>() {
+        Some(storage) => save_players(
+            &world.resource::<SharedClientsContainer>().read(),
+            &worlds_manager,
+            &storage.read(),
+        ),
+        None => 0,
+    };
+    if let Err(e) = world
+        .resource::<SharedInventoryManager>()
+        .write()
+        .get_audit_log_mut()
+        .flush()
+    {
+        sender.send_console_message(format!("&cAudit log save error: {}", e));
+    }
+
+    match worlds_manager.save_all() {
+        Ok(chunks) => {
+            sender.send_console_message(format!(
+                "Saved &e{}&r chunks in &e{}&r worlds and &e{}&r players &8(executed:{:.2?})",
+                chunks,
+                worlds_manager.count(),
+                players,
+                now.elapsed()
+            ));
+        }
+        Err(e) => {
+            sender.send_console_message(format!("&cSave error: {}", e));
+        }
+    }
+    Ok(())
 }
 
 pub(crate) fn command_world(
@@ -38,6 +371,27 @@ pub(crate) fn command_world(
                     ));
                 }
             }
+            "create" => {
+                world_create(world, &sender, world_subcommand)?;
+            }
+            "load" => {
+                world_load(world, &sender, world_subcommand)?;
+            }
+            "unload" => {
+                world_unload(world, &sender, world_subcommand)?;
+            }
+            "delete" => {
+                world_delete(world, &sender, world_subcommand)?;
+            }
+            "info" => {
+                world_info(world, &sender, world_subcommand)?;
+            }
+            "setspawn" => {
+                world_setspawn(world, &sender, world_subcommand)?;
+            }
+            "pregen" => {
+                world_pregen(world, &sender, world_subcommand)?;
+            }
             _ => {
                 sender.send_console_message("Error".to_string());
             }
@@ -46,56 +400,477 @@ pub(crate) fn command_world(
     return Ok(());
 }
 
-pub(crate) fn command_parser_teleport() -> Command {
-    Command::new("tp".to_owned())
-        .arg(Arg::new("x".to_owned()).required(true))
-        .arg(Arg::new("y".to_owned()).required(true))
-        .arg(Arg::new("z".to_owned()).required(true))
+/// Clients whose entity is inside the world
+fn get_world_players(world: &World, world_slug: &String) -> Vec<Client> {
+    let clients = world.resource::<SharedClientsContainer>();
+    let clients = clients.read();
+    clients
+        .iter()
+        .filter(|(_id, client)| match client.get_world_entity() {
+            Some(world_entity) => world_entity.get_world_slug() == world_slug,
+            None => false,
+        })
+        .map(|(_id, client)| client.clone())
+        .collect()
 }
 
-pub(crate) fn command_teleport(
-    world: &mut World,
-    sender: Box<dyn ConsoleSenderType>,
-    args: CommandMatch,
-) -> Result<(), String> {
-    let x = args.get_arg::<f32, _>("x")?.clone();
-    let y = args.get_arg::<f32, _>("y")?.clone();
-    let z = args.get_arg::<f32, _>("z")?.clone();
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{} {}", bytes, UNITS[0]),
+        _ => format!("{:.2} {}", size, UNITS[unit]),
+    }
+}
+
+fn world_create(world: &mut World, sender: &Box<dyn ConsoleSenderType>, args: &CommandMatch) -> Result<(), String> {
+    let slug = args.get_arg::<String, _>("slug")?.clone();
+    let generator = match args.get_arg::<String, _>("generator") {
+        Ok(generator) => generator.clone(),
+        Err(_) => world.resource::<ServerConfig>().get_default_world_generator().clone(),
+    };
+    let seed = match args.get_arg::<u64, _>("seed") {
+        Ok(seed) => Some(seed.clone()),
+        Err(_) => None,
+    };
 
-    let server_time = world.resource::<Time>().elapsed().as_secs_f64();
+    let launch_settings = world.resource::<LaunchSettings>();
     let worlds_manager = world.resource::<SharedWorldsManager>();
+    if worlds_manager.read().has_world_with_slug(&slug) {
+        sender.send_console_message(format!("&cWorld \"{}\" already exists", slug));
+        return Ok(());
+    }
+    if scan_world(launch_settings, &slug)?.is_some() {
+        sender.send_console_message(format!(
+            "&cWorld \"{}\" already exists in the storage; use &4world load {}",
+            slug, slug
+        ));
+        return Ok(());
+    }
 
-    let client = match sender.as_any().downcast_ref::<Client>() {
-        Some(c) => c,
-        None => {
-            sender.send_console_message("This command is allowed to be used only for players".to_string());
-            return Ok(());
+    let result = create_new_world(
+        slug.clone(),
+        seed,
+        generator.clone(),
+        launch_settings,
+        world.resource::<PluginsManager>(),
+        &mut *worlds_manager.write(),
+    );
+    match result {
+        Ok(()) => {
+            sender.send_console_message(format!("World &a\"{}\"&r created; &7generator: &8{}", slug, generator));
         }
+        Err(e) => {
+            sender.send_console_message(format!("&cWorld \"{}\" create error: {}", slug, e));
+        }
+    }
+    Ok(())
+}
+
+fn world_load(world: &mut World, sender: &Box<dyn ConsoleSenderType>, args: &CommandMatch) -> Result<(), String> {
+    let slug = args.get_arg::<String, _>("slug")?.clone();
+
+    let launch_settings = world.resource::<LaunchSettings>();
+    let worlds_manager = world.resource::<SharedWorldsManager>();
+    if worlds_manager.read().has_world_with_slug(&slug) {
+        sender.send_console_message(format!("&cWorld \"{}\" is already loaded", slug));
+        return Ok(());
+    }
+
+    let Some(world_data) = scan_world(launch_settings, &slug)? else {
+        sender.send_console_message(format!("&cWorld \"{}\" not found in the storage", slug));
+        return Ok(());
     };
 
-    let position = Position::new(x, y, z);
-    let rotation = Rotation::new(0.0, 0.0);
+    let storage_settings = StorageSettings::from_path(launch_settings.get_server_data_path());
+    let result = load_world(
+        &world_data,
+        &storage_settings,
+        world.resource::<ServerSettings>(),
+        world.resource::<PluginsManager>(),
+        &*worlds_manager.read(),
+    );
+    match result {
+        Ok(()) => sender.send_console_message(format!("World &a\"{}\"&r loaded", slug)),
+        Err(e) => sender.send_console_message(format!("&cWorld \"{}\" load error: {}", slug, e)),
+    }
+    Ok(())
+}
+
+/// Saves the world and removes it from the server.
+/// Players inside are moved to the default world.
+fn world_unload(world: &mut World, sender: &Box<dyn ConsoleSenderType>, args: &CommandMatch) -> Result<(), String> {
+    let slug = args.get_arg::<String, _>("slug")?.clone();
+    let default_world = world.resource::<ServerConfig>().get_default_world().clone();
+    if slug == default_world {
+        sender.send_console_message("&cThe default world can't be unloaded".to_string());
+        return Ok(());
+    }
+    if !world
+        .resource::<SharedWorldsManager>()
+        .read()
+        .has_world_with_slug(&slug)
+    {
+        sender.send_console_message(format!("&cWorld \"{}\" is not loaded", slug));
+        return Ok(());
+    }
 
-    let Some(world_entity) = client.get_world_entity() else {
+    // Players stay inside if the world can't be saved
+    if let Err(e) = world
+        .resource::<SharedWorldsManager>()
+        .read()
+        .get_world_manager(&slug)
+        .unwrap()
+        .save()
+    {
+        sender.send_console_message(format!("&cWorld \"{}\" save error: {}", slug, e));
+        return Ok(());
+    }
+
+    // Progress is kept, so the pregeneration continues after the world is loaded and started again
+    let pregen_stopped = world.resource_mut::<PregenManager>().stop(&slug).is_some();
+
+    // Forced chunks stay in the forceload file and are applied again when the world is loaded
+    let forced = world.resource::<ForcedChunks>().get_world_chunks(&slug).len();
+
+    let players = get_world_players(world, &slug);
+    {
+        let worlds_manager = world.resource::<SharedWorldsManager>().read();
+        let mut inventory_manager = world.resource::<SharedInventoryManager>().write();
+        for client in players.iter() {
+            despawn_player_from_world(&worlds_manager, &mut inventory_manager, client);
+        }
+
+        let world_manager = worlds_manager.get_world_manager(&slug).unwrap();
+        world_manager.unload(&mut inventory_manager);
+    }
+    world.resource::<SharedWorldsManager>().read().remove_world(&slug);
+
+    for client in players.iter() {
+        SpawnPlayer::create(default_world.clone(), client.clone()).apply(world);
+    }
+
+    sender.send_console_message(format!(
+        "World &a\"{}\"&r unloaded; &8players moved:&7{} &8forced chunks kept:&7{} &8pregeneration stopped:&7{}",
+        slug,
+        players.len(),
+        forced,
+        pregen_stopped
+    ));
+    Ok(())
+}
+
+fn world_delete(world: &mut World, sender: &Box<dyn ConsoleSenderType>, args: &CommandMatch) -> Result<(), String> {
+    let slug = args.get_arg::<String, _>("slug")?.clone();
+    if &slug == world.resource::<ServerConfig>().get_default_world() {
+        sender.send_console_message("&cThe default world can't be deleted".to_string());
+        return Ok(());
+    }
+
+    let players = get_world_players(world, &slug);
+    if !players.is_empty() {
         sender.send_console_message(format!(
-            "Player \"{}\" is not in the world",
-            client.get_client_info().unwrap().get_login()
+            "&cWorld \"{}\" can't be deleted: there are {} players inside",
+            slug,
+            players.len()
         ));
         return Ok(());
+    }
+
+    let launch_settings = world.resource::<LaunchSettings>();
+    if scan_world(launch_settings, &slug)?.is_none() {
+        sender.send_console_message(format!("&cWorld \"{}\" not found in the storage", slug));
+        return Ok(());
+    }
+    let server_data_path = launch_settings.get_server_data_path();
+
+    // World must be removed from the server before its storage
+    {
+        let worlds_manager = world.resource::<SharedWorldsManager>().read();
+        if let Some(world_manager) = worlds_manager.remove_world(&slug) {
+            let mut inventory_manager = world.resource::<SharedInventoryManager>().write();
+            world_manager
+                .get_chunks_map()
+                .unregister_inventories(&mut inventory_manager);
+        }
+    }
+    world.resource_mut::<PregenManager>().cancel(&server_data_path, &slug);
+    if let Err(e) = world.resource_mut::<ForcedChunks>().remove_world(&slug) {
+        log::error!(target: "worlds", "{}", e);
+    }
+
+    let storage_settings = StorageSettings::from_path(server_data_path.clone());
+    WorldStorageManager::delete(storage_settings, &slug)?;
+
+    log::info!(target: "worlds", "World &c\"{}\"&r deleted by &a{}", slug, sender.get_name());
+    sender.send_console_message(format!("World &a\"{}\"&r deleted", slug));
+    Ok(())
+}
+
+fn world_info(world: &mut World, sender: &Box<dyn ConsoleSenderType>, args: &CommandMatch) -> Result<(), String> {
+    let slug = args.get_arg::<String, _>("slug")?.clone();
+
+    let players_count = get_world_players(world, &slug).len();
+    let worlds_manager = world.resource::<SharedWorldsManager>().read();
+    let Some(world_manager) = worlds_manager.get_world_manager(&slug) else {
+        sender.send_console_message(format!("&cWorld \"{}\" is not loaded", slug));
+        return Ok(());
     };
 
-    let worlds_manager = worlds_manager.write();
-    let mut world_manager = worlds_manager
-        .get_world_manager_mut(&world_entity.get_world_slug())
-        .unwrap();
+    let generator_settings = world_manager.get_chunks_map().get_world_generator_settings();
+    let storage_size = match world_manager.get_chunks_map().get_storage_size() {
+        Ok(size) => format_bytes(size),
+        Err(e) => format!("&cunknown ({})", e),
+    };
 
-    move_player(
-        &mut *world_manager,
-        &world_entity,
-        position,
-        rotation,
-        AnimationState::Idle,
-        server_time,
-    );
-    return Ok(());
+    sender.send_console_message(format!("World &a\"{}\"&r:", slug));
+    sender.send_console_message(format!(" &7generator: &8{}", generator_settings.get_method()));
+    sender.send_console_message(format!(" &7seed: &8{}", generator_settings.get_seed()));
+    sender.send_console_message(format!(" &7spawn: &8{}", world_manager.get_spawn_position()));
+    sender.send_console_message(format!(" &7loaded chunks: &8{}", world_manager.get_chunks_count()));
+    sender.send_console_message(format!(" &7players: &8{}", players_count));
+    sender.send_console_message(format!(" &7storage size: &8{}", storage_size));
+    Ok(())
+}
+
+/// All of x y z or none of them
+fn get_setspawn_position(args: &CommandMatch) -> Result<Option<Position>, String> {
+    let coordinates = ["x", "y", "z"].map(|name| args.get_arg::<String, _>(name).ok());
+    let usage = "&cUsage: world setspawn [world] [x y z]".to_string();
+    let [Some(x), Some(y), Some(z)] = coordinates else {
+        return match coordinates.iter().all(|c| c.is_none()) {
+            true => Ok(None),
+            false => Err(usage),
+        };
+    };
+    match (x.parse::<f32>(), y.parse::<f32>(), z.parse::<f32>()) {
+        (Ok(x), Ok(y), Ok(z)) => Ok(Some(Position::new(x, y, z))),
+        _ => Err(usage),
+    }
+}
+
+/// Without coordinates the current position of the player who sent the command is used
+fn world_setspawn(world: &mut World, sender: &Box<dyn ConsoleSenderType>, args: &CommandMatch) -> Result<(), String> {
+    let sender_location = match sender.as_any().downcast_ref::<Client>() {
+        Some(client) => client.get_world_entity(),
+        None => None,
+    };
+
+    let world_slug = match args.get_arg::<String, _>("world") {
+        Ok(slug) => slug.clone(),
+        Err(_) => match sender_location.as_ref() {
+            Some(world_entity) => world_entity.get_world_slug().clone(),
+            None => {
+                sender.send_console_message("&cWorld slug is required".to_string());
+                return Ok(());
+            }
+        },
+    };
+
+    let worlds_manager = world.resource::<SharedWorldsManager>();
+    let worlds_manager = worlds_manager.read();
+    let Some(mut world_manager) = worlds_manager.get_world_manager_mut(&world_slug) else {
+        sender.send_console_message(format!("&cWorld \"{}\" not found", world_slug));
+        return Ok(());
+    };
+
+    let position = match get_setspawn_position(args) {
+        Ok(Some(position)) => position,
+        Err(e) => {
+            sender.send_console_message(e);
+            return Ok(());
+        }
+        Ok(None) => {
+            let sender_position = sender_location
+                .filter(|world_entity| world_entity.get_world_slug() == &world_slug)
+                .and_then(|world_entity| world_manager.get_entity_location(world_entity.get_entity()));
+            match sender_position {
+                Some((position, _rotation)) => position,
+                None => {
+                    sender.send_console_message("&cCoordinates x y z are required".to_string());
+                    return Ok(());
+                }
+            }
+        }
+    };
+
+    world_manager.set_spawn_position(position)?;
+    sender.send_console_message(format!("World &a\"{}\"&r spawn point set to {}", world_slug, position));
+    Ok(())
+}
+
+/// Chunks radius of `world pregen`
+const PREGEN_MAX_RADIUS: u16 = 1000;
+
+const PREGEN_USAGE: &str =
+    "&cUsage: world pregen <slug> <radius> [x,z] | world pregen status | world pregen cancel <slug>";
+
+/// Block position `x,z` of the pregeneration center
+fn parse_pregen_center(center: &str) -> Option<ChunkPosition> {
+    let (x, z) = center.split_once(',')?;
+    let x = x.trim().parse::<f32>().ok()?;
+    let z = z.trim().parse::<f32>().ok()?;
+    Some(Position::new(x, 0.0, z).get_chunk_position())
+}
+
+/// `world pregen <slug> <radius> [x,z]` generates chunks around the block position
+/// or the world spawn; `world pregen status` and `world pregen cancel <slug>`
+fn world_pregen(world: &mut World, sender: &Box<dyn ConsoleSenderType>, args: &CommandMatch) -> Result<(), String> {
+    let server_data_path = world.resource::<LaunchSettings>().get_server_data_path();
+    let target = args.get_arg::<String, _>("slug")?;
+    let radius = args.get_arg::<String, _>("radius").ok();
+    let center = args.get_arg::<String, _>("center").ok();
+
+    match (target.as_str(), radius, center) {
+        (slug, Some(radius), center) if slug != "cancel" => {
+            let slug = slug.to_string();
+            let Ok(radius) = radius.parse::<u16>() else {
+                sender.send_console_message(PREGEN_USAGE.to_string());
+                return Ok(());
+            };
+            if radius == 0 || radius > PREGEN_MAX_RADIUS {
+                sender.send_console_message(format!("&cRadius must be between 1 and {}", PREGEN_MAX_RADIUS));
+                return Ok(());
+            }
+
+            let center = {
+                let worlds_manager = world.resource::<SharedWorldsManager>().read();
+                let Some(world_manager) = worlds_manager.get_world_manager(&slug) else {
+                    sender.send_console_message(format!("&cWorld \"{}\" is not loaded", slug));
+                    return Ok(());
+                };
+                match center.map(|center| parse_pregen_center(&center)) {
+                    Some(Some(center)) => center,
+                    Some(None) => {
+                        sender.send_console_message(PREGEN_USAGE.to_string());
+                        return Ok(());
+                    }
+                    None => world_manager.get_spawn_position().get_chunk_position(),
+                }
+            };
+
+            let mut pregen = world.resource_mut::<PregenManager>();
+            match pregen.start(&server_data_path, &slug, center, radius) {
+                Ok(task) => {
+                    let message = match task.get_processed() {
+                        0 => "started",
+                        _ => "resumed",
+                    };
+                    sender.send_console_message(format!(
+                        "Pregeneration of the world &a\"{}\"&r {}; &8center:&7{} &8radius:&7{} &8chunks:&7{}/{}",
+                        slug,
+                        message,
+                        task.get_center(),
+                        task.get_radius(),
+                        task.get_processed(),
+                        task.get_total()
+                    ));
+                }
+                Err(e) => sender.send_console_message(format!("&cPregeneration error: {}", e)),
+            }
+        }
+        ("status", None, None) => {
+            let pregen = world.resource::<PregenManager>();
+            let mut tasks: Vec<_> = pregen.iter_tasks().collect();
+            if tasks.is_empty() {
+                sender.send_console_message("No pregeneration is running".to_string());
+                return Ok(());
+            }
+            tasks.sort_by(|a, b| a.get_world_slug().cmp(b.get_world_slug()));
+            for task in tasks {
+                let processed = task.get_processed();
+                let (in_flight, max_in_flight) = task.get_in_flight();
+                sender.send_console_message(format!(
+                    " - &a{}&r: &e{}/{}&r ({:.1}%) &8generated:&7{} &8in flight:&7{}/{} &8elapsed:&7{:.0?}",
+                    task.get_world_slug(),
+                    processed,
+                    task.get_total(),
+                    processed as f64 * 100.0 / task.get_total().max(1) as f64,
+                    task.get_generated(),
+                    in_flight,
+                    max_in_flight,
+                    task.get_elapsed()
+                ));
+            }
+        }
+        ("cancel", Some(slug), None) => match world.resource_mut::<PregenManager>().cancel(&server_data_path, &slug) {
+            Some(task) => sender.send_console_message(format!(
+                "Pregeneration of the world &a\"{}\"&r cancelled; &8generated:&7{}",
+                slug,
+                task.get_generated()
+            )),
+            None => sender.send_console_message(format!("&cPregeneration of the world \"{}\" is not running", slug)),
+        },
+        _ => {
+            sender.send_console_message(PREGEN_USAGE.to_string());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{command_parser_world, get_setspawn_position, parse_pregen_center};
+    use crate::{console::commands_executer::eval_command, entities::entity::Position};
+    use common::chunks::block_position::BlockPositionTrait;
+
+    #[test]
+    fn test_world_args() {
+        let args = eval_command(command_parser_world(), "world create caves flat 42").unwrap();
+        let create = args.subcommand().unwrap();
+        assert_eq!(create.get_name(), "create");
+        assert_eq!(create.get_arg::<String, _>("slug").unwrap(), "caves");
+        assert_eq!(create.get_arg::<String, _>("generator").unwrap(), "flat");
+        assert_eq!(create.get_arg::<String, _>("seed").unwrap(), "42");
+
+        let args = eval_command(command_parser_world(), "world create caves").unwrap();
+        assert!(args.subcommand().unwrap().get_arg::<String, _>("generator").is_err());
+
+        for subcommand in ["load", "unload", "delete", "info"] {
+            let args = eval_command(command_parser_world(), &format!("world {} caves", subcommand)).unwrap();
+            let world_args = args.subcommand().unwrap();
+            assert_eq!(world_args.get_name(), subcommand);
+            assert_eq!(world_args.get_arg::<String, _>("slug").unwrap(), "caves");
+            assert!(eval_command(command_parser_world(), &format!("world {}", subcommand)).is_err());
+        }
+        assert!(eval_command(command_parser_world(), "world").is_err());
+    }
+
+    #[test]
+    fn test_setspawn_position() {
+        let position = |command: &str| get_setspawn_position(&eval_command(command_parser_world(), command).unwrap());
+
+        assert!(position("world setspawn").unwrap().is_none());
+        assert!(position("world setspawn default").unwrap().is_none());
+        assert!(position("world setspawn default 1 64.5 3").unwrap() == Some(Position::new(1.0, 64.5, 3.0)));
+
+        // Partial or broken coordinates are not replaced by the sender position
+        assert!(position("world setspawn default 1").is_err());
+        assert!(position("world setspawn default 1 64").is_err());
+        assert!(position("world setspawn default 1 a 3").is_err());
+    }
+
+    #[test]
+    fn test_pregen_args() {
+        let args = eval_command(command_parser_world(), "world pregen default 10 100,-20").unwrap();
+        let pregen = args.subcommand().unwrap();
+        assert_eq!(pregen.get_name(), "pregen");
+        assert_eq!(pregen.get_arg::<String, _>("slug").unwrap(), "default");
+        assert_eq!(pregen.get_arg::<String, _>("radius").unwrap(), "10");
+        assert_eq!(pregen.get_arg::<String, _>("center").unwrap(), "100,-20");
+
+        assert!(eval_command(command_parser_world(), "world pregen status").is_ok());
+        assert!(eval_command(command_parser_world(), "world pregen cancel default").is_ok());
+
+        let center = Position::new(100.0, 0.0, -20.0).get_chunk_position();
+        assert_eq!(parse_pregen_center("100,-20"), Some(center));
+        assert_eq!(parse_pregen_center("100, -20"), Some(center));
+        assert_eq!(parse_pregen_center("100"), None);
+        assert_eq!(parse_pregen_center("a,b"), None);
+    }
 }
@@ -1,5 +1,5 @@
 use ahash::AHashMap;
-use bevy::prelude::{Bundle, Component, Entity, EntityRef, EntityWorldMut, QueryState, World};
+use bevy::prelude::{Bundle, Component, Entity, EntityRef, EntityWorldMut, QueryState, With, World};
 use bevy_ecs::{change_detection::Mut, component::Mutable, query::QueryData};
 use common::{chunks::chunk_position::ChunkPosition, utils::vec_remove_item};
 
@@ -73,6 +73,15 @@ impl Ecs {
         self.ecs.entity_mut(entity)
     }
 
+    /// All entities which have the component
+    pub fn get_entities_with<T: Component>(&self) -> Vec<Entity> {
+        // Component is not registered until the first entity with it is spawned
+        match self.ecs.try_query_filtered::<Entity, With<T>>() {
+            Some(mut query) => query.iter(&self.ecs).collect(),
+            None => Vec::new(),
+        }
+    }
+
     pub fn _query<D: QueryData>(&mut self) -> QueryState<D, ()> {
         self.ecs.query::<D>()
     }
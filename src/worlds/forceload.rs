@@ -0,0 +1,82 @@
+use bevy::prelude::Resource;
+use common::chunks::chunk_position::ChunkPosition;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use crate::utils::yaml_file::{YamlFile, YamlFileResource};
+
+pub const FORCELOAD_FILE: &str = "forceload.yml";
+
+/// Chunks kept loaded by the `forceload` command.
+///
+/// Stored in `forceload.yml` inside the server data folder by world slug.
+#[derive(Resource, Default)]
+pub struct ForcedChunks {
+    chunks: YamlFile<BTreeMap<String, BTreeSet<(i64, i64)>>>,
+
+    /// Increased on every change so worlds can apply it once
+    revision: u64,
+}
+
+impl YamlFileResource for ForcedChunks {
+    const SPAN: &'static str = "worlds.load_forced_chunks";
+    const LOG_TARGET: &'static str = "worlds";
+
+    fn load(server_data_path: &Path) -> Result<Self, String> {
+        Ok(Self {
+            chunks: YamlFile::load(server_data_path, FORCELOAD_FILE)?,
+            revision: 1,
+        })
+    }
+}
+
+impl ForcedChunks {
+    /// Returns the number of newly forced chunks
+    pub fn add(&mut self, world_slug: &String, chunks: &Vec<ChunkPosition>) -> Result<usize, String> {
+        let world_chunks = self.chunks.entry(world_slug.clone()).or_default();
+        let added = chunks.iter().filter(|c| world_chunks.insert((c.x, c.z))).count();
+        if added > 0 {
+            self.revision += 1;
+            self.chunks.save()?;
+        }
+        Ok(added)
+    }
+
+    /// Returns the number of released chunks
+    pub fn remove(&mut self, world_slug: &String, chunks: &Vec<ChunkPosition>) -> Result<usize, String> {
+        let Some(world_chunks) = self.chunks.get_mut(world_slug) else {
+            return Ok(0);
+        };
+        let removed = chunks.iter().filter(|c| world_chunks.remove(&(c.x, c.z))).count();
+        if world_chunks.is_empty() {
+            self.chunks.remove(world_slug);
+        }
+        if removed > 0 {
+            self.revision += 1;
+            self.chunks.save()?;
+        }
+        Ok(removed)
+    }
+
+    /// Forgets chunks of the deleted world
+    pub fn remove_world(&mut self, world_slug: &String) -> Result<(), String> {
+        if self.chunks.remove(world_slug).is_some() {
+            self.revision += 1;
+            self.chunks.save()?;
+        }
+        Ok(())
+    }
+
+    pub fn get_world_chunks(&self, world_slug: &String) -> Vec<ChunkPosition> {
+        match self.chunks.get(world_slug) {
+            Some(chunks) => chunks.iter().map(|(x, z)| ChunkPosition::new(*x, *z)).collect(),
+            None => Default::default(),
+        }
+    }
+
+    pub fn get_revision(&self) -> u64 {
+        self.revision
+    }
+}
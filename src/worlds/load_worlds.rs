@@ -13,6 +13,7 @@ use crate::{
     launch_settings::LaunchSettings,
     plugins::{plugins_manager::PluginsManager, server_settings::ServerSettings},
     runtime_plugin::RuntimePlugin,
+    server_config::ServerConfig,
 };
 
 use super::worlds_manager::{SharedWorldsManager, WorldsManager};
@@ -22,6 +23,7 @@ pub(crate) fn load_worlds(
     worlds_manager: Res<SharedWorldsManager>,
     server_settings: Res<ServerSettings>,
     plugins_manager: Res<PluginsManager>,
+    server_config: Res<ServerConfig>,
 ) {
     if RuntimePlugin::is_stopped() {
         return;
@@ -41,49 +43,22 @@ pub(crate) fn load_worlds(
     };
 
     for world_data in worlds_info.iter() {
-        if !plugins_manager.has_world_generator(world_data.get_world_generator()) {
-            log::error!(target: "worlds", "&cWorld generator \"{}\" not found to load \"{}\" world!", world_data.get_world_generator(), world_data.get_slug());
-            RuntimePlugin::stop();
-            return;
-        }
-
-        let world_generator_settings = WorldGeneratorSettings::from(world_data);
-
-        let world_storage = match WorldStorageManager::init(storage_settings.clone(), world_data.get_slug().clone()) {
-            Ok(s) => s,
-            Err(e) => {
-                log::error!(target: "worlds", "&cWorld storage init error!");
-                log::error!(target: "worlds", "&4Error: &c{}", e);
-                RuntimePlugin::stop();
-                return;
-            }
-        };
-        if let Err(e) = world_storage.validate_block_id_map(server_settings.get_block_id_map()) {
-            log::error!(target: "worlds", "&cWorld validate_block_id_map error!");
-            log::error!(target: "worlds", "&4Error: &c{}", e);
-            RuntimePlugin::stop();
-            return;
-        }
-
-        let create_result = worlds_manager.write().create_world(
-            world_data.get_slug().clone(),
-            world_storage,
-            world_generator_settings.clone(),
-        );
-        if let Err(e) = create_result {
-            log::error!(target: "worlds", "&cWorld create error!");
+        if let Err(e) = load_world(
+            world_data,
+            &storage_settings,
+            &*server_settings,
+            &*plugins_manager,
+            &*worlds_manager.read(),
+        ) {
+            log::error!(target: "worlds", "&cWorld &4\"{}\"&c load error!", world_data.get_slug());
             log::error!(target: "worlds", "&4Error: &c{}", e);
             RuntimePlugin::stop();
             return;
         }
-        log::info!(
-            target: "worlds", "World &a\"{}\"&r loaded; &7generator: &8{} &7seed: &8{}",
-            world_data.get_slug(), world_generator_settings.get_method(), world_generator_settings.get_seed(),
-        );
     }
 
-    let default_world = "default".to_string();
-    let default_world_generator = "default".to_string();
+    let default_world = server_config.get_default_world().clone();
+    let default_world_generator = server_config.get_default_world_generator().clone();
 
     let mut worlds_manager_guard = worlds_manager.write();
     if worlds_manager_guard.count() == 0 && !worlds_manager_guard.has_world_with_slug(&default_world) {
@@ -109,7 +84,47 @@ pub(crate) fn load_worlds(
     }
 }
 
-fn create_new_world(
+/// Reads world info from the storage by slug
+pub(crate) fn scan_world(launch_settings: &LaunchSettings, slug: &String) -> Result<Option<WorldStorageData>, String> {
+    let storage_settings = StorageSettings::from_path(launch_settings.get_server_data_path());
+    let worlds_info = WorldStorageManager::scan_worlds(storage_settings)?;
+    Ok(worlds_info.into_iter().find(|world_data| world_data.get_slug() == slug))
+}
+
+pub(crate) fn load_world(
+    world_data: &WorldStorageData,
+    storage_settings: &StorageSettings,
+    server_settings: &ServerSettings,
+    plugins_manager: &PluginsManager,
+    worlds_manager: &WorldsManager,
+) -> Result<(), String> {
+    if !plugins_manager.has_world_generator(world_data.get_world_generator()) {
+        return Err(format!(
+            "World generator \"{}\" not found to load \"{}\" world",
+            world_data.get_world_generator(),
+            world_data.get_slug()
+        ));
+    }
+
+    let world_generator_settings = WorldGeneratorSettings::from(world_data);
+
+    let world_storage = match WorldStorageManager::init(storage_settings.clone(), world_data.get_slug().clone()) {
+        Ok(s) => s,
+        Err(e) => return Err(format!("World storage init error: {}", e)),
+    };
+    if let Err(e) = world_storage.validate_block_id_map(server_settings.get_block_id_map()) {
+        return Err(format!("World validate_block_id_map error: {}", e));
+    }
+
+    worlds_manager.create_world(world_data.get_slug().clone(), world_storage, world_data.clone())?;
+    log::info!(
+        target: "worlds", "World &a\"{}\"&r loaded; &7generator: &8{} &7seed: &8{}",
+        world_data.get_slug(), world_generator_settings.get_method(), world_generator_settings.get_seed(),
+    );
+    Ok(())
+}
+
+pub(crate) fn create_new_world(
     slug: String,
     seed: Option<u64>,
     method: String,
@@ -122,6 +137,13 @@ fn create_new_world(
         None => rand::thread_rng().gen(),
     };
 
+    if !plugins_manager.has_world_generator(&method) {
+        return Err(format!(
+            "&cWorld generator \"{}\" not found to create \"{}\" world!",
+            method, slug
+        ));
+    }
+
     let server_data_path = launch_settings.get_server_data_path();
     let storage_settings = StorageSettings::from_path(server_data_path);
     let world_storage = match WorldStorageManager::init(storage_settings.clone(), slug.clone()) {
@@ -131,14 +153,6 @@ fn create_new_world(
         }
     };
 
-    if !plugins_manager.has_world_generator(&method) {
-        RuntimePlugin::stop();
-        return Err(format!(
-            "&cWorld generator \"{}\" not found to create \"{}\" world!",
-            method, slug
-        ));
-    }
-
     let plugin = plugins_manager
         .get_world_generator(&method)
         .expect("world_generator is required");
@@ -156,7 +170,6 @@ fn create_new_world(
     let world_data = WorldStorageData::create(slug.clone(), seed, method.clone(), world_macro_data);
     world_storage.create_new(&world_data)?;
 
-    let world_generator_settings = WorldGeneratorSettings::from(&world_data);
-    worlds_manager.create_world(slug.clone(), world_storage, world_generator_settings)?;
+    worlds_manager.create_world(slug.clone(), world_storage, world_data)?;
     Ok(())
 }
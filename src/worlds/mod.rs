@@ -1,3 +1,4 @@
+use bevy::time::common_conditions::on_timer;
 use bevy_app::{App, Plugin, Startup, Update};
 use bevy_ecs::schedule::IntoScheduleConfigs;
 pub mod commands;
@@ -5,21 +6,33 @@ pub mod load_worlds;
 
 use crate::{
     console::commands_executer::{CommandExecuter, CommandsHandler},
+    entities::{dropped_item::update_dropped_items, health::handle_player_deaths},
     plugins::server_settings::rescan_server_settings,
+    server_config::ServerConfig,
+    utils::yaml_file::load_yaml_file_resource,
 };
 use common::timed_lock;
 
 use self::{
-    console_commands::{command_parser_teleport, command_parser_world, command_teleport, command_world},
+    autosave::{autosave_worlds, flush_autosave_worlds},
+    console_commands::{
+        command_chunks, command_forceload, command_parser_chunks, command_parser_forceload, command_parser_save_all,
+        command_parser_world, command_save_all, command_world,
+    },
+    forceload::ForcedChunks,
+    pregen::{resume_pregen, update_pregen, PregenManager},
     worlds_manager::{update_world_chunks, SharedWorldsManager, WorldsManager},
 };
 use crate::plugins::server_plugin::host_functions::set_worlds_manager_bridge;
 use std::sync::Arc;
 
+pub mod autosave;
 pub mod chunks;
 pub mod console_commands;
 pub mod ecs;
+pub mod forceload;
 pub mod on_chunk_loaded;
+pub mod pregen;
 pub mod world_manager;
 pub mod worlds_manager;
 
@@ -30,7 +43,9 @@ impl Plugin for WorldsHandlerPlugin {
     fn build(&self, app: &mut App) {
         let mut commands_handler = app.world_mut().get_resource_mut::<CommandsHandler>().unwrap();
         commands_handler.add_command_executer(CommandExecuter::new(command_parser_world(), command_world));
-        commands_handler.add_command_executer(CommandExecuter::new(command_parser_teleport(), command_teleport));
+        commands_handler.add_command_executer(CommandExecuter::new(command_parser_save_all(), command_save_all));
+        commands_handler.add_command_executer(CommandExecuter::new(command_parser_forceload(), command_forceload));
+        commands_handler.add_command_executer(CommandExecuter::new(command_parser_chunks(), command_chunks));
 
         let worlds_manager =
             SharedWorldsManager::new(Arc::new(timed_lock!(WorldsManager::default(), "worlds_manager")));
@@ -38,8 +53,24 @@ impl Plugin for WorldsHandlerPlugin {
         app.add_systems(Startup, register_worlds_manager_bridge);
 
         app.add_systems(Startup, load_worlds::load_worlds.after(rescan_server_settings));
+        app.insert_resource(ForcedChunks::default());
+        app.add_systems(
+            Startup,
+            load_yaml_file_resource::<ForcedChunks>.before(load_worlds::load_worlds),
+        );
         app.add_systems(Update, update_world_chunks);
-        app.add_systems(Update, on_chunk_loaded::on_chunk_loaded);
+
+        app.insert_resource(PregenManager::default());
+        app.add_systems(Startup, resume_pregen.after(load_worlds::load_worlds));
+        app.add_systems(Update, update_pregen);
+        app.add_systems(Update, on_chunk_loaded::on_chunk_loaded.after(update_world_chunks));
+        app.add_systems(Update, update_dropped_items.after(update_world_chunks));
+        app.add_systems(Update, handle_player_deaths.after(update_world_chunks));
+
+        if let Some(interval) = app.world().resource::<ServerConfig>().get_autosave_interval() {
+            app.add_systems(Update, autosave_worlds.run_if(on_timer(interval)));
+            app.add_systems(Update, flush_autosave_worlds);
+        }
     }
 }
 
@@ -9,7 +9,7 @@ use crate::{
 use super::worlds_manager::SharedWorldsManager;
 
 /// Iterates trough all worlds
-/// and drain all their loaded chunks
+/// and spawns players waiting inside their loaded chunks
 pub fn on_chunk_loaded(
     worlds_manager: Res<SharedWorldsManager>,
     network_container: Res<NetworkContainer>,
@@ -22,12 +22,11 @@ pub fn on_chunk_loaded(
 
     let worlds_manager_guard = worlds_manager.read();
     for world in worlds_manager_guard.iter_worlds() {
-        let loaded_chunks = world.get_chunks_map().drain_loaded_chunks().collect::<Vec<_>>();
-        for chunk_position in loaded_chunks {
+        for chunk_position in world.get_loaded_chunks().iter() {
             let world_slug = world.get_slug().clone();
             let ecs = world.get_ecs();
 
-            'entity_loop: for entity in ecs.get_chunk_entities(&chunk_position).unwrap() {
+            'entity_loop: for entity in ecs.get_chunk_entities(chunk_position).unwrap() {
                 let Some(network) = entity.get::<Client>() else {
                     continue 'entity_loop;
                 };
@@ -0,0 +1,488 @@
+use ahash::AHashMap;
+use bevy::prelude::Resource;
+use bevy_ecs::system::{Res, ResMut};
+use common::{
+    chunks::chunk_position::ChunkPosition, utils::spiral_iterator::SpiralIterator,
+    world_generator::traits::WorldGeneratorSettings, worlds_storage::taits::IWorldStorage,
+};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use super::{
+    chunks::{chunk_generator::generate_chunk, chunks_map::StorageLock},
+    world_manager::WorldManager,
+    worlds_manager::SharedWorldsManager,
+};
+use crate::{
+    debug::tps_counter::TpsCounter, launch_settings::LaunchSettings, plugins::plugins_manager::PluginsManager,
+    plugins::server_plugin::plugin_instance::WASMPluginManager, runtime_plugin::RuntimePlugin,
+    server_config::ServerConfig, utils::yaml_file::YamlFile,
+};
+
+pub const PREGEN_FOLDER: &str = "pregen";
+
+/// How often the progress file is rewritten
+const PROGRESS_SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often the limit of generating chunks follows the TPS
+const THROTTLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Spiral positions checked per tick; most of them can be already loaded
+const MAX_DISPATCH_PER_TICK: usize = 256;
+
+/// Stored in `pregen/<world>.yml` inside the server data folder
+/// to continue the generation after restart
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PregenProgress {
+    center_x: i64,
+    center_z: i64,
+    radius: u16,
+
+    /// Spiral positions before this index are done
+    processed: usize,
+}
+
+impl PregenProgress {
+    pub fn new(center: ChunkPosition, radius: u16) -> Self {
+        Self {
+            center_x: center.x,
+            center_z: center.z,
+            radius,
+            processed: 0,
+        }
+    }
+
+    pub fn get_path(server_data_path: &PathBuf, world_slug: &String) -> PathBuf {
+        server_data_path.join(PREGEN_FOLDER).join(format!("{}.yml", world_slug))
+    }
+
+    fn is_same_area(&self, other: &PregenProgress) -> bool {
+        self.center_x == other.center_x && self.center_z == other.center_z && self.radius == other.radius
+    }
+}
+
+/// Generation of one world
+pub struct PregenTask {
+    world_slug: String,
+    progress: YamlFile<PregenProgress>,
+
+    iter: SpiralIterator,
+    next_index: usize,
+    total: usize,
+
+    /// Current limit of chunks generating at the same time; follows the TPS
+    max_in_flight: usize,
+
+    /// Spiral indexes of chunks generating right now
+    in_flight: Arc<Mutex<BTreeSet<usize>>>,
+    generated: Arc<AtomicUsize>,
+    error: Arc<RwLock<Option<String>>>,
+
+    started: Instant,
+    last_save: Instant,
+    last_throttle: Instant,
+}
+
+impl PregenTask {
+    fn new(world_slug: String, progress: YamlFile<PregenProgress>) -> Self {
+        let radius = progress.radius as i64;
+        let total = SpiralIterator::new(progress.center_x, progress.center_z, radius).count();
+        let mut iter = SpiralIterator::new(progress.center_x, progress.center_z, radius);
+        if progress.processed > 0 {
+            iter.nth(progress.processed - 1);
+        }
+        Self {
+            world_slug,
+            next_index: progress.processed.min(total),
+            progress,
+            iter,
+            total,
+            max_in_flight: 1,
+            in_flight: Default::default(),
+            generated: Default::default(),
+            error: Default::default(),
+            started: Instant::now(),
+            last_save: Instant::now(),
+            last_throttle: Instant::now(),
+        }
+    }
+
+    pub fn get_world_slug(&self) -> &String {
+        &self.world_slug
+    }
+
+    pub fn get_total(&self) -> usize {
+        self.total
+    }
+
+    pub fn get_center(&self) -> ChunkPosition {
+        ChunkPosition::new(self.progress.center_x, self.progress.center_z)
+    }
+
+    pub fn get_radius(&self) -> u16 {
+        self.progress.radius
+    }
+
+    /// Spiral positions before this one are done
+    pub fn get_processed(&self) -> usize {
+        match self.in_flight.lock().first() {
+            Some(index) => *index,
+            None => self.next_index,
+        }
+    }
+
+    pub fn get_generated(&self) -> usize {
+        self.generated.load(Ordering::Relaxed)
+    }
+
+    pub fn get_in_flight(&self) -> (usize, usize) {
+        (self.in_flight.lock().len(), self.max_in_flight)
+    }
+
+    pub fn get_elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.next_index >= self.total && self.in_flight.lock().is_empty()
+    }
+
+    fn save_progress(&mut self) -> Result<(), String> {
+        self.progress.processed = self.get_processed();
+        self.last_save = Instant::now();
+        self.progress.save()
+    }
+
+    /// Sends the next chunks of the spiral to generate on the rayon pool
+    fn dispatch(&mut self, world_manager: &WorldManager, plugin: Arc<WASMPluginManager>) {
+        let chunks_map = world_manager.get_chunks_map();
+        for _ in 0..MAX_DISPATCH_PER_TICK {
+            if self.in_flight.lock().len() >= self.max_in_flight {
+                return;
+            }
+            let Some((x, z)) = self.iter.next() else {
+                return;
+            };
+            let index = self.next_index;
+            self.next_index += 1;
+
+            let chunk_position = ChunkPosition::new(x, z);
+
+            // Loaded chunks are saved by the world itself
+            if chunks_map.get_chunk_column_arc(&chunk_position).is_some() {
+                continue;
+            }
+
+            self.in_flight.lock().insert(index);
+            let in_flight = self.in_flight.clone();
+            let generated = self.generated.clone();
+            let error = self.error.clone();
+            let storage = chunks_map.get_storage();
+            let world_generator_settings = chunks_map.get_world_generator_settings().clone();
+            let plugin = plugin.clone();
+            rayon::spawn(move || {
+                match pregen_chunk(&plugin, world_generator_settings, &storage, chunk_position) {
+                    Ok(true) => {
+                        generated.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Ok(false) => (),
+                    Err(e) => {
+                        *error.write() = Some(format!("chunk {}: {}", chunk_position, e));
+                    }
+                }
+                in_flight.lock().remove(&index);
+            });
+        }
+    }
+}
+
+/// Generates the chunk and writes it to the storage if it's not there yet
+///
+/// Returns true if the chunk was generated
+fn pregen_chunk(
+    plugin: &WASMPluginManager,
+    world_generator_settings: WorldGeneratorSettings,
+    storage: &StorageLock,
+    chunk_position: ChunkPosition,
+) -> Result<bool, String> {
+    if RuntimePlugin::is_stopped() {
+        return Ok(false);
+    }
+    if storage.read().has_chunk_data(&chunk_position)?.is_some() {
+        return Ok(false);
+    }
+
+    let chunk_storage = generate_chunk(plugin, world_generator_settings, chunk_position)?;
+
+    // The chunk could be loaded and saved by the world in the meantime
+    if storage.read().has_chunk_data(&chunk_position)?.is_some() {
+        return Ok(false);
+    }
+    storage.read().save_chunk_data(&chunk_position, &chunk_storage)?;
+    Ok(true)
+}
+
+/// Halves the limit of generating chunks when the server can't keep the target TPS
+/// (down to a pause) and raises it back one by one up to the pool size
+fn throttle(max_in_flight: usize, tps: f32, target_tps: f64, pool_size: usize) -> usize {
+    // TPS is not measured yet
+    if tps <= 0.0 {
+        return max_in_flight.min(pool_size);
+    }
+
+    let ratio = tps as f64 / target_tps;
+    if ratio < 0.9 {
+        max_in_flight / 2
+    } else if ratio >= 0.98 {
+        (max_in_flight + 1).min(pool_size)
+    } else {
+        max_in_flight.min(pool_size)
+    }
+}
+
+/// Chunk pre-generation tasks by world slug
+#[derive(Resource, Default)]
+pub struct PregenManager {
+    tasks: AHashMap<String, PregenTask>,
+}
+
+impl PregenManager {
+    /// Starts the generation of the world area or continues it
+    /// if the progress file contains the same area
+    pub fn start(
+        &mut self,
+        server_data_path: &PathBuf,
+        world_slug: &String,
+        center: ChunkPosition,
+        radius: u16,
+    ) -> Result<&PregenTask, String> {
+        if self.tasks.contains_key(world_slug) {
+            return Err(format!(
+                "pregeneration of the world \"{}\" is already running",
+                world_slug
+            ));
+        }
+
+        let path = PregenProgress::get_path(server_data_path, world_slug);
+        let mut progress = YamlFile::new(path.clone(), PregenProgress::new(center, radius));
+        if let Some(stored) = YamlFile::<PregenProgress>::read(&path)? {
+            if stored.is_same_area(&progress) {
+                progress = stored;
+            }
+        }
+        progress.save()?;
+
+        let task = PregenTask::new(world_slug.clone(), progress);
+        Ok(self.tasks.entry(world_slug.clone()).or_insert(task))
+    }
+
+    /// Stops the generation and forgets its progress.
+    /// Chunks which are generating right now will be saved anyway.
+    pub fn cancel(&mut self, server_data_path: &PathBuf, world_slug: &String) -> Option<PregenTask> {
+        let path = PregenProgress::get_path(server_data_path, world_slug);
+        if path.exists() {
+            if let Err(e) = std::fs::remove_file(&path) {
+                log::error!(target: "worlds", "&cfile &4{}&c remove error: {}", path.display(), e);
+            }
+        }
+        self.tasks.remove(world_slug)
+    }
+
+    /// Stops the generation and keeps its progress, so the same area is resumed by the next start
+    pub fn stop(&mut self, world_slug: &String) -> Option<PregenTask> {
+        let mut task = self.tasks.remove(world_slug)?;
+        if !task.is_finished() {
+            if let Err(e) = task.save_progress() {
+                log::error!(target: "worlds", "{}", e);
+            }
+        }
+        Some(task)
+    }
+
+    pub fn iter_tasks(&self) -> impl Iterator<Item = &PregenTask> {
+        self.tasks.values()
+    }
+}
+
+/// Continues generations interrupted by the server stop
+pub(crate) fn resume_pregen(
+    mut pregen: ResMut<PregenManager>,
+    launch_settings: Res<LaunchSettings>,
+    worlds_manager: Res<SharedWorldsManager>,
+) {
+    let _s = crate::span!("worlds.resume_pregen");
+    let folder = launch_settings.get_server_data_path().join(PREGEN_FOLDER);
+    let Ok(entries) = std::fs::read_dir(&folder) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("yml") {
+            continue;
+        }
+        let Some(world_slug) = path.file_stem().and_then(|s| s.to_str()).map(|s| s.to_string()) else {
+            continue;
+        };
+        if !worlds_manager.read().has_world_with_slug(&world_slug) {
+            continue;
+        }
+
+        match YamlFile::<PregenProgress>::read(&path) {
+            Ok(Some(progress)) => {
+                let task = PregenTask::new(world_slug.clone(), progress);
+                log::info!(
+                    target: "worlds",
+                    "Pregeneration of the world &a\"{}\"&r resumed; &8chunks:&7{}/{}",
+                    world_slug, task.get_processed(), task.get_total()
+                );
+                pregen.tasks.insert(world_slug, task);
+            }
+            Ok(None) => (),
+            Err(e) => log::error!(target: "worlds", "{}", e),
+        }
+    }
+}
+
+pub(crate) fn update_pregen(
+    mut pregen: ResMut<PregenManager>,
+    worlds_manager: Res<SharedWorldsManager>,
+    plugins_manager: Res<PluginsManager>,
+    server_config: Res<ServerConfig>,
+    tps_counter: Option<Res<TpsCounter>>,
+) {
+    let _s = crate::span!("worlds.update_pregen");
+    if RuntimePlugin::is_stopped() || pregen.tasks.is_empty() {
+        return;
+    }
+
+    let tps = match tps_counter {
+        Some(c) => *c.get_tps(),
+        None => 0.0,
+    };
+    // One instance is always left for chunks requested by players
+    let pool_size = server_config.get_wasm_pool_size().saturating_sub(1).max(1);
+
+    let worlds_manager = worlds_manager.read();
+    let mut stopped: Vec<String> = Default::default();
+    for (world_slug, task) in pregen.tasks.iter_mut() {
+        if let Some(e) = task.error.read().clone() {
+            log::error!(target: "worlds", "&cPregeneration of the world &4\"{}\"&c stopped: {}", world_slug, e);
+            stopped.push(world_slug.clone());
+            continue;
+        }
+
+        let Some(world_manager) = worlds_manager.get_world_manager(world_slug) else {
+            log::warn!(target: "worlds", "Pregeneration of the world &e\"{}\"&r stopped: world is unloaded", world_slug);
+            stopped.push(world_slug.clone());
+            continue;
+        };
+        let Some(plugin) = plugins_manager.get_world_generator(&world_manager.get_world_generator()) else {
+            log::error!(target: "worlds", "&cPregeneration of the world &4\"{}\"&c stopped: world generator is not found", world_slug);
+            stopped.push(world_slug.clone());
+            continue;
+        };
+
+        if task.last_throttle.elapsed() >= THROTTLE_INTERVAL {
+            task.max_in_flight = throttle(task.max_in_flight, tps, server_config.get_target_tps(), pool_size);
+            task.last_throttle = Instant::now();
+        }
+        task.dispatch(&*world_manager, plugin);
+
+        if task.is_finished() {
+            log::info!(
+                target: "worlds",
+                "Pregeneration of the world &a\"{}\"&r completed; &8generated:&7{} &8chunks:&7{} &8(executed:{:.2?})",
+                world_slug, task.get_generated(), task.get_total(), task.get_elapsed()
+            );
+            let path = task.progress.get_path();
+            if let Err(e) = std::fs::remove_file(path) {
+                log::error!(target: "worlds", "&cfile &4{}&c remove error: {}", path.display(), e);
+            }
+            stopped.push(world_slug.clone());
+        } else if task.last_save.elapsed() >= PROGRESS_SAVE_INTERVAL {
+            if let Err(e) = task.save_progress() {
+                log::error!(target: "worlds", "{}", e);
+            }
+        }
+    }
+
+    for world_slug in stopped {
+        pregen.stop(&world_slug);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{throttle, PregenManager, PregenProgress, PregenTask};
+    use crate::utils::yaml_file::YamlFile;
+    use common::{chunks::chunk_position::ChunkPosition, utils::spiral_iterator::SpiralIterator};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_throttle() {
+        assert_eq!(throttle(4, 0.0, 60.0, 8), 4);
+        assert_eq!(throttle(4, 60.0, 60.0, 8), 5);
+        assert_eq!(throttle(8, 60.0, 60.0, 8), 8);
+        assert_eq!(throttle(4, 57.0, 60.0, 8), 4);
+        assert_eq!(throttle(4, 40.0, 60.0, 8), 2);
+        assert_eq!(throttle(1, 40.0, 60.0, 8), 0);
+        assert_eq!(throttle(0, 60.0, 60.0, 8), 1);
+    }
+
+    #[test]
+    fn test_resume_spiral() {
+        let mut progress = PregenProgress::new(ChunkPosition::new(3, -2), 4);
+        progress.processed = 10;
+        let mut task = PregenTask::new("default".to_string(), YamlFile::new(PathBuf::new(), progress));
+
+        let mut expected = SpiralIterator::new(3, -2, 4).skip(10);
+        assert_eq!(task.get_processed(), 10);
+        assert_eq!(task.iter.next(), expected.next());
+        assert_eq!(task.total, SpiralIterator::new(3, -2, 4).count());
+    }
+
+    #[test]
+    fn test_stop_keeps_progress() {
+        let server_data_path = std::env::temp_dir().join(format!("brilliance-pregen-{}", std::process::id()));
+        let world_slug = "default".to_string();
+        let center = ChunkPosition::new(0, 0);
+        let mut pregen = PregenManager::default();
+
+        pregen.start(&server_data_path, &world_slug, center, 4).unwrap();
+        pregen.tasks.get_mut(&world_slug).unwrap().next_index = 5;
+        let task = pregen.stop(&world_slug).unwrap();
+        assert_eq!(task.get_processed(), 5);
+        assert_eq!(pregen.iter_tasks().count(), 0);
+
+        // Unloaded world continues from the same chunk
+        assert_eq!(
+            pregen
+                .start(&server_data_path, &world_slug, center, 4)
+                .unwrap()
+                .get_processed(),
+            5
+        );
+
+        // Cancel forgets it
+        pregen.cancel(&server_data_path, &world_slug);
+        assert_eq!(
+            pregen
+                .start(&server_data_path, &world_slug, center, 4)
+                .unwrap()
+                .get_processed(),
+            0
+        );
+        pregen.cancel(&server_data_path, &world_slug);
+        let _ = std::fs::remove_dir_all(&server_data_path);
+    }
+}
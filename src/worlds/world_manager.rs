@@ -1,19 +1,26 @@
 use super::ecs::Ecs;
 use crate::clients::client::WorldEntity;
-use crate::entities::entity::{Position, Rotation};
+use crate::entities::dropped_item::{collect_dropped_items, take_chunk_dropped_items};
+use crate::entities::entity::{IntoServerPosition, Position, Rotation};
 use crate::entities::EntityComponent;
 use crate::inventory::inventory_manager::InventoryManager;
 use crate::plugins::server_plugin::plugin_instance::WASMPluginManager;
-use crate::worlds::chunks::chunks_map::ChunkMap;
-use crate::CHUNKS_DISTANCE;
+use crate::worlds::chunks::chunks_load_state::ChunkTicket;
+use crate::worlds::chunks::chunks_map::{ChunkMap, ChunksUpdateSettings};
+use crate::worlds::forceload::ForcedChunks;
 use bevy_ecs::bundle::Bundle;
+use bevy_ecs::entity::Entity;
 use common::chunks::block_position::BlockPositionTrait;
 use common::chunks::chunk_position::ChunkPosition;
 use common::world_generator::traits::WorldGeneratorSettings;
+use common::worlds_storage::taits::WorldStorageData;
 use common::WorldStorageManager;
 use std::sync::Arc;
 use std::time::Duration;
 
+/// Position where players appear when the world has no other place for them
+pub const DEFAULT_SPAWN_POSITION: Position = Position::new(0.0, 100.0, 0.0);
+
 pub struct ChunkChanged {
     pub old_chunk: ChunkPosition,
     pub new_chunk: ChunkPosition,
@@ -25,18 +32,31 @@ pub struct WorldManager {
     slug: String,
     ecs: Ecs,
     chunks_map: ChunkMap,
+
+    // World info stored inside the world storage
+    world_data: WorldStorageData,
+
+    // Spawn chunk and radius of the current spawn tickets
+    spawn_area: Option<(ChunkPosition, u16)>,
+
+    // Revision of ForcedChunks applied to the chunks map
+    forced_revision: u64,
+
+    // Chunks loaded during the current tick
+    loaded_chunks: Vec<ChunkPosition>,
 }
 
 impl WorldManager {
-    pub fn new(
-        slug: String,
-        world_storage: WorldStorageManager,
-        world_generator_settings: WorldGeneratorSettings,
-    ) -> Result<Self, String> {
+    pub fn new(slug: String, world_storage: WorldStorageManager, world_data: WorldStorageData) -> Result<Self, String> {
+        let world_generator_settings = WorldGeneratorSettings::from(&world_data);
         Ok(WorldManager {
             slug,
             ecs: Ecs::new(),
             chunks_map: ChunkMap::new(world_storage, world_generator_settings),
+            world_data,
+            spawn_area: None,
+            forced_revision: 0,
+            loaded_chunks: Default::default(),
         })
     }
 
@@ -45,6 +65,10 @@ impl WorldManager {
         world_generator_settings.get_method().clone()
     }
 
+    pub fn get_seed(&self) -> u64 {
+        self.chunks_map.get_world_generator_settings().get_seed()
+    }
+
     pub fn get_ecs(&self) -> &Ecs {
         &self.ecs
     }
@@ -69,11 +93,37 @@ impl WorldManager {
         self.get_chunks_map().count()
     }
 
+    pub fn get_world_data(&self) -> &WorldStorageData {
+        &self.world_data
+    }
+
+    pub fn get_spawn_position(&self) -> Position {
+        match self.world_data.get_spawn_position() {
+            Some(position) => position.to_server(),
+            None => DEFAULT_SPAWN_POSITION,
+        }
+    }
+
+    /// Changes the spawn point and writes it into the world storage
+    pub fn set_spawn_position(&mut self, position: Position) -> Result<(), String> {
+        self.world_data.set_spawn_position(Some(position.to_network()));
+        self.chunks_map.save_world_data(&self.world_data)
+    }
+
+    /// Current position and rotation of the entity inside this world
+    pub fn get_entity_location(&self, entity: Entity) -> Option<(Position, Rotation)> {
+        let entity_ref = self.ecs.get_entity(entity)?;
+        let position = entity_ref.get::<Position>()?;
+        let rotation = entity_ref.get::<Rotation>()?;
+        Some((*position, *rotation))
+    }
+
     pub fn spawn_player<B: Bundle>(
         &mut self,
         position: Position,
         bundle: B,
         components: Vec<EntityComponent>,
+        chunks_distance: u16,
     ) -> WorldEntity {
         let entity = self.get_ecs_mut().spawn(bundle, position.get_chunk_position());
 
@@ -91,12 +141,17 @@ impl WorldManager {
                             entity_ecs.insert(c);
                         }
                     }
+                    EntityComponent::Health(c) => {
+                        if let Some(c) = c {
+                            entity_ecs.insert(c);
+                        }
+                    }
                 }
             }
         }
 
         self.get_chunks_map_mut()
-            .start_chunks_render(entity, &position.get_chunk_position(), CHUNKS_DISTANCE);
+            .start_chunks_render(entity, &position.get_chunk_position(), chunks_distance);
 
         WorldEntity::new(self.get_slug().clone(), entity)
     }
@@ -109,6 +164,7 @@ impl WorldManager {
         world_entity: &WorldEntity,
         position: Position,
         rotation: Rotation,
+        chunks_distance: u16,
     ) -> Option<ChunkChanged> {
         let mut changed_chunks: Option<ChunkChanged> = None;
 
@@ -123,7 +179,7 @@ impl WorldManager {
                 world_entity.get_entity(),
                 &old_chunk,
                 &new_chunk,
-                CHUNKS_DISTANCE,
+                chunks_distance,
             );
             changed_chunks = Some(chunks);
         }
@@ -138,11 +194,53 @@ impl WorldManager {
         changed_chunks
     }
 
-    pub fn save(&self) -> Result<(), String> {
+    /// Re-creates the player's chunk tickets around his current chunk for the new view distance.
+    ///
+    /// Returns his abandoned and new chunks
+    pub fn player_resize_render(&mut self, world_entity: &WorldEntity, chunks_distance: u16) -> ChunkChanged {
+        let entity = world_entity.get_entity();
+        let chunk_position = self
+            .ecs
+            .get_entity(entity)
+            .unwrap()
+            .get::<Position>()
+            .expect("player inside world must have position")
+            .get_chunk_position();
+        self.chunks_map
+            .resize_chunks_render(entity, &chunk_position, chunks_distance)
+    }
+
+    /// Saves chunks changed since the last save
+    ///
+    /// Returns the number of written chunks
+    pub fn save(&self) -> Result<usize, String> {
         let now = std::time::Instant::now();
+        self.chunks_map.store_dropped_items(collect_dropped_items(&self.ecs));
         let count = self.chunks_map.save()?;
-        log::info!(target: "worlds", "World &a\"{}\"&r saved; &8chunks:&7{} &8(executed:{:.2?})", self.get_slug(), count, now.elapsed());
-        Ok(())
+        if count > 0 {
+            log::info!(target: "worlds", "World &a\"{}\"&r saved; &8chunks:&7{} &8(executed:{:.2?})", self.get_slug(), count, now.elapsed());
+        }
+        Ok(count)
+    }
+
+    /// Starts saving changed chunks in the background
+    ///
+    /// Returns false if the previous autosave is not finished yet
+    pub fn start_autosave(&self) -> bool {
+        self.chunks_map.store_dropped_items(collect_dropped_items(&self.ecs));
+        self.chunks_map.start_background_save()
+    }
+
+    /// Result of the finished autosave and its duration
+    pub fn poll_autosave(&self) -> Option<(Result<usize, String>, std::time::Duration)> {
+        self.chunks_map.poll_background_save()
+    }
+
+    /// Forgets inventories of the world before it will be removed from the server
+    ///
+    /// The world must be saved first
+    pub fn unload(&self, inventory_manager: &mut InventoryManager) {
+        self.chunks_map.unregister_inventories(inventory_manager);
     }
 
     pub fn despawn_player(&mut self, world_entity: &WorldEntity, inventory_manager: &mut InventoryManager) {
@@ -158,15 +256,54 @@ impl WorldManager {
         self.get_ecs_mut().despawn(world_entity.get_entity(), chunk_position);
     }
 
+    /// Chunks loaded during the current tick; filled by `update_world_chunks`
+    pub fn get_loaded_chunks(&self) -> &Vec<ChunkPosition> {
+        &self.loaded_chunks
+    }
+
+    pub(crate) fn set_loaded_chunks(&mut self, loaded_chunks: Vec<ChunkPosition>) {
+        self.loaded_chunks = loaded_chunks;
+    }
+
+    /// Moves spawn tickets if the spawn position or the radius was changed
+    pub fn update_spawn_area(&mut self, radius: u16) {
+        let spawn_area = (self.get_spawn_position().get_chunk_position(), radius);
+        if self.spawn_area.as_ref() == Some(&spawn_area) {
+            return;
+        }
+        self.chunks_map.set_spawn_area(&spawn_area.0, spawn_area.1);
+        self.spawn_area = Some(spawn_area);
+    }
+
+    /// Re-creates forced tickets if the list of forced chunks was changed
+    pub fn update_forced_chunks(&mut self, forced_chunks: &ForcedChunks) {
+        if self.forced_revision == forced_chunks.get_revision() {
+            return;
+        }
+        self.chunks_map.release_all_tickets(&ChunkTicket::Forced);
+        for chunk_position in forced_chunks.get_world_chunks(&self.slug) {
+            self.chunks_map.add_ticket(chunk_position, ChunkTicket::Forced, None);
+        }
+        self.forced_revision = forced_chunks.get_revision();
+    }
+
     /// Proxy for sending update_chunks
     pub fn update_chunks_state(
         &mut self,
         delta: Duration,
         wasm_plugin_manager: Arc<WASMPluginManager>,
         inventory_manager: &mut InventoryManager,
+        settings: &ChunksUpdateSettings,
     ) {
         let world_slug = self.get_slug().clone();
-        self.chunks_map
-            .update_chunks_state(delta, &world_slug, wasm_plugin_manager, inventory_manager);
+        let ecs = &mut self.ecs;
+        self.chunks_map.update_chunks_state(
+            delta,
+            &world_slug,
+            wasm_plugin_manager,
+            inventory_manager,
+            settings,
+            |chunk_position| take_chunk_dropped_items(ecs, chunk_position),
+        );
     }
 }
@@ -1,12 +1,17 @@
 use bevy::prelude::Resource;
 use bevy::time::Time;
 use bevy_ecs::system::Res;
-use common::{world_generator::traits::WorldGeneratorSettings, WorldStorageManager};
+use common::{worlds_storage::taits::WorldStorageData, WorldStorageManager};
 use dashmap::DashMap;
 
+use crate::entities::dropped_item::spawn_stored_dropped_items;
 use crate::inventory::SharedInventoryManager;
+use crate::items_manager::items_manager::SharedItemsManager;
+use crate::server_config::ServerConfig;
 use crate::{plugins::plugins_manager::PluginsManager, runtime_plugin::RuntimePlugin, utils::Shared};
 
+use super::chunks::chunks_map::merge_save_results;
+use super::forceload::ForcedChunks;
 use super::world_manager::WorldManager;
 
 type WorldsType = DashMap<String, WorldManager>;
@@ -32,24 +37,24 @@ impl WorldsManager {
         self.worlds.contains_key(slug)
     }
 
-    pub fn save_all(&self) -> Result<(), String> {
-        for entry in self.worlds.iter() {
-            entry.value().save()?;
-        }
-        Ok(())
+    /// Returns the number of written chunks of all worlds
+    ///
+    /// A failed world doesn't stop saving of the others; errors of all worlds are returned together
+    pub fn save_all(&self) -> Result<usize, String> {
+        merge_save_results(self.worlds.iter().map(|entry| entry.value().save()))
     }
 
     pub fn create_world(
         &self,
         slug: String,
         world_storage: WorldStorageManager,
-        world_generator_settings: WorldGeneratorSettings,
+        world_data: WorldStorageData,
     ) -> Result<(), String> {
         if self.worlds.contains_key(&slug) {
             return Err(format!("&cWorld with slug &4\"{}\"&c already exists", slug));
         }
 
-        let world = match WorldManager::new(slug.clone(), world_storage, world_generator_settings) {
+        let world = match WorldManager::new(slug.clone(), world_storage, world_data) {
             Ok(w) => w,
             Err(e) => return Err(format!("&cWorld &4\"{}\"&c error: {}", slug, e)),
         };
@@ -57,6 +62,10 @@ impl WorldsManager {
         Ok(())
     }
 
+    pub fn remove_world(&self, slug: &String) -> Option<WorldManager> {
+        self.worlds.remove(slug).map(|(_slug, world)| world)
+    }
+
     pub fn count(&self) -> usize {
         self.worlds.len()
     }
@@ -141,32 +150,42 @@ pub fn update_world_chunks(
     time: Res<Time>,
     plugins_manager: Res<PluginsManager>,
     inventory_manager: Res<SharedInventoryManager>,
+    server_config: Res<ServerConfig>,
+    forced_chunks: Res<ForcedChunks>,
+    items_manager: Res<SharedItemsManager>,
 ) {
     let mut inventory_manager = inventory_manager.write();
     let _s = crate::span!("worlds.update_world_chunks");
     if RuntimePlugin::is_stopped() {
         return;
     }
+    let settings = server_config.get_chunks_update_settings();
     let worlds_manager_guard = worlds_manager.read();
     for mut world in worlds_manager_guard.iter_worlds_mut() {
         let loaded_chunks = world.get_chunks_map().drain_loaded_chunks().collect::<Vec<_>>();
-        for chunk_position in loaded_chunks {
+        for chunk_position in loaded_chunks.iter() {
             let world_slug = world.get_slug().clone();
-            let Some(chunk_column_arc) = world.get_chunks_map().get_chunk_column_arc(&chunk_position) else {
+            let Some(chunk_column_arc) = world.get_chunks_map().get_chunk_column_arc(chunk_position) else {
                 continue;
             };
             let chunk_storage = chunk_column_arc.read().get_chunk_storage().clone();
             inventory_manager.state_mut().register_chunk_inventories(
                 world_slug.clone(),
-                chunk_position,
+                *chunk_position,
                 &chunk_storage,
             );
+            spawn_stored_dropped_items(&mut world, &items_manager.read(), &chunk_storage);
         }
+        world.set_loaded_chunks(loaded_chunks);
 
         let wasm_plugin_manager = plugins_manager
             .get_world_generator(&world.get_world_generator())
             .expect("world_generator is required");
 
-        world.update_chunks_state(time.delta(), wasm_plugin_manager, &mut inventory_manager);
+        let spawn_chunks_radius = server_config.get_spawn_chunks_radius(world.get_slug());
+        world.update_spawn_area(spawn_chunks_radius);
+        world.update_forced_chunks(&forced_chunks);
+
+        world.update_chunks_state(time.delta(), wasm_plugin_manager, &mut inventory_manager, &settings);
     }
 }